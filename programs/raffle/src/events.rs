@@ -0,0 +1,174 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{
+    ConsolationPool, EndTimeExtension, EntryType, PaymentType, PrizeType, RandomnessSource,
+};
+
+#[event]
+pub struct RaffleCreated {
+    pub raffle: Pubkey,
+    pub raffler: Pubkey,
+    pub prize: Pubkey,
+    pub prize_type: PrizeType,
+    pub entry_type: EntryType,
+    pub payment_type: PaymentType,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub end_time_extension: Option<EndTimeExtension>,
+    pub max_tickets: u32,
+    pub min_tickets: Option<u32>,
+    pub num_winners: u8,
+    pub randomness_source: RandomnessSource,
+    /// series this raffle is the latest round of
+    pub jackpot_series: Option<Pubkey>,
+    pub consolation: Option<ConsolationPool>,
+}
+
+#[event]
+pub struct TicketsPurchased {
+    pub raffle: Pubkey,
+    pub entrant: Pubkey,
+    pub amount: u32,
+    /// amount paid in the payment mint, withheld burn proceeds count as wrapped SOL.
+    /// Other NFT entries and stakes cost 0
+    pub cost: u64,
+    /// index of the first of the purchased tickets, the rest follow consecutively
+    pub first_ticket_index: u32,
+    /// end time after this purchase, later than before if it landed in the extension window
+    pub end_time: i64,
+}
+
+#[event]
+pub struct RandomnessRequested {
+    pub raffle: Pubkey,
+    pub request: Pubkey,
+    /// the request this one replaces after timing out
+    pub replaces: Option<Pubkey>,
+}
+
+/// a commit-reveal raffle's secret was revealed, the draw settles with the hash of target_slot
+#[event]
+pub struct SecretRevealed {
+    pub raffle: Pubkey,
+    pub secret: [u8; 32],
+    pub target_slot: u64,
+}
+
+/// a commit-reveal draw wasn't settled before its slot hash expired, entrants can claim refunds
+#[event]
+pub struct DrawExpired {
+    pub raffle: Pubkey,
+}
+
+#[event]
+pub struct RandomnessFulfilled {
+    pub raffle: Pubkey,
+    pub randomness: [u8; 32],
+    pub winning_tickets: Vec<u32>,
+    /// owners of the winning tickets, in the same order
+    pub winning_entrants: Vec<Pubkey>,
+}
+
+#[event]
+pub struct PrizeClaimed {
+    pub raffle: Pubkey,
+    pub winner: Pubkey,
+    /// None when the prize returned to the raffler
+    pub ticket_index: Option<u32>,
+    /// proceeds sent to the raffler's treasury
+    pub treasury_proceeds: u64,
+    /// proceeds share sent to the fees wallet
+    pub fee: u64,
+}
+
+#[event]
+pub struct BundlePrizeAdded {
+    pub raffle: Pubkey,
+    pub mint: Pubkey,
+    /// amount received into custody, including earlier deposits of the same mint
+    pub amount: u64,
+}
+
+#[event]
+pub struct BundlePrizeDelivered {
+    pub raffle: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub recipient: Pubkey,
+}
+
+#[event]
+pub struct JackpotRolled {
+    pub jackpot_series: Pubkey,
+    /// the round that ended without its pot being won
+    pub raffle: Pubkey,
+    pub next_raffle: Pubkey,
+    /// amount received by the next round's vault
+    pub amount: u64,
+    /// proceeds sent to the raffler's treasury
+    pub treasury_proceeds: u64,
+    /// proceeds share sent to the fees wallet
+    pub fee: u64,
+}
+
+#[event]
+pub struct ConsolationClaimed {
+    pub raffle: Pubkey,
+    /// the entrant, or the raffler authority taking back a pool nobody can claim
+    pub entrant: Pubkey,
+    /// non-winning tickets the share was paid for
+    pub tickets: u32,
+    pub amount: u64,
+}
+
+#[event]
+pub struct RaffleCancelled {
+    pub raffle: Pubkey,
+    /// the prize returned to the raffler authority
+    pub prize: Pubkey,
+    /// consolation pool returned to the raffler authority
+    pub consolation_returned: u64,
+}
+
+#[event]
+pub struct RefundClaimed {
+    pub raffle: Pubkey,
+    pub entrant: Pubkey,
+    pub tickets: u32,
+    /// tokens returned, 0 for NFT entries
+    pub amount: u64,
+    /// the NFT returned for NFT entries
+    pub nft: Option<Pubkey>,
+}
+
+#[event]
+pub struct StakeWithdrawn {
+    pub raffle: Pubkey,
+    pub entrant: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct NftCollected {
+    pub raffle: Pubkey,
+    /// the mint, or asset id for compressed NFTs
+    pub nft: Pubkey,
+    pub destination: Pubkey,
+}
+
+#[event]
+pub struct RafflerUpdated {
+    pub raffler: Pubkey,
+    pub authority: Pubkey,
+    pub treasury: Pubkey,
+    pub staker: Option<Pubkey>,
+    pub is_active: bool,
+}
+
+#[event]
+pub struct ConfigUpdated {
+    pub raffle_fee: u64,
+    pub proceeds_share: u16,
+    pub randomness_timeout: i64,
+}
@@ -0,0 +1,190 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::{
+    events::BundlePrizeAdded,
+    state::{BundleItem, PaymentType, PrizeBundle, Raffle, Raffler},
+    utils::{get_transfer_fee, transfer_checked_with_hooks},
+    RaffleError,
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AddBundlePrize<'info> {
+    #[account(
+        seeds = [
+            b"RAFFLE",
+            raffler.authority.as_ref(),
+            b"raffler"
+        ],
+        bump = raffler.bump,
+        has_one = authority @ RaffleError::Unauthorized
+    )]
+    pub raffler: Box<Account<'info, Raffler>>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"RAFFLE",
+            raffle.entrants.as_ref(),
+            b"raffle"
+        ],
+        bump = raffle.bump,
+        has_one = raffler
+    )]
+    pub raffle: Box<Account<'info, Raffle>>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = PrizeBundle::LEN,
+        seeds = [
+            b"RAFFLE",
+            raffle.key().as_ref(),
+            b"prize-bundle"
+        ],
+        bump
+    )]
+    pub prize_bundle: Box<Account<'info, PrizeBundle>>,
+
+    pub prize: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = prize,
+        associated_token::authority = authority,
+        associated_token::token_program = prize_token_program
+    )]
+    pub prize_token: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = prize,
+        associated_token::authority = raffle,
+        associated_token::token_program = prize_token_program
+    )]
+    pub prize_custody: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub prize_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> AddBundlePrize<'info> {
+    pub fn transfer_prize_ctx(&self) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        let cpi_accounts = TransferChecked {
+            from: self.prize_token.to_account_info(),
+            mint: self.prize.to_account_info(),
+            to: self.prize_custody.to_account_info(),
+            authority: self.authority.to_account_info(),
+        };
+
+        let cpi_program = self.prize_token_program.to_account_info();
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+}
+
+/// Deposits a token or NFT into the raffle's prize bundle. Depositing a mint already in
+/// the bundle tops up its amount. Transfer hook accounts are passed as remaining accounts.
+pub fn add_bundle_prize_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, AddBundlePrize<'info>>,
+    amount: u64,
+) -> Result<()> {
+    let current_time = Clock::get().unwrap().unix_timestamp;
+    let raffle = &ctx.accounts.raffle;
+    let prize = &ctx.accounts.prize;
+
+    require_gt!(raffle.start_time, current_time, RaffleError::BundleLocked);
+    require_eq!(
+        raffle.num_winners,
+        1,
+        RaffleError::BundleRequiresSingleWinner
+    );
+    require_gt!(amount, 0, RaffleError::InvalidPrize);
+
+    // custody is the raffle's ATA, so it can't be shared with the main prize or the proceeds
+    require_keys_neq!(prize.key(), raffle.prize, RaffleError::InvalidPrize);
+    if let PaymentType::Token { token_mint, .. } = raffle.payment_type {
+        require_keys_neq!(prize.key(), token_mint, RaffleError::InvalidPrize);
+    }
+    if let Some(consolation) = raffle.consolation {
+        require_keys_neq!(prize.key(), consolation.mint, RaffleError::InvalidPrize);
+    }
+
+    let transfer_fee = get_transfer_fee(&prize.to_account_info(), amount)?;
+
+    transfer_checked_with_hooks(
+        ctx.accounts
+            .transfer_prize_ctx()
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+        amount,
+        prize.decimals,
+    )?;
+
+    let received = amount
+        .checked_sub(transfer_fee)
+        .ok_or(RaffleError::ProgramSubError)?;
+
+    let prize_bundle = &mut ctx.accounts.prize_bundle;
+
+    // first deposit, the bundle was just created
+    if prize_bundle.raffle == Pubkey::default() {
+        ***prize_bundle = PrizeBundle::init(
+            ctx.accounts.raffle.key(),
+            ctx.accounts.raffle.entrants,
+            ctx.accounts.authority.key(),
+            ctx.accounts.raffle.bump,
+            ctx.bumps.prize_bundle,
+        );
+    }
+
+    match prize_bundle
+        .items
+        .iter_mut()
+        .find(|item| item.mint == prize.key())
+    {
+        Some(item) => {
+            item.amount = item
+                .amount
+                .checked_add(received)
+                .ok_or(RaffleError::ProgramAddError)?;
+        }
+        None => {
+            require_gt!(
+                PrizeBundle::MAX_ITEMS,
+                prize_bundle.items.len(),
+                RaffleError::BundleFull
+            );
+
+            prize_bundle.items.push(BundleItem {
+                mint: prize.key(),
+                amount: received,
+                delivered: false,
+            });
+        }
+    }
+
+    let amount = prize_bundle
+        .items
+        .iter()
+        .find(|item| item.mint == prize.key())
+        .map_or(0, |item| item.amount);
+
+    let raffle = &mut ctx.accounts.raffle;
+    raffle.bundle_items = prize_bundle.items.len() as u8;
+
+    emit_cpi!(BundlePrizeAdded {
+        raffle: raffle.key(),
+        mint: prize.key(),
+        amount,
+    });
+
+    Ok(())
+}
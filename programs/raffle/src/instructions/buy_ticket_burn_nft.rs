@@ -10,11 +10,13 @@ use anchor_spl::{
 use solana_program::system_instruction;
 
 use crate::{
-    state::{Entrants, EntryType, PaymentType, Raffle, Raffler},
+    events::TicketsPurchased,
+    state::{EntrantTally, Entrants, EntryType, PaymentType, Raffle, Raffler},
     utils::add_entrants,
     RaffleError, NATIVE_MINT,
 };
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct BuyTicketBurnNft<'info> {
     #[account(
@@ -27,8 +29,6 @@ pub struct BuyTicketBurnNft<'info> {
         bump = raffle.bump,
         has_one = raffler,
         has_one = entrants,
-        constraint = Clock::get().unwrap().unix_timestamp >= raffle.start_time @ RaffleError::NotStarted,
-        constraint = Clock::get().unwrap().unix_timestamp < raffle.end_time @ RaffleError::Ended,
     )]
     pub raffle: Box<Account<'info, Raffle>>,
 
@@ -43,7 +43,21 @@ pub struct BuyTicketBurnNft<'info> {
     pub raffler: Box<Account<'info, Raffler>>,
 
     #[account(mut)]
-    pub entrants: Box<Account<'info, Entrants>>,
+    pub entrants: AccountLoader<'info, Entrants>,
+
+    #[account(
+        init_if_needed,
+        payer = entrant,
+        space = EntrantTally::LEN,
+        seeds = [
+            b"RAFFLE",
+            raffle.key().as_ref(),
+            entrant.key().as_ref(),
+            b"entrant-tally"
+        ],
+        bump
+    )]
+    pub entrant_tally: Box<Account<'info, EntrantTally>>,
 
     #[account(
         mut,
@@ -228,7 +242,7 @@ pub fn buy_ticket_burn_nft_handler(ctx: Context<BuyTicketBurnNft>) -> Result<()>
         )
     }
 
-    match raffle.payment_type {
+    let cost = match raffle.payment_type {
         PaymentType::Token {
             token_mint: _,
             ticket_price: _,
@@ -282,19 +296,36 @@ pub fn buy_ticket_burn_nft_handler(ctx: Context<BuyTicketBurnNft>) -> Result<()>
                             .sync_native_purchaser_ctx()
                             .with_signer(&[authority_seed]),
                     )?;
+
+                    proceeds
+                } else {
+                    0
                 }
             }
             _ => return err!(RaffleError::InvalidInstruction),
         },
-        _ => {}
-    }
-
-    add_entrants(
-        &mut ctx.accounts.entrants,
+        // cNFT entries are bought with buy_ticket_cnft
+        PaymentType::Cnft { collection: _ } => return err!(RaffleError::InvalidInstruction),
+    };
+
+    let first_ticket_index = add_entrants(
+        &mut ctx.accounts.raffle,
+        &ctx.accounts.entrants,
+        &mut ctx.accounts.entrant_tally,
+        ctx.bumps.entrant_tally,
         ctx.accounts.entrant.to_account_info(),
         ctx.accounts.system_program.to_account_info(),
         1,
     )?;
 
+    emit_cpi!(TicketsPurchased {
+        raffle: ctx.accounts.raffle.key(),
+        entrant: ctx.accounts.entrant.key(),
+        amount: 1,
+        cost,
+        first_ticket_index,
+        end_time: ctx.accounts.raffle.end_time,
+    });
+
     Ok(())
 }
@@ -0,0 +1,288 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    metadata::{Metadata, MetadataAccount},
+    token::{Mint, TokenAccount},
+};
+use mpl_bubblegum::{
+    hash::{hash_creators, hash_metadata},
+    instructions::{BurnCpiBuilder, TransferCpiBuilder},
+    types::MetadataArgs,
+};
+
+use crate::{
+    events::TicketsPurchased,
+    state::{EntrantTally, Entrants, EntryType, PaymentType, Raffle, Raffler},
+    utils::add_entrants,
+    RaffleError, COMPRESSION_PROGRAM, NOOP_PROGRAM,
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct BuyTicketCnft<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"RAFFLE",
+            entrants.key().as_ref(),
+            b"raffle"
+        ],
+        bump = raffle.bump,
+        has_one = raffler,
+        has_one = entrants,
+    )]
+    pub raffle: Box<Account<'info, Raffle>>,
+
+    #[account(
+        seeds = [
+            b"RAFFLE",
+            raffler.authority.as_ref(),
+            b"raffler"
+        ],
+        bump = raffler.bump
+    )]
+    pub raffler: Box<Account<'info, Raffler>>,
+
+    #[account(mut)]
+    pub entrants: AccountLoader<'info, Entrants>,
+
+    #[account(
+        init_if_needed,
+        payer = entrant,
+        space = EntrantTally::LEN,
+        seeds = [
+            b"RAFFLE",
+            raffle.key().as_ref(),
+            entrant.key().as_ref(),
+            b"entrant-tally"
+        ],
+        bump
+    )]
+    pub entrant_tally: Box<Account<'info, EntrantTally>>,
+
+    /// CHECK: checked in CPI
+    #[account(mut)]
+    pub tree_config: AccountInfo<'info>,
+
+    /// CHECK: checked in CPI
+    #[account(mut)]
+    pub merkle_tree: AccountInfo<'info>,
+
+    /// CHECK: checked in CPI
+    pub leaf_delegate: AccountInfo<'info>,
+
+    pub gated_nft_mint: Option<Box<Account<'info, Mint>>>,
+
+    #[account(
+        seeds = [
+            b"metadata",
+            Metadata::id().as_ref(),
+            gated_nft_mint.as_ref().unwrap().key().as_ref()
+        ],
+        seeds::program = Metadata::id(),
+        bump,
+        constraint = match raffle.gated_collection {
+            Option::Some(val) => {
+                let coll = gated_nft_metadata.collection.as_ref().expect("Gated NFT collection not included");
+                val == coll.key && coll.verified
+            },
+            Option::None => true
+        }
+    )]
+    pub gated_nft_metadata: Option<Box<Account<'info, MetadataAccount>>>,
+
+    #[account(
+        associated_token::mint = gated_nft_mint,
+        associated_token::authority = entrant,
+        constraint = gated_nft_token.amount == 1 @ RaffleError::GatedRaffle
+    )]
+    pub gated_nft_token: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(mut)]
+    pub entrant: Signer<'info>,
+
+    /// CHECK: explicit address check
+    #[account(address = mpl_bubblegum::ID)]
+    pub bubblegum_program: AccountInfo<'info>,
+    /// CHECK: explicit address check
+    #[account(address = NOOP_PROGRAM)]
+    pub log_wrapper: AccountInfo<'info>,
+    /// CHECK: explicit address check
+    #[account(address = COMPRESSION_PROGRAM)]
+    pub compression_program: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> BuyTicketCnft<'info> {
+    fn transfer_cnft(
+        &self,
+        proof: &[AccountInfo<'info>],
+        root: [u8; 32],
+        data_hash: [u8; 32],
+        creator_hash: [u8; 32],
+        nonce: u64,
+        index: u32,
+    ) -> Result<()> {
+        let bubblegum_program = &self.bubblegum_program.to_account_info();
+        let tree_config = &self.tree_config.to_account_info();
+        let leaf_owner = &self.entrant.to_account_info();
+        let leaf_delegate = &self.leaf_delegate.to_account_info();
+        let new_leaf_owner = &self.raffle.to_account_info();
+        let merkle_tree = &self.merkle_tree.to_account_info();
+        let log_wrapper = &self.log_wrapper.to_account_info();
+        let compression_program = &self.compression_program.to_account_info();
+        let system_program = &self.system_program.to_account_info();
+
+        let proof_accounts: Vec<(&AccountInfo<'info>, bool, bool)> =
+            proof.iter().map(|node| (node, false, false)).collect();
+
+        let mut cpi_transfer = TransferCpiBuilder::new(bubblegum_program);
+
+        cpi_transfer
+            .tree_config(tree_config)
+            .leaf_owner(leaf_owner, true)
+            .leaf_delegate(leaf_delegate, false)
+            .new_leaf_owner(new_leaf_owner)
+            .merkle_tree(merkle_tree)
+            .log_wrapper(log_wrapper)
+            .compression_program(compression_program)
+            .system_program(system_program)
+            .root(root)
+            .data_hash(data_hash)
+            .creator_hash(creator_hash)
+            .nonce(nonce)
+            .index(index)
+            .add_remaining_accounts(&proof_accounts);
+
+        // performs the CPI
+        cpi_transfer.invoke()?;
+        Ok(())
+    }
+
+    fn burn_cnft(
+        &self,
+        proof: &[AccountInfo<'info>],
+        root: [u8; 32],
+        data_hash: [u8; 32],
+        creator_hash: [u8; 32],
+        nonce: u64,
+        index: u32,
+    ) -> Result<()> {
+        let bubblegum_program = &self.bubblegum_program.to_account_info();
+        let tree_config = &self.tree_config.to_account_info();
+        let leaf_owner = &self.entrant.to_account_info();
+        let leaf_delegate = &self.leaf_delegate.to_account_info();
+        let merkle_tree = &self.merkle_tree.to_account_info();
+        let log_wrapper = &self.log_wrapper.to_account_info();
+        let compression_program = &self.compression_program.to_account_info();
+        let system_program = &self.system_program.to_account_info();
+
+        let proof_accounts: Vec<(&AccountInfo<'info>, bool, bool)> =
+            proof.iter().map(|node| (node, false, false)).collect();
+
+        let mut cpi_burn = BurnCpiBuilder::new(bubblegum_program);
+
+        cpi_burn
+            .tree_config(tree_config)
+            .leaf_owner(leaf_owner, true)
+            .leaf_delegate(leaf_delegate, false)
+            .merkle_tree(merkle_tree)
+            .log_wrapper(log_wrapper)
+            .compression_program(compression_program)
+            .system_program(system_program)
+            .root(root)
+            .data_hash(data_hash)
+            .creator_hash(creator_hash)
+            .nonce(nonce)
+            .index(index)
+            .add_remaining_accounts(&proof_accounts);
+
+        cpi_burn.invoke()?;
+        Ok(())
+    }
+}
+
+pub fn buy_ticket_cnft_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, BuyTicketCnft<'info>>,
+    root: [u8; 32],
+    metadata_args: Vec<u8>,
+    nonce: u64,
+    index: u32,
+) -> Result<()> {
+    let raffle = &ctx.accounts.raffle;
+
+    if raffle.gated_collection.is_some() {
+        require!(
+            ctx.accounts.gated_nft_metadata.is_some(),
+            RaffleError::GatedRaffle
+        )
+    }
+
+    let collection = match raffle.payment_type {
+        PaymentType::Token {
+            token_mint: _,
+            ticket_price: _,
+        } => return err!(RaffleError::NftInstruction),
+        PaymentType::Cnft { collection } => collection,
+        _ => return err!(RaffleError::InvalidInstruction),
+    };
+
+    // the leaf hash commits to data_hash, so the CPI will reject metadata that doesn't match the tree
+    let metadata = MetadataArgs::try_from_slice(&metadata_args)
+        .map_err(|_| RaffleError::InvalidCnftMetadata)?;
+
+    let metadata_collection = metadata
+        .collection
+        .as_ref()
+        .ok_or(RaffleError::InvalidCollection)?;
+
+    require!(
+        metadata_collection.verified && metadata_collection.key == collection,
+        RaffleError::InvalidCollection
+    );
+
+    let data_hash = hash_metadata(&metadata).map_err(|_| RaffleError::InvalidCnftMetadata)?;
+    let creator_hash = hash_creators(&metadata.creators);
+
+    match raffle.entry_type {
+        EntryType::Spend => ctx.accounts.transfer_cnft(
+            ctx.remaining_accounts,
+            root,
+            data_hash,
+            creator_hash,
+            nonce,
+            index,
+        )?,
+        EntryType::Burn {
+            withold_burn_proceeds: false,
+        } => ctx.accounts.burn_cnft(
+            ctx.remaining_accounts,
+            root,
+            data_hash,
+            creator_hash,
+            nonce,
+            index,
+        )?,
+        _ => return err!(RaffleError::InvalidInstruction),
+    }
+
+    let first_ticket_index = add_entrants(
+        &mut ctx.accounts.raffle,
+        &ctx.accounts.entrants,
+        &mut ctx.accounts.entrant_tally,
+        ctx.bumps.entrant_tally,
+        ctx.accounts.entrant.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        1,
+    )?;
+
+    emit_cpi!(TicketsPurchased {
+        raffle: ctx.accounts.raffle.key(),
+        entrant: ctx.accounts.entrant.key(),
+        amount: 1,
+        cost: 0,
+        first_ticket_index,
+        end_time: ctx.accounts.raffle.end_time,
+    });
+
+    Ok(())
+}
@@ -9,11 +9,13 @@ use anchor_spl::{
 };
 
 use crate::{
-    state::{Entrants, EntryType, PaymentType, Raffle, Raffler},
+    events::TicketsPurchased,
+    state::{EntrantTally, Entrants, EntryType, NftEntry, PaymentType, Raffle, Raffler},
     utils::add_entrants,
     RaffleError,
 };
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct BuyTicketSendNft<'info> {
     #[account(
@@ -26,8 +28,6 @@ pub struct BuyTicketSendNft<'info> {
         bump = raffle.bump,
         has_one = raffler,
         has_one = entrants,
-        constraint = Clock::get().unwrap().unix_timestamp >= raffle.start_time @ RaffleError::NotStarted,
-        constraint = Clock::get().unwrap().unix_timestamp < raffle.end_time @ RaffleError::Ended,
     )]
     pub raffle: Box<Account<'info, Raffle>>,
 
@@ -42,7 +42,21 @@ pub struct BuyTicketSendNft<'info> {
     pub raffler: Box<Account<'info, Raffler>>,
 
     #[account(mut)]
-    pub entrants: Box<Account<'info, Entrants>>,
+    pub entrants: AccountLoader<'info, Entrants>,
+
+    #[account(
+        init_if_needed,
+        payer = entrant,
+        space = EntrantTally::LEN,
+        seeds = [
+            b"RAFFLE",
+            raffle.key().as_ref(),
+            entrant.key().as_ref(),
+            b"entrant-tally"
+        ],
+        bump
+    )]
+    pub entrant_tally: Box<Account<'info, EntrantTally>>,
 
     #[account(
         mint::decimals = 0,
@@ -66,6 +80,21 @@ pub struct BuyTicketSendNft<'info> {
     )]
     pub nft_destination: Option<Box<Account<'info, TokenAccount>>>,
 
+    /// records the sender of the NFT so it can be refunded, required if the raffle has min_tickets
+    #[account(
+        init,
+        payer = entrant,
+        space = NftEntry::LEN,
+        seeds = [
+            b"RAFFLE",
+            raffle.key().as_ref(),
+            nft_mint.as_ref().ok_or(RaffleError::NftMintNeeded)?.key().as_ref(),
+            b"nft-entry"
+        ],
+        bump
+    )]
+    pub nft_entry: Option<Box<Account<'info, NftEntry>>>,
+
     #[account(
         mut,
         seeds = [
@@ -204,22 +233,47 @@ pub fn buy_ticket_send_nft_handler(ctx: Context<BuyTicketSendNft>) -> Result<()>
                 raffle.entry_type,
                 EntryType::Burn {
                     withold_burn_proceeds: _
-                }
+                } | EntryType::Stake { minimum_period: _ }
             ) {
                 return err!(RaffleError::InvalidInstruction);
             } else {
                 ctx.accounts.transfer_nft()?;
             }
+
+            if raffle.min_tickets.is_some() {
+                let raffle_key = raffle.key();
+                let entrant = ctx.accounts.entrant.key();
+                let nft_mint = ctx.accounts.nft_mint.as_ref().unwrap().key();
+                let nft_entry = ctx
+                    .accounts
+                    .nft_entry
+                    .as_mut()
+                    .ok_or(RaffleError::NftEntryNeeded)?;
+
+                ***nft_entry = NftEntry::init(raffle_key, entrant, nft_mint, ctx.bumps.nft_entry);
+            }
         }
         _ => return err!(RaffleError::InvalidInstruction),
     }
 
-    add_entrants(
-        &mut ctx.accounts.entrants,
+    let first_ticket_index = add_entrants(
+        &mut ctx.accounts.raffle,
+        &ctx.accounts.entrants,
+        &mut ctx.accounts.entrant_tally,
+        ctx.bumps.entrant_tally,
         ctx.accounts.entrant.to_account_info(),
         ctx.accounts.system_program.to_account_info(),
         1,
     )?;
 
+    emit_cpi!(TicketsPurchased {
+        raffle: ctx.accounts.raffle.key(),
+        entrant: ctx.accounts.entrant.key(),
+        amount: 1,
+        cost: 0,
+        first_ticket_index,
+        end_time: ctx.accounts.raffle.end_time,
+    });
+
     Ok(())
 }
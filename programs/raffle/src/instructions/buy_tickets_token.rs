@@ -2,19 +2,22 @@ use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
     metadata::{Metadata, MetadataAccount},
-    token::{
-        burn, close_account, sync_native, transfer, Burn, CloseAccount, Mint, SyncNative, Token,
-        TokenAccount, Transfer,
+    token::{Mint, TokenAccount},
+    token_interface::{
+        self, burn, close_account, sync_native, Burn, CloseAccount, SyncNative, TokenInterface,
+        TransferChecked,
     },
 };
 use solana_program::system_instruction;
 
 use crate::{
-    state::{Entrants, EntryType, PaymentType, Raffle, Raffler},
-    utils::add_entrants,
+    events::TicketsPurchased,
+    state::{EntrantTally, Entrants, EntryType, PaymentType, Raffle, Raffler},
+    utils::{add_entrants, harvest_withheld_fees, transfer_checked_with_hooks},
     RaffleError, NATIVE_MINT,
 };
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct BuyTicketsToken<'info> {
     #[account(
@@ -27,8 +30,6 @@ pub struct BuyTicketsToken<'info> {
         bump = raffle.bump,
         has_one = raffler,
         has_one = entrants,
-        constraint = Clock::get().unwrap().unix_timestamp >= raffle.start_time @ RaffleError::NotStarted,
-        constraint = Clock::get().unwrap().unix_timestamp < raffle.end_time @ RaffleError::Ended,
     )]
     pub raffle: Box<Account<'info, Raffle>>,
 
@@ -43,7 +44,21 @@ pub struct BuyTicketsToken<'info> {
     pub raffler: Box<Account<'info, Raffler>>,
 
     #[account(mut)]
-    pub entrants: Box<Account<'info, Entrants>>,
+    pub entrants: AccountLoader<'info, Entrants>,
+
+    #[account(
+        init_if_needed,
+        payer = entrant,
+        space = EntrantTally::LEN,
+        seeds = [
+            b"RAFFLE",
+            raffle.key().as_ref(),
+            entrant.key().as_ref(),
+            b"entrant-tally"
+        ],
+        bump
+    )]
+    pub entrant_tally: Box<Account<'info, EntrantTally>>,
 
     #[account(
         mut,
@@ -52,23 +67,25 @@ pub struct BuyTicketsToken<'info> {
             _ => return err!(RaffleError::TokenMintUnexpected)
         } @ RaffleError::InvalidTokenMint
     )]
-    pub token_mint: Option<Box<Account<'info, Mint>>>,
+    pub token_mint: Option<Box<InterfaceAccount<'info, token_interface::Mint>>>,
 
     #[account(
         init_if_needed,
         payer = entrant,
         associated_token::mint = token_mint,
-        associated_token::authority = entrant
+        associated_token::authority = entrant,
+        associated_token::token_program = token_program
     )]
-    pub token_source: Option<Box<Account<'info, TokenAccount>>>,
+    pub token_source: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
 
     #[account(
         init_if_needed,
         payer = entrant,
         associated_token::mint = token_mint,
-        associated_token::authority = raffle
+        associated_token::authority = raffle,
+        associated_token::token_program = token_program
     )]
-    pub token_destination: Option<Box<Account<'info, TokenAccount>>>,
+    pub token_destination: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
 
     pub gated_nft_mint: Option<Box<Account<'info, Mint>>>,
 
@@ -100,7 +117,7 @@ pub struct BuyTicketsToken<'info> {
     #[account(mut)]
     pub entrant: Signer<'info>,
 
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
@@ -124,13 +141,18 @@ impl<'info> BuyTicketsToken<'info> {
         CpiContext::new(cpi_program, cpi_accounts)
     }
 
-    pub fn transfer_token_ctx(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
-        let cpi_accounts = Transfer {
+    pub fn transfer_token_ctx(&self) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        let cpi_accounts = TransferChecked {
             from: self
                 .token_source
                 .as_ref()
                 .expect("token_source account expected")
                 .to_account_info(),
+            mint: self
+                .token_mint
+                .as_ref()
+                .expect("token_mint account expected")
+                .to_account_info(),
             to: self
                 .token_destination
                 .as_ref()
@@ -163,7 +185,10 @@ impl<'info> BuyTicketsToken<'info> {
     }
 }
 
-pub fn buy_tickets_token_handler(ctx: Context<BuyTicketsToken>, amount: u32) -> Result<()> {
+pub fn buy_tickets_token_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, BuyTicketsToken<'info>>,
+    amount: u32,
+) -> Result<()> {
     let raffle = &ctx.accounts.raffle;
 
     if raffle.gated_collection.is_some() {
@@ -175,17 +200,24 @@ pub fn buy_tickets_token_handler(ctx: Context<BuyTicketsToken>, amount: u32) ->
 
     let amount_u64 = amount as u64;
 
-    match raffle.payment_type {
+    let cost = match raffle.payment_type {
         PaymentType::Token {
             token_mint,
             ticket_price,
         } => {
+            if matches!(raffle.entry_type, EntryType::Stake { minimum_period: _ }) {
+                return err!(RaffleError::InvalidInstruction);
+            }
+
             let cost = ticket_price
                 .checked_mul(amount_u64)
                 .ok_or(RaffleError::ProgramMulError)?;
 
             let token_mint_acc = &ctx.accounts.token_mint.as_ref().unwrap();
             let token_source = &ctx.accounts.token_source.as_ref().unwrap();
+            let decimals = token_mint_acc.decimals;
+            // transfer hook extra accounts are passed as remaining accounts
+            let hook_accounts = ctx.remaining_accounts.to_vec();
 
             require_keys_eq!(
                 token_mint,
@@ -225,9 +257,21 @@ pub fn buy_tickets_token_handler(ctx: Context<BuyTicketsToken>, amount: u32) ->
                         ctx.accounts.sync_native_purchaser_ctx(), // .with_signer(&[&marketplace.marketplace_seeds()]),
                     )?;
 
-                    transfer(ctx.accounts.transfer_token_ctx(), cost)?;
+                    transfer_checked_with_hooks(
+                        ctx.accounts
+                            .transfer_token_ctx()
+                            .with_remaining_accounts(hook_accounts),
+                        cost,
+                        decimals,
+                    )?;
                 } else {
-                    transfer(ctx.accounts.transfer_token_ctx(), cost)?;
+                    transfer_checked_with_hooks(
+                        ctx.accounts
+                            .transfer_token_ctx()
+                            .with_remaining_accounts(hook_accounts),
+                        cost,
+                        decimals,
+                    )?;
                 }
             } else {
                 if matches!(
@@ -238,24 +282,70 @@ pub fn buy_tickets_token_handler(ctx: Context<BuyTicketsToken>, amount: u32) ->
                 ) {
                     burn(ctx.accounts.burn_token_ctx(), cost)?;
                 } else {
-                    transfer(ctx.accounts.transfer_token_ctx(), cost)?;
+                    transfer_checked_with_hooks(
+                        ctx.accounts
+                            .transfer_token_ctx()
+                            .with_remaining_accounts(hook_accounts),
+                        cost,
+                        decimals,
+                    )?;
                 }
             }
 
             if token_source.amount == 0 {
+                harvest_withheld_fees(
+                    &ctx.accounts.token_program.to_account_info(),
+                    &token_mint_acc.to_account_info(),
+                    &token_source.to_account_info(),
+                )?;
                 close_account(ctx.accounts.close_account_ctx())?;
             }
+
+            cost
         }
         PaymentType::Nft { collection: _ } => return err!(RaffleError::TokenInstruction),
-        _ => {}
-    }
-
-    add_entrants(
-        &mut ctx.accounts.entrants,
+        // cNFT entries are bought with buy_ticket_cnft
+        PaymentType::Cnft { collection: _ } => return err!(RaffleError::InvalidInstruction),
+    };
+
+    // the in-memory vault still holds the pre-purchase balance, so the reload gives what the
+    // purchase actually added after any Token-2022 transfer fee
+    let paid = match ctx.accounts.token_destination.as_mut() {
+        Some(token_destination) => {
+            let balance_before = token_destination.amount;
+            token_destination.reload()?;
+            token_destination
+                .amount
+                .checked_sub(balance_before)
+                .ok_or(RaffleError::ProgramSubError)?
+        }
+        None => 0,
+    };
+
+    let first_ticket_index = add_entrants(
+        &mut ctx.accounts.raffle,
+        &ctx.accounts.entrants,
+        &mut ctx.accounts.entrant_tally,
+        ctx.bumps.entrant_tally,
         ctx.accounts.entrant.to_account_info(),
         ctx.accounts.system_program.to_account_info(),
         amount,
     )?;
 
+    let entrant_tally = &mut ctx.accounts.entrant_tally;
+    entrant_tally.paid = entrant_tally
+        .paid
+        .checked_add(paid)
+        .ok_or(RaffleError::ProgramAddError)?;
+
+    emit_cpi!(TicketsPurchased {
+        raffle: ctx.accounts.raffle.key(),
+        entrant: ctx.accounts.entrant.key(),
+        amount,
+        cost,
+        first_ticket_index,
+        end_time: ctx.accounts.raffle.end_time,
+    });
+
     Ok(())
 }
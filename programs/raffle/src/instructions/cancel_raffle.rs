@@ -0,0 +1,551 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    metadata::{mpl_token_metadata::instructions::TransferV1CpiBuilder, Metadata},
+    token_interface::{
+        close_account, CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked,
+    },
+};
+
+use crate::{
+    events::RaffleCancelled,
+    state::{Entrants, PaymentType, PrizeBundle, PrizeType, Raffle, RaffleState, Raffler},
+    utils::{
+        harvest_withheld_fees, split_hook_accounts, transfer_checked_with_hooks, transfer_cnft,
+        transfer_core_asset, transfer_from_prize_vault, CnftArgs,
+    },
+    RaffleError,
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelRaffle<'info> {
+    #[account(
+        seeds = [
+            b"RAFFLE",
+            raffler.authority.as_ref(),
+            b"raffler"
+        ],
+        bump = raffler.bump,
+        has_one = authority @ RaffleError::Unauthorized
+    )]
+    pub raffler: Box<Account<'info, Raffler>>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [
+            b"RAFFLE",
+            entrants.key().as_ref(),
+            b"raffle"
+        ],
+        bump = raffle.bump,
+        has_one = entrants,
+        has_one = raffler
+    )]
+    pub raffle: Box<Account<'info, Raffle>>,
+
+    #[account(
+        mut,
+        close = authority,
+        constraint = entrants.load()?.total == 0 @ RaffleError::RaffleHasEntrants
+    )]
+    pub entrants: AccountLoader<'info, Entrants>,
+
+    #[account(mut, address = raffle.prize)]
+    pub prize: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = prize,
+        associated_token::authority = raffle,
+        associated_token::token_program = prize_token_program
+    )]
+    pub prize_custody: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = prize,
+        associated_token::authority = authority,
+        associated_token::token_program = prize_token_program
+    )]
+    pub prize_destination: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// holds the lamports of a SOL prize
+    #[account(
+        mut,
+        seeds = [
+            b"RAFFLE",
+            raffle.key().as_ref(),
+            b"prize-vault"
+        ],
+        bump
+    )]
+    pub prize_vault: Option<SystemAccount<'info>>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"RAFFLE",
+            raffle.key().as_ref(),
+            b"prize-bundle"
+        ],
+        bump = prize_bundle.bump
+    )]
+    pub prize_bundle: Option<Box<Account<'info, PrizeBundle>>>,
+
+    #[account(
+        mut,
+        address = match raffle.payment_type {
+            PaymentType::Token { token_mint, ticket_price: _ } => token_mint,
+            _ => return err!(RaffleError::TokenMintUnexpected)
+        } @ RaffleError::InvalidTokenMint
+    )]
+    pub token_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = raffle,
+        associated_token::token_program = token_program
+    )]
+    pub token_vault: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        address = raffle.consolation.ok_or(RaffleError::NoConsolationPool)?.mint @ RaffleError::InvalidConsolationPool
+    )]
+    pub consolation_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = consolation_mint,
+        associated_token::authority = raffle,
+        associated_token::token_program = consolation_token_program
+    )]
+    pub consolation_custody: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = consolation_mint,
+        associated_token::authority = authority,
+        associated_token::token_program = consolation_token_program
+    )]
+    pub consolation_destination: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    /// NFT prizes must use the legacy token program, token prizes can use either
+    pub prize_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub metadata_program: Program<'info, Metadata>,
+    /// CHECK: account checked in CPI
+    pub sysvar_instructions: AccountInfo<'info>,
+    /// CHECK: account checked in CPI
+    pub auth_rules: Option<AccountInfo<'info>>,
+    /// CHECK: account checked in CPI
+    pub auth_rules_program: Option<AccountInfo<'info>>,
+    pub consolation_token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+impl<'info> CancelRaffle<'info> {
+    fn transfer_nft(
+        &self,
+        prize_metadata: &AccountInfo<'info>,
+        prize_master_edition: &AccountInfo<'info>,
+        source_token_record: Option<AccountInfo<'info>>,
+        destination_token_record: Option<AccountInfo<'info>>,
+    ) -> Result<()> {
+        let entrants_key = &self.entrants.key();
+        let bump = self.raffle.bump;
+        let metadata_program = &self.metadata_program;
+        let token = &self
+            .prize_custody
+            .as_ref()
+            .expect("prize_custody expected")
+            .to_account_info();
+        let token_owner = &self.raffle.to_account_info();
+        let payer = &self.authority.to_account_info();
+        let destination_token = &self
+            .prize_destination
+            .as_ref()
+            .expect("prize_destination expected")
+            .to_account_info();
+        let destination_owner = &self.authority.to_account_info();
+        let mint = &self
+            .prize
+            .as_ref()
+            .expect("prize expected")
+            .to_account_info();
+        let system_program = &self.system_program.to_account_info();
+        let sysvar_instructions = &self.sysvar_instructions.to_account_info();
+        let spl_token_program = &&self.prize_token_program.to_account_info();
+        let spl_ata_program = &self.associated_token_program.to_account_info();
+        let auth_rules_program = self.auth_rules_program.as_ref();
+        let auth_rules = self.auth_rules.as_ref();
+        let token_record = source_token_record
+            .as_ref()
+            .map(|token_record| token_record.to_account_info());
+        let destination_token_record = destination_token_record.as_ref();
+
+        let mut cpi_transfer = TransferV1CpiBuilder::new(&metadata_program);
+
+        cpi_transfer
+            .token(token)
+            .token_owner(token_owner)
+            .destination_token(destination_token)
+            .destination_owner(destination_owner)
+            .mint(mint)
+            .metadata(&prize_metadata)
+            .edition(Some(&prize_master_edition))
+            .authority(token_owner)
+            .payer(payer)
+            .system_program(system_program)
+            .sysvar_instructions(sysvar_instructions)
+            .spl_token_program(spl_token_program)
+            .spl_ata_program(spl_ata_program)
+            .authorization_rules_program(auth_rules_program)
+            .authorization_rules(auth_rules)
+            .token_record(token_record.as_ref())
+            .destination_token_record(destination_token_record)
+            .amount(1);
+
+        let authority_seed = &[
+            &b"RAFFLE"[..],
+            &entrants_key.as_ref(),
+            &b"raffle"[..],
+            &[bump],
+        ];
+
+        // performs the CPI
+        cpi_transfer.invoke_signed(&[authority_seed])?;
+        Ok(())
+    }
+
+    pub fn transfer_prize_ctx(&self) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        let cpi_accounts = TransferChecked {
+            from: self
+                .prize_custody
+                .as_ref()
+                .expect("prize_custody expected")
+                .to_account_info(),
+            mint: self
+                .prize
+                .as_ref()
+                .expect("prize expected")
+                .to_account_info(),
+            to: self
+                .prize_destination
+                .as_ref()
+                .expect("prize_destination expected")
+                .to_account_info(),
+            authority: self.raffle.to_account_info(),
+        };
+
+        let cpi_program = self.prize_token_program.to_account_info();
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+
+    pub fn close_prize_custody_ctx(&self) -> CpiContext<'_, '_, '_, 'info, CloseAccount<'info>> {
+        let cpi_accounts = CloseAccount {
+            account: self
+                .prize_custody
+                .as_ref()
+                .expect("prize_custody expected")
+                .to_account_info(),
+            destination: self.authority.to_account_info(),
+            authority: self.raffle.to_account_info(),
+        };
+        let cpi_program = self.prize_token_program.to_account_info();
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+
+    pub fn transfer_consolation_ctx(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        let cpi_accounts = TransferChecked {
+            from: self
+                .consolation_custody
+                .as_ref()
+                .expect("consolation_custody expected")
+                .to_account_info(),
+            mint: self
+                .consolation_mint
+                .as_ref()
+                .expect("consolation_mint expected")
+                .to_account_info(),
+            to: self
+                .consolation_destination
+                .as_ref()
+                .expect("consolation_destination expected")
+                .to_account_info(),
+            authority: self.raffle.to_account_info(),
+        };
+
+        let cpi_program = self
+            .consolation_token_program
+            .as_ref()
+            .expect("consolation_token_program expected")
+            .to_account_info();
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+
+    pub fn close_consolation_custody_ctx(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, CloseAccount<'info>> {
+        let cpi_accounts = CloseAccount {
+            account: self
+                .consolation_custody
+                .as_ref()
+                .expect("consolation_custody expected")
+                .to_account_info(),
+            destination: self.authority.to_account_info(),
+            authority: self.raffle.to_account_info(),
+        };
+        let cpi_program = self
+            .consolation_token_program
+            .as_ref()
+            .expect("consolation_token_program expected")
+            .to_account_info();
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+
+    pub fn close_token_vault_ctx(&self) -> CpiContext<'_, '_, '_, 'info, CloseAccount<'info>> {
+        let cpi_accounts = CloseAccount {
+            account: self
+                .token_vault
+                .as_ref()
+                .expect("token_vault expected")
+                .to_account_info(),
+            destination: self.authority.to_account_info(),
+            authority: self.raffle.to_account_info(),
+        };
+        let cpi_program = self.token_program.to_account_info();
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+}
+
+pub fn cancel_raffle_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, CancelRaffle<'info>>,
+    prize_cnft: Option<CnftArgs>,
+    consolation_hook_accounts: u8,
+) -> Result<()> {
+    let current_time = Clock::get().unwrap().unix_timestamp;
+    let raffle = &mut ctx.accounts.raffle;
+    raffle.sync_state(0, current_time)?;
+    raffle.transition(RaffleState::Cancelled)?;
+
+    // a pot carried in from earlier rounds stays with the series
+    require_eq!(raffle.carried_pot, 0, RaffleError::JackpotMustRoll);
+
+    // bundle items go back to the raffler through claim_bundle_prize
+    if raffle.bundle_items > 0 {
+        let prize_bundle = ctx
+            .accounts
+            .prize_bundle
+            .as_mut()
+            .ok_or(RaffleError::BundleNeeded)?;
+        prize_bundle.recipient = Some(ctx.accounts.authority.key());
+    }
+
+    let raffle = &ctx.accounts.raffle;
+    let entrants_key = ctx.accounts.entrants.key();
+    let (remaining_accounts, consolation_hook_accounts) =
+        split_hook_accounts(ctx.remaining_accounts, consolation_hook_accounts)?;
+    let remaining_accounts = &mut remaining_accounts.iter();
+    let bump = raffle.bump;
+
+    let authority_seed = &[
+        &b"RAFFLE"[..],
+        &entrants_key.as_ref(),
+        &b"raffle"[..],
+        &[bump],
+    ];
+
+    // cNFT and Core prizes read their accounts from here, see utils::cnft and utils::core_asset.
+    // Token prizes pass any transfer hook accounts here
+    let prize_accounts = remaining_accounts.as_slice();
+
+    match raffle.prize_type {
+        PrizeType::Nft => {
+            let prize_metadata = next_account_info(remaining_accounts)?;
+            let prize_master_edition = next_account_info(remaining_accounts)?;
+
+            let source_token_record = match next_account_info(remaining_accounts) {
+                Ok(val) => Some(val.to_account_info()),
+                Err(_) => None,
+            };
+
+            let destination_token_record = match next_account_info(remaining_accounts) {
+                Ok(val) => Some(val.to_account_info()),
+                Err(_) => None,
+            };
+
+            ctx.accounts.transfer_nft(
+                prize_metadata,
+                prize_master_edition,
+                source_token_record,
+                destination_token_record,
+            )?;
+        }
+        PrizeType::Token { amount: _ } => {
+            let prize_custody = ctx
+                .accounts
+                .prize_custody
+                .as_ref()
+                .expect("prize_custody expected");
+            let decimals = ctx
+                .accounts
+                .prize
+                .as_ref()
+                .expect("prize expected")
+                .decimals;
+
+            transfer_checked_with_hooks(
+                ctx.accounts
+                    .transfer_prize_ctx()
+                    .with_signer(&[authority_seed])
+                    .with_remaining_accounts(prize_accounts.to_vec()),
+                prize_custody.amount,
+                decimals,
+            )?;
+        }
+        PrizeType::Cnft => {
+            let asset_id = transfer_cnft(
+                prize_accounts,
+                &ctx.accounts.raffle.to_account_info(),
+                &ctx.accounts.authority.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                prize_cnft.ok_or(RaffleError::CnftArgsNeeded)?,
+                &[authority_seed],
+            )?;
+            require_keys_eq!(asset_id, raffle.prize, RaffleError::InvalidPrize);
+        }
+        PrizeType::CoreAsset => {
+            let authority = &ctx.accounts.authority.to_account_info();
+            let asset = transfer_core_asset(
+                prize_accounts,
+                authority,
+                &ctx.accounts.raffle.to_account_info(),
+                authority,
+                &ctx.accounts.system_program.to_account_info(),
+                &[authority_seed],
+            )?;
+            require_keys_eq!(asset, raffle.prize, RaffleError::InvalidPrize);
+        }
+        PrizeType::Sol { lamports: _ } => {
+            let prize_vault = ctx
+                .accounts
+                .prize_vault
+                .as_ref()
+                .expect("prize_vault expected");
+
+            transfer_from_prize_vault(
+                prize_vault,
+                &ctx.accounts.authority.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                raffle.key(),
+                ctx.bumps.prize_vault,
+                prize_vault.lamports(),
+            )?;
+        }
+        // nothing was escrowed, no tickets were sold and no pot was carried in
+        PrizeType::Pot { winner_share: _ } => {}
+    }
+
+    if matches!(
+        raffle.prize_type,
+        PrizeType::Nft | PrizeType::Token { amount: _ }
+    ) {
+        harvest_withheld_fees(
+            &ctx.accounts.prize_token_program.to_account_info(),
+            &ctx.accounts
+                .prize
+                .as_ref()
+                .expect("prize expected")
+                .to_account_info(),
+            &ctx.accounts
+                .prize_custody
+                .as_ref()
+                .expect("prize_custody expected")
+                .to_account_info(),
+        )?;
+
+        close_account(
+            ctx.accounts
+                .close_prize_custody_ctx()
+                .with_signer(&[authority_seed]),
+        )?;
+    }
+
+    // the payment vault created at init is empty as no tickets were sold
+    if let (Some(token_mint), Some(token_vault)) =
+        (&ctx.accounts.token_mint, &ctx.accounts.token_vault)
+    {
+        harvest_withheld_fees(
+            &ctx.accounts.token_program.to_account_info(),
+            &token_mint.to_account_info(),
+            &token_vault.to_account_info(),
+        )?;
+
+        close_account(
+            ctx.accounts
+                .close_token_vault_ctx()
+                .with_signer(&[authority_seed]),
+        )?;
+    }
+
+    // the raffle account closes below, so the consolation pool has to go back now
+    let mut consolation_returned = 0;
+
+    if raffle.consolation.is_some() {
+        let consolation_custody = ctx
+            .accounts
+            .consolation_custody
+            .as_ref()
+            .ok_or(RaffleError::ConsolationPoolNeeded)?;
+        let consolation_mint = ctx
+            .accounts
+            .consolation_mint
+            .as_ref()
+            .ok_or(RaffleError::ConsolationPoolNeeded)?;
+
+        transfer_checked_with_hooks(
+            ctx.accounts
+                .transfer_consolation_ctx()
+                .with_signer(&[authority_seed])
+                .with_remaining_accounts(consolation_hook_accounts.to_vec()),
+            consolation_custody.amount,
+            consolation_mint.decimals,
+        )?;
+        consolation_returned = consolation_custody.amount;
+
+        harvest_withheld_fees(
+            &ctx.accounts
+                .consolation_token_program
+                .as_ref()
+                .ok_or(RaffleError::ConsolationPoolNeeded)?
+                .to_account_info(),
+            &consolation_mint.to_account_info(),
+            &consolation_custody.to_account_info(),
+        )?;
+
+        close_account(
+            ctx.accounts
+                .close_consolation_custody_ctx()
+                .with_signer(&[authority_seed]),
+        )?;
+    }
+
+    emit_cpi!(RaffleCancelled {
+        raffle: raffle.key(),
+        prize: raffle.prize,
+        consolation_returned,
+    });
+
+    Ok(())
+}
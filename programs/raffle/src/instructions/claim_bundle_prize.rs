@@ -0,0 +1,163 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        close_account, CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked,
+    },
+};
+
+use crate::{
+    events::BundlePrizeDelivered,
+    state::PrizeBundle,
+    utils::{harvest_withheld_fees, transfer_checked_with_hooks},
+    RaffleError,
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimBundlePrize<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"RAFFLE",
+            prize_bundle.raffle.as_ref(),
+            b"prize-bundle"
+        ],
+        bump = prize_bundle.bump,
+        has_one = raffle,
+        has_one = authority
+    )]
+    pub prize_bundle: Box<Account<'info, PrizeBundle>>,
+
+    /// CHECK: only signs for custody, the raffle account is closed if it was cancelled
+    pub raffle: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub prize: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = prize,
+        associated_token::authority = raffle,
+        associated_token::token_program = prize_token_program
+    )]
+    pub prize_custody: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = prize,
+        associated_token::authority = recipient,
+        associated_token::token_program = prize_token_program
+    )]
+    pub prize_destination: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: checked against the bundle's recipient in the handler
+    pub recipient: AccountInfo<'info>,
+
+    /// CHECK: the raffler authority that funded the bundle, receives the custody rent back
+    #[account(mut)]
+    pub authority: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub prize_token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> ClaimBundlePrize<'info> {
+    pub fn transfer_prize_ctx(&self) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        let cpi_accounts = TransferChecked {
+            from: self.prize_custody.to_account_info(),
+            mint: self.prize.to_account_info(),
+            to: self.prize_destination.to_account_info(),
+            authority: self.raffle.to_account_info(),
+        };
+
+        let cpi_program = self.prize_token_program.to_account_info();
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+
+    pub fn close_account_ctx(&self) -> CpiContext<'_, '_, '_, 'info, CloseAccount<'info>> {
+        let cpi_accounts = CloseAccount {
+            account: self.prize_custody.to_account_info(),
+            destination: self.authority.to_account_info(),
+            authority: self.raffle.to_account_info(),
+        };
+        let cpi_program = self.prize_token_program.to_account_info();
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+}
+
+/// Delivers one item of a prize bundle to its recipient. Anyone can crank this once
+/// `claim_prize` or `cancel_raffle` has set the recipient, one call per item.
+pub fn claim_bundle_prize_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, ClaimBundlePrize<'info>>,
+) -> Result<()> {
+    let prize_bundle = &ctx.accounts.prize_bundle;
+    let prize = &ctx.accounts.prize;
+
+    let recipient = prize_bundle
+        .recipient
+        .ok_or(RaffleError::BundleNotClaimed)?;
+    require_keys_eq!(
+        ctx.accounts.recipient.key(),
+        recipient,
+        RaffleError::NotWinner
+    );
+
+    let item_index = prize_bundle
+        .items
+        .iter()
+        .position(|item| item.mint == prize.key())
+        .ok_or(RaffleError::InvalidPrize)?;
+    let item = prize_bundle.items[item_index];
+    require!(!item.delivered, RaffleError::AlreadyClaimed);
+
+    let entrants_key = prize_bundle.entrants;
+    let authority_seed = &[
+        &b"RAFFLE"[..],
+        &entrants_key.as_ref(),
+        &b"raffle"[..],
+        &[prize_bundle.raffle_bump],
+    ];
+
+    transfer_checked_with_hooks(
+        ctx.accounts
+            .transfer_prize_ctx()
+            .with_signer(&[authority_seed])
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+        ctx.accounts.prize_custody.amount,
+        prize.decimals,
+    )?;
+
+    harvest_withheld_fees(
+        &ctx.accounts.prize_token_program.to_account_info(),
+        &prize.to_account_info(),
+        &ctx.accounts.prize_custody.to_account_info(),
+    )?;
+
+    close_account(
+        ctx.accounts
+            .close_account_ctx()
+            .with_signer(&[authority_seed]),
+    )?;
+
+    let prize_bundle = &mut ctx.accounts.prize_bundle;
+    prize_bundle.items[item_index].delivered = true;
+
+    emit_cpi!(BundlePrizeDelivered {
+        raffle: prize_bundle.raffle,
+        mint: item.mint,
+        amount: item.amount,
+        recipient,
+    });
+
+    if prize_bundle.undelivered() == 0 {
+        prize_bundle.close(ctx.accounts.authority.to_account_info())?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,227 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        close_account, CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked,
+    },
+};
+
+use crate::{
+    events::ConsolationClaimed,
+    state::{EntrantTally, Raffle, RaffleState, Raffler},
+    utils::{harvest_withheld_fees, transfer_checked_with_hooks},
+    RaffleError,
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimConsolation<'info> {
+    #[account(
+        seeds = [
+            b"RAFFLE",
+            raffler.authority.as_ref(),
+            b"raffler"
+        ],
+        bump = raffler.bump,
+        has_one = authority
+    )]
+    pub raffler: Box<Account<'info, Raffler>>,
+
+    /// entrants may already be closed by the last prize claim
+    #[account(
+        mut,
+        seeds = [
+            b"RAFFLE",
+            raffle.entrants.as_ref(),
+            b"raffle"
+        ],
+        bump = raffle.bump,
+        has_one = raffler
+    )]
+    pub raffle: Box<Account<'info, Raffle>>,
+
+    /// None when the raffler authority takes back a pool nobody can claim
+    #[account(
+        mut,
+        seeds = [
+            b"RAFFLE",
+            raffle.key().as_ref(),
+            entrant.key().as_ref(),
+            b"entrant-tally"
+        ],
+        bump = entrant_tally.bump,
+        has_one = raffle,
+        has_one = entrant
+    )]
+    pub entrant_tally: Option<Box<Account<'info, EntrantTally>>>,
+
+    #[account(
+        address = raffle.consolation.ok_or(RaffleError::NoConsolationPool)?.mint @ RaffleError::InvalidConsolationPool
+    )]
+    pub consolation_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = consolation_mint,
+        associated_token::authority = raffle,
+        associated_token::token_program = token_program
+    )]
+    pub consolation_custody: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = entrant,
+        associated_token::mint = consolation_mint,
+        associated_token::authority = entrant,
+        associated_token::token_program = token_program
+    )]
+    pub consolation_destination: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub entrant: Signer<'info>,
+
+    /// receives the custody rent once the pool is paid out
+    #[account(mut)]
+    pub authority: SystemAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ClaimConsolation<'info> {
+    pub fn transfer_consolation_ctx(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        let cpi_accounts = TransferChecked {
+            from: self.consolation_custody.to_account_info(),
+            mint: self.consolation_mint.to_account_info(),
+            to: self.consolation_destination.to_account_info(),
+            authority: self.raffle.to_account_info(),
+        };
+
+        let cpi_program = self.token_program.to_account_info();
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+
+    pub fn close_custody_ctx(&self) -> CpiContext<'_, '_, '_, 'info, CloseAccount<'info>> {
+        let cpi_accounts = CloseAccount {
+            account: self.consolation_custody.to_account_info(),
+            destination: self.authority.to_account_info(),
+            authority: self.raffle.to_account_info(),
+        };
+        let cpi_program = self.token_program.to_account_info();
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+}
+
+/// Pays an entrant their share of the consolation pool, in proportion to their tickets that
+/// didn't win, once the winners can no longer be re-drawn. Each claim takes its share of
+/// what is left, so the last claimant picks up any rounding remainder. If there are no
+/// non-winning tickets, or the raffle is refunding, the raffler authority claims without a
+/// tally and takes the whole pool back. Cancelled raffles return the pool in cancel_raffle.
+pub fn claim_consolation_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, ClaimConsolation<'info>>,
+) -> Result<()> {
+    let raffle = &ctx.accounts.raffle;
+    let consolation = raffle.consolation.ok_or(RaffleError::NoConsolationPool)?;
+    let balance = ctx.accounts.consolation_custody.amount;
+
+    let (tickets, amount) = match ctx.accounts.entrant_tally.as_mut() {
+        Some(entrant_tally) => {
+            require!(raffle.winners_final(), RaffleError::WinnersNotFinal);
+            require!(
+                !entrant_tally.consolation_claimed,
+                RaffleError::AlreadyClaimed
+            );
+
+            let winning_tickets = raffle
+                .winners
+                .iter()
+                .filter(|w| w.entrant == entrant_tally.entrant)
+                .count() as u32;
+            let tickets = entrant_tally.tickets.saturating_sub(winning_tickets);
+            require_gt!(tickets, 0, RaffleError::NoConsolationShare);
+
+            let unclaimed_tickets = consolation
+                .eligible_tickets
+                .checked_sub(consolation.claimed_tickets)
+                .ok_or(RaffleError::ProgramSubError)?;
+            let amount =
+                u64::try_from(balance as u128 * tickets as u128 / unclaimed_tickets as u128)
+                    .unwrap();
+
+            entrant_tally.consolation_claimed = true;
+            (tickets, amount)
+        }
+        None => {
+            require_keys_eq!(
+                ctx.accounts.entrant.key(),
+                ctx.accounts.raffler.authority,
+                RaffleError::OnlyAdminCanClaim
+            );
+            require!(
+                raffle.state == RaffleState::Refunding
+                    || (raffle.winners_final() && consolation.eligible_tickets == 0),
+                RaffleError::WinnersNotFinal
+            );
+
+            (0, balance)
+        }
+    };
+
+    let raffle = &ctx.accounts.raffle;
+    let bump = raffle.bump;
+    let entrants_key = raffle.entrants;
+    let authority_seed = &[
+        &b"RAFFLE"[..],
+        &entrants_key.as_ref(),
+        &b"raffle"[..],
+        &[bump],
+    ];
+
+    if amount > 0 {
+        transfer_checked_with_hooks(
+            ctx.accounts
+                .transfer_consolation_ctx()
+                .with_signer(&[authority_seed])
+                .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+            amount,
+            ctx.accounts.consolation_mint.decimals,
+        )?;
+    }
+
+    let raffle = &mut ctx.accounts.raffle;
+    let consolation = raffle
+        .consolation
+        .as_mut()
+        .ok_or(RaffleError::NoConsolationPool)?;
+    consolation.claimed_tickets = consolation
+        .claimed_tickets
+        .checked_add(tickets)
+        .ok_or(RaffleError::ProgramAddError)?;
+    let is_paid_out = consolation.claimed_tickets >= consolation.eligible_tickets;
+
+    emit_cpi!(ConsolationClaimed {
+        raffle: raffle.key(),
+        entrant: ctx.accounts.entrant.key(),
+        tickets,
+        amount,
+    });
+
+    if !is_paid_out {
+        return Ok(());
+    }
+
+    harvest_withheld_fees(
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.consolation_mint.to_account_info(),
+        &ctx.accounts.consolation_custody.to_account_info(),
+    )?;
+
+    close_account(
+        ctx.accounts
+            .close_custody_ctx()
+            .with_signer(&[authority_seed]),
+    )
+}
@@ -2,15 +2,25 @@ use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
     metadata::{mpl_token_metadata::instructions::TransferV1CpiBuilder, Metadata},
-    token::{close_account, transfer, CloseAccount, Mint, Token, TokenAccount, Transfer},
+    token_interface::{
+        close_account, CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked,
+    },
 };
 
 use crate::{
-    state::{Entrants, EntryType, PaymentType, PrizeType, ProgramConfig, Raffle, Raffler},
-    utils::expand_randomness,
+    events::PrizeClaimed,
+    state::{
+        Entrants, EntryType, PaymentType, PrizeBundle, PrizeType, ProgramConfig, Raffle,
+        RaffleState, Raffler, UnclaimedPrize,
+    },
+    utils::{
+        harvest_withheld_fees, prize_vault_balance, transfer_checked_with_hooks, transfer_cnft,
+        transfer_core_asset, transfer_from_prize_vault, CnftArgs,
+    },
     RaffleError, FEES_WALLET, NATIVE_MINT,
 };
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct ClaimPrize<'info> {
     #[account(
@@ -39,16 +49,12 @@ pub struct ClaimPrize<'info> {
         ],
         bump = raffle.bump,
         has_one = entrants,
-        has_one = prize,
         has_one = raffler
     )]
     pub raffle: Box<Account<'info, Raffle>>,
 
-    #[account(
-        mut,
-        close = fees_wallet
-    )]
-    pub entrants: Box<Account<'info, Entrants>>,
+    #[account(mut)]
+    pub entrants: AccountLoader<'info, Entrants>,
 
     #[account( mut, address = FEES_WALLET )]
     pub fees_wallet: Option<SystemAccount<'info>>,
@@ -57,11 +63,13 @@ pub struct ClaimPrize<'info> {
         init_if_needed,
         payer = payer,
         associated_token::mint = proceeds_mint,
-        associated_token::authority = fees_wallet
+        associated_token::authority = fees_wallet,
+        associated_token::token_program = token_program
     )]
-    pub fees_wallet_token: Option<Box<Account<'info, TokenAccount>>>,
+    pub fees_wallet_token: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
     #[account(
+        mut,
         address = match raffle.payment_type {
             PaymentType::Token { token_mint, ticket_price: _ } => token_mint,
             PaymentType::Nft { collection } => {
@@ -74,40 +82,67 @@ pub struct ClaimPrize<'info> {
             _ => return err!(RaffleError::TokenMintUnexpected)
         } @ RaffleError::InvalidTokenMint
     )]
-    pub proceeds_mint: Option<Box<Account<'info, Mint>>>,
+    pub proceeds_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
 
     #[account(
         mut,
         associated_token::mint = proceeds_mint,
-        associated_token::authority = raffle
+        associated_token::authority = raffle,
+        associated_token::token_program = token_program
     )]
-    pub proceeds_source: Option<Box<Account<'info, TokenAccount>>>,
+    pub proceeds_source: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
     #[account(
         init_if_needed,
         payer = payer,
         associated_token::mint = proceeds_mint,
-        associated_token::authority = treasury
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program
     )]
-    pub proceeds_destination: Option<Box<Account<'info, TokenAccount>>>,
+    pub proceeds_destination: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
-    #[account(address = raffle.prize)]
-    pub prize: Box<Account<'info, Mint>>,
+    #[account(mut, address = raffle.prize)]
+    pub prize: Option<Box<InterfaceAccount<'info, Mint>>>,
 
     #[account(
         mut,
         associated_token::mint = prize,
-        associated_token::authority = raffle
+        associated_token::authority = raffle,
+        associated_token::token_program = prize_token_program
     )]
-    pub prize_custody: Box<Account<'info, TokenAccount>>,
+    pub prize_custody: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
     #[account(
         init_if_needed,
         payer = payer,
         associated_token::mint = prize,
-        associated_token::authority = winner
+        associated_token::authority = winner,
+        associated_token::token_program = prize_token_program
+    )]
+    pub prize_destination: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// holds the lamports of a SOL prize
+    #[account(
+        mut,
+        seeds = [
+            b"RAFFLE",
+            raffle.key().as_ref(),
+            b"prize-vault"
+        ],
+        bump
+    )]
+    pub prize_vault: Option<SystemAccount<'info>>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"RAFFLE",
+            raffle.key().as_ref(),
+            b"prize-bundle"
+        ],
+        bump = prize_bundle.bump
     )]
-    pub prize_destination: Box<Account<'info, TokenAccount>>,
+    pub prize_bundle: Option<Box<Account<'info, PrizeBundle>>>,
 
     #[account(mut)]
     pub winner: SystemAccount<'info>,
@@ -121,7 +156,9 @@ pub struct ClaimPrize<'info> {
     #[account(mut, address = raffler.treasury)]
     pub treasury: Option<SystemAccount<'info>>,
 
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
+    /// NFT prizes must use the legacy token program, token prizes can use either
+    pub prize_token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub metadata_program: Program<'info, Metadata>,
@@ -144,15 +181,27 @@ impl<'info> ClaimPrize<'info> {
         let entrants_key = &self.entrants.key();
         let bump = self.raffle.bump;
         let metadata_program = &self.metadata_program;
-        let token = &self.prize_custody.to_account_info();
+        let token = &self
+            .prize_custody
+            .as_ref()
+            .expect("prize_custody expected")
+            .to_account_info();
         let token_owner = &self.raffle.to_account_info();
         let payer = &self.payer.to_account_info();
-        let destination_token = &self.prize_destination.to_account_info();
+        let destination_token = &self
+            .prize_destination
+            .as_ref()
+            .expect("prize_destination expected")
+            .to_account_info();
         let destination_owner = &self.winner.to_account_info();
-        let mint = &self.prize.to_account_info();
+        let mint = &self
+            .prize
+            .as_ref()
+            .expect("prize expected")
+            .to_account_info();
         let system_program = &self.system_program.to_account_info();
         let sysvar_instructions = &self.sysvar_instructions.to_account_info();
-        let spl_token_program = &&self.token_program.to_account_info();
+        let spl_token_program = &&self.prize_token_program.to_account_info();
         let spl_ata_program = &self.associated_token_program.to_account_info();
         let auth_rules_program = self.auth_rules_program.as_ref();
         let auth_rules = self.auth_rules.as_ref();
@@ -197,21 +246,30 @@ impl<'info> ClaimPrize<'info> {
 
     pub fn close_account_ctx(&self) -> CpiContext<'_, '_, '_, 'info, CloseAccount<'info>> {
         let cpi_accounts = CloseAccount {
-            account: self.prize_custody.to_account_info(),
+            account: self
+                .prize_custody
+                .as_ref()
+                .expect("prize_custody expected")
+                .to_account_info(),
             destination: self.authority.to_account_info(),
             authority: self.raffle.to_account_info(),
         };
-        let cpi_program = self.token_program.to_account_info();
+        let cpi_program = self.prize_token_program.to_account_info();
         CpiContext::new(cpi_program, cpi_accounts)
     }
 
-    pub fn transfer_proceeds_ctx(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
-        let cpi_accounts = Transfer {
+    pub fn transfer_proceeds_ctx(&self) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        let cpi_accounts = TransferChecked {
             from: self
                 .proceeds_source
                 .as_ref()
                 .expect("token_account expected")
                 .to_account_info(),
+            mint: self
+                .proceeds_mint
+                .as_ref()
+                .expect("proceeds_mint expected")
+                .to_account_info(),
             to: self
                 .proceeds_destination
                 .as_ref()
@@ -225,13 +283,20 @@ impl<'info> ClaimPrize<'info> {
         CpiContext::new(cpi_program, cpi_accounts)
     }
 
-    pub fn transfer_proceeds_share_ctx(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
-        let cpi_accounts = Transfer {
+    pub fn transfer_proceeds_share_ctx(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        let cpi_accounts = TransferChecked {
             from: self
                 .proceeds_source
                 .as_ref()
                 .expect("token_account expected")
                 .to_account_info(),
+            mint: self
+                .proceeds_mint
+                .as_ref()
+                .expect("proceeds_mint expected")
+                .to_account_info(),
             to: self
                 .fees_wallet_token
                 .as_ref()
@@ -245,18 +310,56 @@ impl<'info> ClaimPrize<'info> {
         CpiContext::new(cpi_program, cpi_accounts)
     }
 
-    pub fn transfer_prize_ctx(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
-        let cpi_accounts = Transfer {
-            from: self.prize_custody.to_account_info(),
-            to: self.prize_destination.to_account_info(),
+    pub fn transfer_prize_ctx(&self) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        let cpi_accounts = TransferChecked {
+            from: self
+                .prize_custody
+                .as_ref()
+                .expect("prize_custody expected")
+                .to_account_info(),
+            mint: self
+                .prize
+                .as_ref()
+                .expect("prize expected")
+                .to_account_info(),
+            to: self
+                .prize_destination
+                .as_ref()
+                .expect("prize_destination expected")
+                .to_account_info(),
             authority: self.raffle.to_account_info(),
         };
 
-        let cpi_program = self.token_program.to_account_info();
+        let cpi_program = self.prize_token_program.to_account_info();
 
         CpiContext::new(cpi_program, cpi_accounts)
     }
 
+    /// pot raffles pay winners straight from the proceeds, in the payment mint
+    pub fn transfer_pot_ctx(&self) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        let cpi_accounts = TransferChecked {
+            from: self
+                .proceeds_source
+                .as_ref()
+                .expect("proceeds_source expected")
+                .to_account_info(),
+            mint: self
+                .proceeds_mint
+                .as_ref()
+                .expect("proceeds_mint expected")
+                .to_account_info(),
+            to: self
+                .prize_destination
+                .as_ref()
+                .expect("prize_destination expected")
+                .to_account_info(),
+            authority: self.raffle.to_account_info(),
+        };
+
+        let cpi_program = self.token_program.to_account_info();
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+
     pub fn close_proceeds_ctx(&self) -> CpiContext<'_, '_, '_, 'info, CloseAccount<'info>> {
         let cpi_accounts = CloseAccount {
             account: self.proceeds_source.as_ref().unwrap().to_account_info(),
@@ -271,7 +374,14 @@ impl<'info> ClaimPrize<'info> {
 pub fn claim_prize_handler<'info>(
     ctx: Context<'_, '_, '_, 'info, ClaimPrize<'info>>,
     ticket_index: u32,
+    prize_cnft: Option<CnftArgs>,
 ) -> Result<()> {
+    let current_time = Clock::get().unwrap().unix_timestamp;
+    let total_tickets = ctx.accounts.entrants.load()?.total;
+    ctx.accounts
+        .raffle
+        .sync_state(total_tickets, current_time)?;
+
     let rafflooor = &ctx.accounts.raffler;
     let raffle = &ctx.accounts.raffle;
     let entrants_key = ctx.accounts.entrants.key();
@@ -285,6 +395,9 @@ pub fn claim_prize_handler<'info>(
         &[bump],
     ];
 
+    // cNFT and Core prizes read their accounts from here, see utils::cnft and utils::core_asset
+    let prize_accounts = remaining_accounts.as_slice();
+
     let prize_metadata = if raffle.prize_type == PrizeType::Nft {
         Some(next_account_info(remaining_accounts)?)
     } else {
@@ -298,47 +411,78 @@ pub fn claim_prize_handler<'info>(
     };
 
     let source_token_record = match next_account_info(remaining_accounts) {
-        Ok(val) => Some(val.to_account_info()),
-        Err(_) => None,
+        Ok(val) if raffle.prize_type == PrizeType::Nft => Some(val.to_account_info()),
+        _ => None,
     };
 
     let destination_token_record = match next_account_info(remaining_accounts) {
-        Ok(val) => Some(val.to_account_info()),
-        Err(_) => None,
+        Ok(val) if raffle.prize_type == PrizeType::Nft => Some(val.to_account_info()),
+        _ => None,
     };
 
     require!(!raffle.claimed, RaffleError::AlreadyClaimed);
 
-    let entrants = &ctx.accounts.entrants;
+    // the prize returns to the raffler if the raffle didn't sell its minimum tickets
+    let is_refunding = raffle.state == RaffleState::Refunding;
+
+    let winner_slot = if total_tickets == 0 || is_refunding {
+        // a jackpot round without entrants rolls its pot into the next round instead
+        require!(
+            is_refunding || raffle.jackpot_series.is_none(),
+            RaffleError::JackpotMustRoll
+        );
+
+        // tickets can still be bought until end_time
+        require_gte!(current_time, raffle.end_time, RaffleError::RaffleNotEnded);
 
-    if entrants.total == 0 {
         require_keys_eq!(
             ctx.accounts.winner.key(),
             ctx.accounts.raffler.authority,
             RaffleError::OnlyAdminCanClaim
         );
+        None
     } else {
-        let randomness = match raffle.randomness {
-            Some(randomness) => randomness,
-            None => return err!(RaffleError::WinnerNotDrawn),
-        };
-        let winner_rand = expand_randomness(randomness);
-        let winner_index = winner_rand % entrants.total;
-
-        msg!("winner rand {} winner index {}", winner_rand, winner_index);
+        require!(
+            raffle.state == RaffleState::Drawn,
+            RaffleError::WinnerNotDrawn
+        );
 
-        require_eq!(ticket_index, winner_index, RaffleError::TicketNotWinner);
+        let winner_slot = raffle
+            .winners
+            .iter()
+            .position(|w| w.ticket_index == ticket_index)
+            .ok_or(RaffleError::TicketNotWinner)?;
 
-        let entrant_for_ticket = Entrants::get_entrant(
-            ctx.accounts.entrants.to_account_info().data.borrow(),
-            ticket_index as usize,
+        require!(
+            !raffle.winners[winner_slot].claimed,
+            RaffleError::AlreadyClaimed
         );
 
-        require_keys_eq!(
-            ctx.accounts.winner.key(),
-            entrant_for_ticket,
-            RaffleError::NotWinner
-        );
+        msg!("winner index {}", ticket_index);
+
+        // once the window lapses the raffler can take back shares that can't be re-drawn
+        let is_reclaim = raffle.claim_expired(current_time)
+            && ctx.accounts.winner.key() == rafflooor.authority
+            && (raffle.unclaimed_prize == UnclaimedPrize::Reclaim
+                || !raffle.can_redraw(total_tickets));
+
+        if is_reclaim {
+            require!(
+                raffle.jackpot_series.is_none(),
+                RaffleError::JackpotMustRoll
+            );
+        } else {
+            require!(
+                !raffle.claim_expired(current_time),
+                RaffleError::ClaimWindowExpired
+            );
+
+            require_keys_eq!(
+                ctx.accounts.winner.key(),
+                raffle.winners[winner_slot].entrant,
+                RaffleError::NotWinner
+            );
+        }
 
         if ctx.accounts.payer.key() != ctx.accounts.winner.key() {
             require_keys_eq!(
@@ -347,69 +491,137 @@ pub fn claim_prize_handler<'info>(
                 RaffleError::OnlyWinnerOrAdminCanSettle
             );
         }
-    }
 
-    let should_transfer = match raffle.payment_type {
-        PaymentType::Nft { collection: _ } => {
-            matches!(
-                raffle.entry_type,
-                EntryType::Burn {
-                    withold_burn_proceeds: true
-                }
-            )
-        }
-        PaymentType::Token {
-            token_mint: _,
-            ticket_price: _,
-        } => true,
-        _ => false,
+        Some(winner_slot)
     };
 
-    if should_transfer {
+    let is_first_claim = raffle.winners.iter().all(|w| !w.claimed);
+    let is_pot = matches!(raffle.prize_type, PrizeType::Pot { winner_share: _ });
+
+    let should_transfer = is_first_claim
+        && !is_refunding
+        && match raffle.payment_type {
+            PaymentType::Nft { collection: _ } => {
+                matches!(
+                    raffle.entry_type,
+                    EntryType::Burn {
+                        withold_burn_proceeds: true
+                    }
+                )
+            }
+            PaymentType::Token {
+                token_mint: _,
+                ticket_price: _,
+            } => !matches!(raffle.entry_type, EntryType::Stake { minimum_period: _ }),
+            _ => false,
+        };
+
+    let (treasury_proceeds, fee) = if should_transfer {
         msg!("Transferring token");
 
         let fee_bp = ctx.accounts.program_config.proceeds_share;
+        // Token-2022 transfer fees withheld on ticket purchases are not part of the balance,
+        // so the share is taken from what the raffle actually received
         let proceeds = ctx.accounts.proceeds_source.as_ref().unwrap().amount;
+        let proceeds_mint = ctx.accounts.proceeds_mint.as_ref().unwrap();
+        let decimals = proceeds_mint.decimals;
 
-        let proceeds_128 = proceeds as u128;
-        let fee_bp_128 = fee_bp as u128;
-        let fee_128 = proceeds_128 * fee_bp_128 / 10_000;
-        let fee_64 = u64::try_from(fee_128).unwrap();
-
-        let treasury_proceeds = proceeds
-            .checked_sub(fee_64)
-            .ok_or(RaffleError::ProgramSubError)?;
+        let (treasury_proceeds, fee_64) = raffle.split_proceeds(proceeds, fee_bp)?;
 
         if fee_64 > 0 {
-            transfer(
+            transfer_checked_with_hooks(
                 ctx.accounts
                     .transfer_proceeds_share_ctx()
-                    .with_signer(&[authority_seed]),
+                    .with_signer(&[authority_seed])
+                    .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
                 fee_64,
+                decimals,
             )?;
         }
 
         if treasury_proceeds > 0 {
-            transfer(
+            transfer_checked_with_hooks(
                 ctx.accounts
                     .transfer_proceeds_ctx()
-                    .with_signer(&[authority_seed]),
+                    .with_signer(&[authority_seed])
+                    .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
                 treasury_proceeds,
+                decimals,
             )?;
         }
 
-        close_account(
-            ctx.accounts
-                .close_proceeds_ctx()
-                .with_signer(&[authority_seed]),
-        )?;
-    }
+        // the pot is paid out below, the vault closes after the last winner claims
+        if !is_pot {
+            harvest_withheld_fees(
+                &ctx.accounts.token_program.to_account_info(),
+                &proceeds_mint.to_account_info(),
+                &ctx.accounts
+                    .proceeds_source
+                    .as_ref()
+                    .unwrap()
+                    .to_account_info(),
+            )?;
+
+            close_account(
+                ctx.accounts
+                    .close_proceeds_ctx()
+                    .with_signer(&[authority_seed]),
+            )?;
+        }
+
+        (treasury_proceeds, fee_64)
+    } else {
+        (0, 0)
+    };
 
     let raffle = &mut ctx.accounts.raffle;
 
-    raffle.claimed = true;
+    // each remaining winner takes an equal share of what is left in custody,
+    // the last claimant also picks up any rounding remainder
+    let unclaimed = match winner_slot {
+        Some(slot) => {
+            let unclaimed = raffle.unclaimed_winners();
+            raffle.winners[slot].claimed = true;
+            unclaimed
+        }
+        None => 1,
+    };
+
+    let is_last_claim = unclaimed == 1;
+
+    if is_last_claim {
+        raffle.claimed = true;
+
+        // bundle items follow the main prize, delivered by claim_bundle_prize
+        if raffle.bundle_items > 0 {
+            let prize_bundle = ctx
+                .accounts
+                .prize_bundle
+                .as_mut()
+                .ok_or(RaffleError::BundleNeeded)?;
+            prize_bundle.recipient = Some(ctx.accounts.winner.key());
+        }
+
+        // refunding raffles stay in that state so entrants can keep claiming refunds
+        if total_tickets == 0 && !is_refunding {
+            raffle.close_unsold(total_tickets, current_time)?;
+        } else if !is_refunding {
+            raffle.transition(RaffleState::Claimed)?;
+        }
+    }
+
+    let prize = raffle.prize;
+    let prize_type = raffle.prize_type;
+
+    emit_cpi!(PrizeClaimed {
+        raffle: raffle.key(),
+        winner: ctx.accounts.winner.key(),
+        ticket_index: winner_slot.map(|_| ticket_index),
+        treasury_proceeds,
+        fee,
+    });
 
-    match raffle.prize_type {
+    match prize_type {
         PrizeType::Nft => {
             ctx.accounts.transfer_nft(
                 prize_metadata.unwrap(),
@@ -418,16 +630,181 @@ pub fn claim_prize_handler<'info>(
                 destination_token_record,
             )?;
         }
-        PrizeType::Token { amount } => {
-            transfer(
+        PrizeType::Token { amount: _ } => {
+            let share = ctx
+                .accounts
+                .prize_custody
+                .as_ref()
+                .expect("prize_custody expected")
+                .amount
+                .checked_div(unclaimed as u64)
+                .ok_or(RaffleError::ProgramDivError)?;
+            let decimals = ctx
+                .accounts
+                .prize
+                .as_ref()
+                .expect("prize expected")
+                .decimals;
+
+            transfer_checked_with_hooks(
                 ctx.accounts
                     .transfer_prize_ctx()
-                    .with_signer(&[authority_seed]),
-                amount,
+                    .with_signer(&[authority_seed])
+                    .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+                share,
+                decimals,
+            )?;
+        }
+        PrizeType::Cnft => {
+            let asset_id = transfer_cnft(
+                prize_accounts,
+                &ctx.accounts.raffle.to_account_info(),
+                &ctx.accounts.winner.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                prize_cnft.ok_or(RaffleError::CnftArgsNeeded)?,
+                &[authority_seed],
+            )?;
+            require_keys_eq!(asset_id, prize, RaffleError::InvalidPrize);
+        }
+        PrizeType::CoreAsset => {
+            let asset = transfer_core_asset(
+                prize_accounts,
+                &ctx.accounts.payer.to_account_info(),
+                &ctx.accounts.raffle.to_account_info(),
+                &ctx.accounts.winner.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                &[authority_seed],
             )?;
+            require_keys_eq!(asset, prize, RaffleError::InvalidPrize);
         }
+        PrizeType::Sol { lamports: _ } => {
+            let prize_vault = ctx
+                .accounts
+                .prize_vault
+                .as_ref()
+                .expect("prize_vault expected");
+            let share = prize_vault_balance(prize_vault)?
+                .checked_div(unclaimed as u64)
+                .ok_or(RaffleError::ProgramDivError)?;
+
+            transfer_from_prize_vault(
+                prize_vault,
+                &ctx.accounts.winner.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                ctx.accounts.raffle.key(),
+                ctx.bumps.prize_vault,
+                share,
+            )?;
+        }
+        // refunds are paid from the same vault, so nothing is taken while refunding
+        PrizeType::Pot { winner_share: _ } if !is_refunding => {
+            let proceeds_source = ctx
+                .accounts
+                .proceeds_source
+                .as_mut()
+                .expect("proceeds_source expected");
+            proceeds_source.reload()?;
+
+            let share = proceeds_source
+                .amount
+                .checked_div(unclaimed as u64)
+                .ok_or(RaffleError::ProgramDivError)?;
+            let decimals = ctx
+                .accounts
+                .proceeds_mint
+                .as_ref()
+                .expect("proceeds_mint expected")
+                .decimals;
+
+            transfer_checked_with_hooks(
+                ctx.accounts
+                    .transfer_pot_ctx()
+                    .with_signer(&[authority_seed])
+                    .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+                share,
+                decimals,
+            )?;
+        }
+        PrizeType::Pot { winner_share: _ } => {}
+    }
+
+    if !is_last_claim {
+        return Ok(());
+    }
+
+    // entrants stays open while refunding so claim_refund can keep checking the ticket count
+    if !is_refunding {
+        ctx.accounts.entrants.close(
+            ctx.accounts
+                .fees_wallet
+                .as_ref()
+                .expect("fees_wallet expected")
+                .to_account_info(),
+        )?;
     }
 
+    if matches!(prize_type, PrizeType::Cnft | PrizeType::CoreAsset) {
+        return Ok(());
+    }
+
+    if is_pot {
+        if is_refunding {
+            return Ok(());
+        }
+
+        harvest_withheld_fees(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts
+                .proceeds_mint
+                .as_ref()
+                .expect("proceeds_mint expected")
+                .to_account_info(),
+            &ctx.accounts
+                .proceeds_source
+                .as_ref()
+                .expect("proceeds_source expected")
+                .to_account_info(),
+        )?;
+
+        return close_account(
+            ctx.accounts
+                .close_proceeds_ctx()
+                .with_signer(&[authority_seed]),
+        );
+    }
+
+    // the vault's rent goes back to the raffler once every share has been paid
+    if let PrizeType::Sol { lamports: _ } = prize_type {
+        let prize_vault = ctx
+            .accounts
+            .prize_vault
+            .as_ref()
+            .expect("prize_vault expected");
+
+        return transfer_from_prize_vault(
+            prize_vault,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            ctx.accounts.raffle.key(),
+            ctx.bumps.prize_vault,
+            prize_vault.lamports(),
+        );
+    }
+
+    harvest_withheld_fees(
+        &ctx.accounts.prize_token_program.to_account_info(),
+        &ctx.accounts
+            .prize
+            .as_ref()
+            .expect("prize expected")
+            .to_account_info(),
+        &ctx.accounts
+            .prize_custody
+            .as_ref()
+            .expect("prize_custody expected")
+            .to_account_info(),
+    )?;
+
     close_account(
         ctx.accounts
             .close_account_ctx()
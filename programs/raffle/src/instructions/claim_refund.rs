@@ -0,0 +1,366 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    metadata::{
+        mpl_token_metadata::instructions::TransferV1CpiBuilder, MasterEditionAccount, Metadata,
+        MetadataAccount, TokenRecordAccount,
+    },
+    token::{Mint, Token, TokenAccount},
+    token_interface::{self, close_account, CloseAccount, TokenInterface, TransferChecked},
+};
+
+use crate::{
+    events::RefundClaimed,
+    state::{EntrantTally, Entrants, NftEntry, PaymentType, Raffle, RaffleState},
+    utils::transfer_checked_with_hooks,
+    RaffleError,
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimRefund<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"RAFFLE",
+            entrants.key().as_ref(),
+            b"raffle"
+        ],
+        bump = raffle.bump,
+        has_one = entrants
+    )]
+    pub raffle: Box<Account<'info, Raffle>>,
+
+    pub entrants: AccountLoader<'info, Entrants>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"RAFFLE",
+            raffle.key().as_ref(),
+            entrant.key().as_ref(),
+            b"entrant-tally"
+        ],
+        bump = entrant_tally.bump,
+        has_one = raffle,
+        has_one = entrant
+    )]
+    pub entrant_tally: Box<Account<'info, EntrantTally>>,
+
+    #[account(mut)]
+    pub entrant: Signer<'info>,
+
+    #[account(
+        address = match raffle.payment_type {
+            PaymentType::Token { token_mint, ticket_price: _ } => token_mint,
+            _ => return err!(RaffleError::TokenMintUnexpected)
+        } @ RaffleError::InvalidTokenMint
+    )]
+    pub token_mint: Option<Box<InterfaceAccount<'info, token_interface::Mint>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = raffle,
+        associated_token::token_program = token_program
+    )]
+    pub token_vault: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+
+    #[account(
+        init_if_needed,
+        payer = entrant,
+        associated_token::mint = token_mint,
+        associated_token::authority = entrant,
+        associated_token::token_program = token_program
+    )]
+    pub token_destination: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+
+    #[account(
+        mut,
+        close = entrant,
+        seeds = [
+            b"RAFFLE",
+            raffle.key().as_ref(),
+            nft_entry.mint.as_ref(),
+            b"nft-entry"
+        ],
+        bump = nft_entry.bump,
+        has_one = raffle,
+        has_one = entrant
+    )]
+    pub nft_entry: Option<Box<Account<'info, NftEntry>>>,
+
+    #[account(address = nft_entry.as_ref().ok_or(RaffleError::NftEntryNeeded)?.mint @ RaffleError::NftUnexpected)]
+    pub nft_mint: Option<Box<Account<'info, Mint>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = nft_mint,
+        associated_token::authority = raffle
+    )]
+    pub nft_source: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(
+        init_if_needed,
+        payer = entrant,
+        associated_token::mint = nft_mint,
+        associated_token::authority = entrant
+    )]
+    pub nft_destination: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(mut)]
+    pub nft_metadata: Option<Box<Account<'info, MetadataAccount>>>,
+    pub nft_edition: Option<Box<Account<'info, MasterEditionAccount>>>,
+
+    #[account(mut)]
+    pub source_token_record: Option<Box<Account<'info, TokenRecordAccount>>>,
+    /// CHECK: this account is initialized in the CPI call
+    #[account(mut)]
+    pub destination_token_record: Option<AccountInfo<'info>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub nft_token_program: Option<Program<'info, Token>>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub metadata_program: Option<Program<'info, Metadata>>,
+    /// CHECK: account checked in CPI
+    pub sysvar_instructions: Option<AccountInfo<'info>>,
+    /// CHECK: account checked in CPI
+    pub auth_rules: Option<AccountInfo<'info>>,
+    /// CHECK: account checked in CPI
+    pub auth_rules_program: Option<AccountInfo<'info>>,
+}
+
+impl<'info> ClaimRefund<'info> {
+    fn transfer_nft(&self) -> Result<()> {
+        let entrants_key = &self.raffle.entrants;
+        let bump = self.raffle.bump;
+        let metadata_program = self
+            .metadata_program
+            .as_ref()
+            .expect("metadata_program expected");
+        let token = &self
+            .nft_source
+            .as_ref()
+            .expect("nft_source expected")
+            .to_account_info();
+        let token_owner = &self.raffle.to_account_info();
+        let payer = &self.entrant.to_account_info();
+        let destination_token = &self
+            .nft_destination
+            .as_ref()
+            .expect("nft_destination expected")
+            .to_account_info();
+        let destination_owner = &self.entrant.to_account_info();
+        let mint = &self
+            .nft_mint
+            .as_ref()
+            .expect("nft_mint expected")
+            .to_account_info();
+        let metadata = &self
+            .nft_metadata
+            .as_ref()
+            .expect("nft_metadata expected")
+            .to_account_info();
+        let edition = &self
+            .nft_edition
+            .as_ref()
+            .expect("nft_edition expected")
+            .to_account_info();
+        let system_program = &self.system_program.to_account_info();
+        let sysvar_instructions = self
+            .sysvar_instructions
+            .as_ref()
+            .expect("sysvar_instructions expected");
+        let spl_token_program = &self
+            .nft_token_program
+            .as_ref()
+            .expect("nft_token_program expected")
+            .to_account_info();
+        let spl_ata_program = &self.associated_token_program.to_account_info();
+        let auth_rules_program = self.auth_rules_program.as_ref();
+        let auth_rules = self.auth_rules.as_ref();
+        let token_record = &self
+            .source_token_record
+            .as_ref()
+            .map(|token_record| token_record.to_account_info());
+        let destination_token_record = self.destination_token_record.as_ref();
+
+        let metadata_program = metadata_program.to_account_info();
+        let mut cpi_transfer = TransferV1CpiBuilder::new(&metadata_program);
+
+        cpi_transfer
+            .token(token)
+            .token_owner(token_owner)
+            .destination_token(destination_token)
+            .destination_owner(destination_owner)
+            .mint(mint)
+            .metadata(metadata)
+            .edition(Some(edition))
+            .authority(token_owner)
+            .payer(payer)
+            .system_program(system_program)
+            .sysvar_instructions(sysvar_instructions)
+            .spl_token_program(spl_token_program)
+            .spl_ata_program(spl_ata_program)
+            .authorization_rules_program(auth_rules_program)
+            .authorization_rules(auth_rules)
+            .token_record(token_record.as_ref())
+            .destination_token_record(destination_token_record)
+            .amount(1);
+
+        let authority_seed = &[
+            &b"RAFFLE"[..],
+            &entrants_key.as_ref(),
+            &b"raffle"[..],
+            &[bump],
+        ];
+
+        // performs the CPI
+        cpi_transfer.invoke_signed(&[authority_seed])?;
+        Ok(())
+    }
+
+    pub fn close_nft_source_ctx(&self) -> CpiContext<'_, '_, '_, 'info, CloseAccount<'info>> {
+        let cpi_accounts = CloseAccount {
+            account: self
+                .nft_source
+                .as_ref()
+                .expect("nft_source expected")
+                .to_account_info(),
+            destination: self.entrant.to_account_info(),
+            authority: self.raffle.to_account_info(),
+        };
+        let cpi_program = self
+            .nft_token_program
+            .as_ref()
+            .expect("nft_token_program expected")
+            .to_account_info();
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+
+    pub fn transfer_token_ctx(&self) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        let cpi_accounts = TransferChecked {
+            from: self
+                .token_vault
+                .as_ref()
+                .expect("token_vault expected")
+                .to_account_info(),
+            mint: self
+                .token_mint
+                .as_ref()
+                .expect("token_mint expected")
+                .to_account_info(),
+            to: self
+                .token_destination
+                .as_ref()
+                .expect("token_destination expected")
+                .to_account_info(),
+            authority: self.raffle.to_account_info(),
+        };
+
+        let cpi_program = self.token_program.to_account_info();
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+}
+
+pub fn claim_refund_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, ClaimRefund<'info>>,
+) -> Result<()> {
+    let current_time = Clock::get().unwrap().unix_timestamp;
+    let total_tickets = ctx.accounts.entrants.load()?.total;
+    ctx.accounts
+        .raffle
+        .sync_state(total_tickets, current_time)?;
+
+    let raffle = &ctx.accounts.raffle;
+    require!(
+        raffle.state == RaffleState::Refunding,
+        RaffleError::RaffleNotRefunding
+    );
+
+    let entrants_key = raffle.entrants;
+    let bump = raffle.bump;
+
+    let authority_seed = &[
+        &b"RAFFLE"[..],
+        &entrants_key.as_ref(),
+        &b"raffle"[..],
+        &[bump],
+    ];
+
+    let (tickets_refunded, amount, nft) = match raffle.payment_type {
+        PaymentType::Token {
+            token_mint: _,
+            ticket_price: _,
+        } => {
+            let tickets = ctx.accounts.entrant_tally.tickets;
+            let decimals = ctx
+                .accounts
+                .token_mint
+                .as_ref()
+                .ok_or(RaffleError::TokenMintNeeded)?
+                .decimals;
+
+            // what this wallet's purchases added to the vault, so Token-2022 transfer fees withheld
+            // on purchase fall on each buyer and a carried jackpot pot is left in place
+            let refund = ctx.accounts.entrant_tally.paid;
+
+            if refund > 0 {
+                transfer_checked_with_hooks(
+                    ctx.accounts
+                        .transfer_token_ctx()
+                        .with_signer(&[authority_seed])
+                        .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+                    refund,
+                    decimals,
+                )?;
+            }
+
+            (tickets, refund, None)
+        }
+        PaymentType::Nft { collection: _ } => {
+            require!(
+                ctx.accounts.nft_entry.is_some(),
+                RaffleError::NftEntryNeeded
+            );
+
+            ctx.accounts.transfer_nft()?;
+
+            close_account(
+                ctx.accounts
+                    .close_nft_source_ctx()
+                    .with_signer(&[authority_seed]),
+            )?;
+
+            (
+                1,
+                0,
+                ctx.accounts
+                    .nft_mint
+                    .as_ref()
+                    .map(|nft_mint| nft_mint.key()),
+            )
+        }
+        _ => return err!(RaffleError::InvalidInstruction),
+    };
+
+    let entrant_tally = &mut ctx.accounts.entrant_tally;
+    entrant_tally.tickets = entrant_tally.tickets.saturating_sub(tickets_refunded);
+
+    emit_cpi!(RefundClaimed {
+        raffle: ctx.accounts.raffle.key(),
+        entrant: ctx.accounts.entrant.key(),
+        tickets: tickets_refunded,
+        amount,
+        nft,
+    });
+
+    if entrant_tally.tickets == 0 {
+        ctx.accounts
+            .entrant_tally
+            .close(ctx.accounts.entrant.to_account_info())?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    state::{EntrantTally, Raffle, RaffleState},
+    RaffleError,
+};
+
+#[derive(Accounts)]
+pub struct CloseEntrantTally<'info> {
+    #[account(
+        seeds = [
+            b"RAFFLE",
+            raffle.entrants.as_ref(),
+            b"raffle"
+        ],
+        bump = raffle.bump
+    )]
+    pub raffle: Box<Account<'info, Raffle>>,
+
+    #[account(
+        mut,
+        close = entrant,
+        seeds = [
+            b"RAFFLE",
+            raffle.key().as_ref(),
+            entrant.key().as_ref(),
+            b"entrant-tally"
+        ],
+        bump = entrant_tally.bump,
+        has_one = raffle,
+        has_one = entrant
+    )]
+    pub entrant_tally: Box<Account<'info, EntrantTally>>,
+
+    #[account(mut)]
+    pub entrant: Signer<'info>,
+}
+
+/// Returns the tally's rent to the entrant once the raffle is drawn, as it is only needed for
+/// purchases and refunds. If the raffle has a consolation pool the tally stays open until the
+/// winners are final and the entrant has claimed any share they are owed.
+pub fn close_entrant_tally_handler(ctx: Context<CloseEntrantTally>) -> Result<()> {
+    let raffle = &ctx.accounts.raffle;
+    let entrant_tally = &ctx.accounts.entrant_tally;
+
+    require!(
+        matches!(raffle.state, RaffleState::Drawn | RaffleState::Claimed),
+        RaffleError::NotDrawn
+    );
+
+    if raffle.consolation.is_some() && !entrant_tally.consolation_claimed {
+        require!(raffle.winners_final(), RaffleError::WinnersNotFinal);
+
+        let winning_tickets = raffle
+            .winners
+            .iter()
+            .filter(|w| w.entrant == entrant_tally.entrant)
+            .count() as u32;
+
+        require_eq!(
+            entrant_tally.tickets.saturating_sub(winning_tickets),
+            0,
+            RaffleError::ConsolationUnclaimed
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,151 @@
+use anchor_lang::prelude::*;
+use mpl_bubblegum::{instructions::TransferCpiBuilder, utils::get_asset_id};
+
+use crate::{
+    events::NftCollected,
+    state::{EntryType, PaymentType, Raffle, Raffler},
+    RaffleError, COMPRESSION_PROGRAM, NOOP_PROGRAM,
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CollectCnft<'info> {
+    #[account(
+        seeds = [
+            b"RAFFLE",
+            raffler.authority.as_ref(),
+            b"raffler"
+        ],
+        bump = raffler.bump,
+    )]
+    pub raffler: Box<Account<'info, Raffler>>,
+
+    #[account(
+        seeds = [
+            b"RAFFLE",
+            raffle.entrants.key().as_ref(),
+            b"raffle"
+        ],
+        bump = raffle.bump,
+        has_one = raffler
+    )]
+    pub raffle: Box<Account<'info, Raffle>>,
+
+    /// CHECK: checked in CPI
+    #[account(mut)]
+    pub tree_config: AccountInfo<'info>,
+
+    /// CHECK: checked in CPI
+    #[account(mut)]
+    pub merkle_tree: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(address = raffler.treasury)]
+    pub treasury: SystemAccount<'info>,
+
+    /// CHECK: explicit address check
+    #[account(address = mpl_bubblegum::ID)]
+    pub bubblegum_program: AccountInfo<'info>,
+    /// CHECK: explicit address check
+    #[account(address = NOOP_PROGRAM)]
+    pub log_wrapper: AccountInfo<'info>,
+    /// CHECK: explicit address check
+    #[account(address = COMPRESSION_PROGRAM)]
+    pub compression_program: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CollectCnft<'info> {
+    fn transfer_cnft(
+        &self,
+        proof: &[AccountInfo<'info>],
+        root: [u8; 32],
+        data_hash: [u8; 32],
+        creator_hash: [u8; 32],
+        nonce: u64,
+        index: u32,
+    ) -> Result<()> {
+        let entrants_key = &self.raffle.entrants;
+        let bump = self.raffle.bump;
+        let bubblegum_program = &self.bubblegum_program.to_account_info();
+        let tree_config = &self.tree_config.to_account_info();
+        let leaf_owner = &self.raffle.to_account_info();
+        let new_leaf_owner = &self.treasury.to_account_info();
+        let merkle_tree = &self.merkle_tree.to_account_info();
+        let log_wrapper = &self.log_wrapper.to_account_info();
+        let compression_program = &self.compression_program.to_account_info();
+        let system_program = &self.system_program.to_account_info();
+
+        let proof_accounts: Vec<(&AccountInfo<'info>, bool, bool)> =
+            proof.iter().map(|node| (node, false, false)).collect();
+
+        let mut cpi_transfer = TransferCpiBuilder::new(bubblegum_program);
+
+        cpi_transfer
+            .tree_config(tree_config)
+            .leaf_owner(leaf_owner, true)
+            .leaf_delegate(leaf_owner, false)
+            .new_leaf_owner(new_leaf_owner)
+            .merkle_tree(merkle_tree)
+            .log_wrapper(log_wrapper)
+            .compression_program(compression_program)
+            .system_program(system_program)
+            .root(root)
+            .data_hash(data_hash)
+            .creator_hash(creator_hash)
+            .nonce(nonce)
+            .index(index)
+            .add_remaining_accounts(&proof_accounts);
+
+        let authority_seed = &[
+            &b"RAFFLE"[..],
+            &entrants_key.as_ref(),
+            &b"raffle"[..],
+            &[bump],
+        ];
+
+        // performs the CPI
+        cpi_transfer.invoke_signed(&[authority_seed])?;
+        Ok(())
+    }
+}
+
+pub fn collect_cnft_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, CollectCnft<'info>>,
+    root: [u8; 32],
+    data_hash: [u8; 32],
+    creator_hash: [u8; 32],
+    nonce: u64,
+    index: u32,
+) -> Result<()> {
+    let raffle = &ctx.accounts.raffle;
+    require!(raffle.is_drawn(), RaffleError::NotDrawn);
+    require!(
+        matches!(raffle.payment_type, PaymentType::Cnft { collection: _ })
+            && matches!(raffle.entry_type, EntryType::Spend),
+        RaffleError::InvalidInstruction
+    );
+
+    // the prize is held by the raffle too, and only leaves through claim_prize
+    let asset_id = get_asset_id(&ctx.accounts.merkle_tree.key(), nonce);
+    require_keys_neq!(asset_id, raffle.prize, RaffleError::InvalidPrize);
+
+    ctx.accounts.transfer_cnft(
+        ctx.remaining_accounts,
+        root,
+        data_hash,
+        creator_hash,
+        nonce,
+        index,
+    )?;
+
+    emit_cpi!(NftCollected {
+        raffle: ctx.accounts.raffle.key(),
+        nft: asset_id,
+        destination: ctx.accounts.treasury.key(),
+    });
+
+    Ok(())
+}
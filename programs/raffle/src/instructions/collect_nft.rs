@@ -9,10 +9,12 @@ use anchor_spl::{
 };
 
 use crate::{
-    state::{Raffle, Raffler},
+    events::NftCollected,
+    state::{EntryType, NftEntry, PrizeBundle, Raffle, Raffler},
     RaffleError,
 };
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct CollectNft<'info> {
     #[account(
@@ -55,6 +57,35 @@ pub struct CollectNft<'info> {
     )]
     pub nft_destination: Account<'info, TokenAccount>,
 
+    /// refund record for raffles with min_tickets, closed back to the entrant that sent the NFT
+    #[account(
+        mut,
+        seeds = [
+            b"RAFFLE",
+            raffle.key().as_ref(),
+            nft_mint.key().as_ref(),
+            b"nft-entry"
+        ],
+        bump = nft_entry.bump,
+        has_one = raffle
+    )]
+    pub nft_entry: Option<Box<Account<'info, NftEntry>>>,
+
+    /// CHECK: checked against nft_entry.entrant
+    #[account(mut)]
+    pub entrant: Option<AccountInfo<'info>>,
+
+    /// needed when the raffle has a bundle, so bundle items can't be collected as entries
+    #[account(
+        seeds = [
+            b"RAFFLE",
+            raffle.key().as_ref(),
+            b"prize-bundle"
+        ],
+        bump = prize_bundle.bump
+    )]
+    pub prize_bundle: Option<Box<Account<'info, PrizeBundle>>>,
+
     #[account(mut)]
     pub nft_metadata: Box<Account<'info, MetadataAccount>>,
     pub nft_edition: Box<Account<'info, MasterEditionAccount>>,
@@ -158,7 +189,26 @@ impl<'info> CollectNft<'info> {
 
 pub fn collect_nft_handler(ctx: Context<CollectNft>) -> Result<()> {
     let raffle = &ctx.accounts.raffle;
-    require!(raffle.randomness.is_some(), RaffleError::NotDrawn);
+    require!(raffle.is_drawn(), RaffleError::NotDrawn);
+    require!(
+        !matches!(raffle.entry_type, EntryType::Stake { minimum_period: _ }),
+        RaffleError::InvalidInstruction
+    );
+
+    // the prize and bundle items are held by the raffle alongside the entries
+    let nft_mint = ctx.accounts.nft_mint.key();
+    require_keys_neq!(nft_mint, raffle.prize, RaffleError::InvalidPrize);
+    if raffle.bundle_items > 0 {
+        let prize_bundle = ctx
+            .accounts
+            .prize_bundle
+            .as_ref()
+            .ok_or(RaffleError::BundleNeeded)?;
+        require!(
+            prize_bundle.items.iter().all(|item| item.mint != nft_mint),
+            RaffleError::InvalidPrize
+        );
+    }
 
     let entrants_key = raffle.entrants;
 
@@ -177,5 +227,33 @@ pub fn collect_nft_handler(ctx: Context<CollectNft>) -> Result<()> {
         ctx.accounts
             .close_account_ctx()
             .with_signer(&[authority_seed]),
-    )
+    )?;
+
+    if ctx.accounts.raffle.min_tickets.is_some() {
+        let nft_entry = ctx
+            .accounts
+            .nft_entry
+            .as_ref()
+            .ok_or(RaffleError::NftEntryNeeded)?;
+        let entrant = ctx
+            .accounts
+            .entrant
+            .as_ref()
+            .ok_or(RaffleError::NftEntryNeeded)?;
+        require_keys_eq!(
+            entrant.key(),
+            nft_entry.entrant,
+            RaffleError::InvalidEntrant
+        );
+
+        nft_entry.close(entrant.to_account_info())?;
+    }
+
+    emit_cpi!(NftCollected {
+        raffle: ctx.accounts.raffle.key(),
+        nft: nft_mint,
+        destination: ctx.accounts.treasury.key(),
+    });
+
+    Ok(())
 }
@@ -2,8 +2,13 @@ use anchor_lang::prelude::*;
 
 use solana_randomness_service::{SimpleRandomnessV1Account, ID as SolanaRandomnessServiceID};
 
-use crate::{state::Raffle, RaffleError};
+use crate::{
+    events::RandomnessFulfilled,
+    state::{Entrants, Raffle},
+    RaffleError,
+};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct ConsumeRandomness<'info> {
     #[account(
@@ -16,19 +21,41 @@ pub struct ConsumeRandomness<'info> {
 
     pub request: Box<Account<'info, SimpleRandomnessV1Account>>,
 
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [
+            b"RAFFLE",
+            entrants.key().as_ref(),
+            b"raffle"
+        ],
+        bump = raffle.bump,
+        has_one = entrants
+    )]
     pub raffle: Account<'info, Raffle>,
+
+    pub entrants: AccountLoader<'info, Entrants>,
 }
 
 pub fn consume_randomness_handler(ctx: Context<ConsumeRandomness>, result: Vec<u8>) -> Result<()> {
     let raffle = &mut ctx.accounts.raffle;
 
-    require!(raffle.randomness.is_none(), RaffleError::WinnerAlreadyDrawn);
+    let request = &ctx.accounts.request;
+
+    // only the request made in draw_winner for this raffle can settle it
+    raffle.assert_randomness_request(request.key(), request.request_slot)?;
+
+    let randomness: [u8; 32] = result
+        .try_into()
+        .map_err(|_| RaffleError::InvalidRandomnessLength)?;
+
+    raffle.settle_randomness(randomness, &ctx.accounts.entrants)?;
 
-    // we are trusting switchboard to give us the correct number of bytes.
-    raffle.randomness = Some(result.try_into().unwrap_or_else(|v: Vec<u8>| {
-        panic!("Expected a Vec of length {} but it was {}", 32, v.len())
-    }));
+    emit_cpi!(RandomnessFulfilled {
+        raffle: raffle.key(),
+        randomness,
+        winning_tickets: raffle.winners.iter().map(|w| w.ticket_index).collect(),
+        winning_entrants: raffle.winners.iter().map(|w| w.entrant).collect(),
+    });
 
     Ok(())
 }
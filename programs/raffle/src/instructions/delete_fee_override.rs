@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+
+use crate::{program::Raffle, state::FeeOverride, RaffleError};
+
+#[derive(Accounts)]
+pub struct DeleteFeeOverride<'info> {
+    #[account(
+        mut,
+        close = authority,
+        seeds = [
+            b"RAFFLE",
+            fee_override.raffler.as_ref(),
+            b"fee-override"
+        ],
+        bump = fee_override.bump
+    )]
+    pub fee_override: Account<'info, FeeOverride>,
+
+    #[account(
+        constraint = program.programdata_address()? == Some(program_data.key()) @ RaffleError::AdminOnly
+    )]
+    pub program: Program<'info, Raffle>,
+
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(authority.key()) @ RaffleError::AdminOnly
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+pub fn delete_fee_override_handler(_ctx: Context<DeleteFeeOverride>) -> Result<()> {
+    Ok(())
+}
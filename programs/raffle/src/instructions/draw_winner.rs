@@ -1,13 +1,18 @@
 use anchor_lang::prelude::*;
-use solana_randomness_service::{program::SolanaRandomnessService, TransactionOptions};
+use solana_randomness_service::{
+    cpi::accounts::SimpleRandomnessV1Request, program::SolanaRandomnessService,
+};
 
-use switchboard_solana::{get_ixn_discriminator, prelude::*};
+use switchboard_solana::prelude::*;
 
 use crate::{
-    state::{Entrants, Raffle},
-    RaffleError, ID,
+    events::RandomnessRequested,
+    state::{Entrants, Raffle, RandomnessSource},
+    utils::request_randomness,
+    RaffleError,
 };
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct DrawWinner<'info> {
     /// The Solana Randomness Service program.
@@ -58,7 +63,7 @@ pub struct DrawWinner<'info> {
     )]
     pub raffle: Account<'info, Raffle>,
 
-    pub entrants: Account<'info, Entrants>,
+    pub entrants: AccountLoader<'info, Entrants>,
 
     /// The account that will pay for the randomness request.
     #[account(mut)]
@@ -73,6 +78,21 @@ pub struct DrawWinner<'info> {
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
+impl<'info> DrawWinner<'info> {
+    pub fn randomness_request_accounts(&self) -> SimpleRandomnessV1Request<'info> {
+        SimpleRandomnessV1Request {
+            request: self.randomness_request.to_account_info(),
+            escrow: self.randomness_escrow.to_account_info(),
+            state: self.randomness_state.to_account_info(),
+            mint: self.randomness_mint.to_account_info(),
+            payer: self.payer.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+            associated_token_program: self.associated_token_program.to_account_info(),
+        }
+    }
+}
+
 pub fn draw_winner_handler(
     ctx: Context<DrawWinner>,
     uri: String,
@@ -80,47 +100,35 @@ pub fn draw_winner_handler(
 ) -> anchor_lang::prelude::Result<()> {
     let priority_fee = priority_fee.unwrap_or(100);
     let current_time = Clock::get().unwrap().unix_timestamp;
-    let raffle = &ctx.accounts.raffle;
-    let entrants = &ctx.accounts.entrants;
+    let raffle = &mut ctx.accounts.raffle;
+    let total = ctx.accounts.entrants.load()?.total;
     require_gt!(uri.len(), 0, RaffleError::UriRequired);
+
     require!(
-        current_time >= raffle.end_time || entrants.total >= entrants.max,
-        RaffleError::RaffleNotEnded
+        raffle.randomness_source == RandomnessSource::SwitchboardService,
+        RaffleError::InvalidRandomnessSource
     );
-    require!(raffle.randomness.is_none(), RaffleError::WinnerAlreadyDrawn,);
-
-    solana_randomness_service::cpi::simple_randomness_v1(
-        CpiContext::new(
-            ctx.accounts.randomness_service.to_account_info(),
-            solana_randomness_service::cpi::accounts::SimpleRandomnessV1Request {
-                request: ctx.accounts.randomness_request.to_account_info(),
-                escrow: ctx.accounts.randomness_escrow.to_account_info(),
-                state: ctx.accounts.randomness_state.to_account_info(),
-                mint: ctx.accounts.randomness_mint.to_account_info(),
-                payer: ctx.accounts.payer.to_account_info(),
-                system_program: ctx.accounts.system_program.to_account_info(),
-                token_program: ctx.accounts.token_program.to_account_info(),
-                associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
-            },
-        ),
-        32,
-        solana_randomness_service::Callback {
-            program_id: ID,
-            accounts: vec![
-                AccountMeta::new_readonly(ctx.accounts.randomness_state.key(), true).into(),
-                AccountMeta::new_readonly(ctx.accounts.randomness_request.key(), false).into(),
-                AccountMeta::new(ctx.accounts.raffle.key(), false).into(),
-            ],
-            ix_data: get_ixn_discriminator("consume_randomness").to_vec(),
-        },
-        Some(TransactionOptions {
-            compute_units: Some(1_000_000),
-            compute_unit_price: Some(priority_fee),
-        }),
+
+    raffle.begin_draw(total, current_time)?;
+
+    request_randomness(
+        ctx.accounts.randomness_service.to_account_info(),
+        ctx.accounts.randomness_request_accounts(),
+        ctx.accounts.raffle.key(),
+        ctx.accounts.entrants.key(),
+        ctx.accounts.event_authority.key(),
+        priority_fee,
     )?;
 
     let raffle = &mut ctx.accounts.raffle;
     raffle.uri = uri;
+    raffle.record_randomness_request(ctx.accounts.randomness_request.key())?;
+
+    emit_cpi!(RandomnessRequested {
+        raffle: raffle.key(),
+        request: ctx.accounts.randomness_request.key(),
+        replaces: None,
+    });
 
     Ok(())
 }
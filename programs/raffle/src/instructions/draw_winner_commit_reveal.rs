@@ -0,0 +1,74 @@
+use anchor_lang::{prelude::*, solana_program::keccak};
+
+use crate::{
+    events::SecretRevealed,
+    state::{Entrants, Raffle, RandomnessSource},
+    RaffleError, COMMIT_REVEAL_SLOT_DELAY,
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DrawWinnerCommitReveal<'info> {
+    #[account(
+        mut,
+        has_one = entrants,
+        seeds = [
+            b"RAFFLE",
+            entrants.key().as_ref(),
+            b"raffle"
+        ],
+        bump = raffle.bump
+    )]
+    pub raffle: Account<'info, Raffle>,
+
+    pub entrants: AccountLoader<'info, Entrants>,
+}
+
+/// Reveals the secret committed at creation. The draw is settled by the hash of a slot
+/// that has not been produced yet, so the secret alone does not decide the winners.
+pub fn draw_winner_commit_reveal_handler(
+    ctx: Context<DrawWinnerCommitReveal>,
+    uri: String,
+    secret: [u8; 32],
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let raffle = &mut ctx.accounts.raffle;
+    let total = ctx.accounts.entrants.load()?.total;
+    require_gt!(uri.len(), 0, RaffleError::UriRequired);
+
+    // the first draw makes the secret public, so re-draws use On-Demand
+    let RandomnessSource::CommitReveal {
+        commitment,
+        secret: None,
+    } = raffle.randomness_source
+    else {
+        return err!(RaffleError::InvalidRandomnessSource);
+    };
+
+    require!(
+        keccak::hash(&secret).to_bytes() == commitment,
+        RaffleError::InvalidCommitmentReveal
+    );
+
+    raffle.begin_draw(total, clock.unix_timestamp)?;
+
+    raffle.randomness_source = RandomnessSource::CommitReveal {
+        commitment,
+        secret: Some(secret),
+    };
+    raffle.uri = uri;
+    raffle.randomness_request = None;
+    raffle.request_slot = clock
+        .slot
+        .checked_add(COMMIT_REVEAL_SLOT_DELAY)
+        .ok_or(RaffleError::ProgramAddError)?;
+    raffle.requested_at = clock.unix_timestamp;
+
+    emit_cpi!(SecretRevealed {
+        raffle: raffle.key(),
+        secret,
+        target_slot: raffle.request_slot,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use anchor_lang::prelude::*;
+use switchboard_on_demand::accounts::RandomnessAccountData;
+
+use crate::{
+    events::RandomnessRequested,
+    state::{Entrants, ProgramConfig, Raffle, RaffleState, RandomnessSource},
+    RaffleError, SWITCHBOARD_ON_DEMAND,
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DrawWinnerOnDemand<'info> {
+    #[account(
+        seeds = [b"program-config"],
+        bump = program_config.bump
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+
+    #[account(
+        mut,
+        has_one = entrants,
+        seeds = [
+            b"RAFFLE",
+            entrants.key().as_ref(),
+            b"raffle"
+        ],
+        bump = raffle.bump
+    )]
+    pub raffle: Account<'info, Raffle>,
+
+    pub entrants: AccountLoader<'info, Entrants>,
+
+    /// The Switchboard On-Demand randomness account, committed in this transaction.
+    /// CHECK: owner is checked here and the data is parsed in the handler
+    #[account(owner = SWITCHBOARD_ON_DEMAND)]
+    pub randomness_account: AccountInfo<'info>,
+}
+
+/// Binds the raffle to an On-Demand randomness account that was committed to the previous
+/// slot, so its value cannot be known yet. A request that was never revealed can be
+/// replaced once the configured timeout has passed. This also draws commit-reveal raffles
+/// whose secret was never revealed, and re-draws them once the secret is public.
+pub fn draw_winner_on_demand_handler(ctx: Context<DrawWinnerOnDemand>, uri: String) -> Result<()> {
+    let clock = Clock::get()?;
+    let raffle = &mut ctx.accounts.raffle;
+    let total = ctx.accounts.entrants.load()?.total;
+    require_gt!(uri.len(), 0, RaffleError::UriRequired);
+
+    let timeout = ctx.accounts.program_config.randomness_timeout;
+
+    match raffle.randomness_source {
+        RandomnessSource::SwitchboardOnDemand => (),
+        RandomnessSource::CommitReveal { secret: None, .. } => {
+            raffle.assert_reveal_missed(timeout, clock.unix_timestamp)?;
+            raffle.randomness_source = RandomnessSource::SwitchboardOnDemand;
+        }
+        RandomnessSource::CommitReveal {
+            secret: Some(_), ..
+        } if raffle.state == RaffleState::Drawn => {
+            raffle.randomness_source = RandomnessSource::SwitchboardOnDemand;
+        }
+        _ => return err!(RaffleError::InvalidRandomnessSource),
+    }
+
+    let stale_request = raffle.randomness_request;
+
+    if raffle.state == RaffleState::AwaitingRandomness {
+        raffle.assert_request_timed_out(timeout, clock.unix_timestamp)?;
+    } else {
+        raffle.begin_draw(total, clock.unix_timestamp)?;
+    }
+
+    let seed_slot = {
+        let randomness_data =
+            RandomnessAccountData::parse(ctx.accounts.randomness_account.data.borrow())
+                .map_err(|_| ErrorCode::AccountDidNotDeserialize)?;
+        randomness_data.seed_slot
+    };
+
+    require_eq!(
+        seed_slot,
+        clock.slot.saturating_sub(1),
+        RaffleError::StaleRandomnessCommitment
+    );
+
+    raffle.uri = uri;
+    raffle.record_randomness_request(ctx.accounts.randomness_account.key())?;
+    raffle.request_slot = seed_slot;
+
+    emit_cpi!(RandomnessRequested {
+        raffle: raffle.key(),
+        request: ctx.accounts.randomness_account.key(),
+        replaces: stale_request,
+    });
+
+    Ok(())
+}
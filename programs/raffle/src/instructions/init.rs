@@ -1,6 +1,7 @@
 use anchor_lang::prelude::*;
 
 use crate::{
+    events::RafflerUpdated,
     state::{ProgramConfig, Raffler},
     RaffleError, STAKE_PROGRAM,
 };
@@ -9,6 +10,7 @@ use proc_macro_regex::regex;
 
 regex!(regex_slug "^(?:[_a-z0-9]+)*$");
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct Init<'info> {
     #[account(
@@ -98,5 +100,13 @@ pub fn init_handler(
         ctx.bumps.raffler,
     );
 
+    emit_cpi!(RafflerUpdated {
+        raffler: raffler.key(),
+        authority: raffler.authority,
+        treasury: raffler.treasury,
+        staker: raffler.staker,
+        is_active: raffler.is_active,
+    });
+
     Ok(())
 }
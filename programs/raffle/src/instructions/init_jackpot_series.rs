@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::{
+    state::{JackpotSeries, Raffler},
+    RaffleError,
+};
+
+#[derive(Accounts)]
+pub struct InitJackpotSeries<'info> {
+    #[account(
+        seeds = [
+            b"RAFFLE",
+            raffler.authority.as_ref(),
+            b"raffler"
+        ],
+        bump = raffler.bump,
+        has_one = authority @ RaffleError::Unauthorized
+    )]
+    pub raffler: Box<Account<'info, Raffler>>,
+
+    #[account(
+        init,
+        space = JackpotSeries::LEN,
+        payer = authority,
+        seeds = [
+            b"RAFFLE",
+            raffler.key().as_ref(),
+            token_mint.key().as_ref(),
+            b"jackpot-series"
+        ],
+        bump
+    )]
+    pub jackpot_series: Box<Account<'info, JackpotSeries>>,
+
+    pub token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn init_jackpot_series_handler(
+    ctx: Context<InitJackpotSeries>,
+    winner_share: u16,
+) -> Result<()> {
+    require!(
+        winner_share > 0 && winner_share <= 10_000,
+        RaffleError::InvalidWinnerShare
+    );
+
+    **ctx.accounts.jackpot_series = JackpotSeries::init(
+        ctx.accounts.raffler.key(),
+        ctx.accounts.token_mint.key(),
+        winner_share,
+        ctx.bumps.jackpot_series,
+    );
+
+    Ok(())
+}
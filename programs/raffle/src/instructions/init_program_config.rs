@@ -1,6 +1,6 @@
 use anchor_lang::prelude::*;
 
-use crate::{program::Raffle, state::ProgramConfig, RaffleError};
+use crate::{events::ConfigUpdated, program::Raffle, state::ProgramConfig, RaffleError};
 
 #[derive(Accounts)]
 pub struct InitProgramConfig<'info> {
@@ -27,16 +27,37 @@ pub struct InitProgramConfig<'info> {
     pub authority: Signer<'info>,
 
     pub system_program: Program<'info, System>,
+
+    /// CHECK: Only the event authority can invoke self-CPI. Added by hand rather than with
+    /// #[event_cpi] as the program account is already part of this instruction
+    #[account(seeds = [b"__event_authority"], bump)]
+    pub event_authority: AccountInfo<'info>,
 }
 
 pub fn init_program_config_handler(
     ctx: Context<InitProgramConfig>,
     raffle_fee: u64,
     proceeds_share: u16,
+    randomness_timeout: Option<i64>,
 ) -> Result<()> {
     let program_config = &mut ctx.accounts.program_config;
-
-    **program_config = ProgramConfig::init(raffle_fee, proceeds_share, ctx.bumps.program_config);
+    let randomness_timeout =
+        randomness_timeout.unwrap_or(ProgramConfig::DEFAULT_RANDOMNESS_TIMEOUT);
+
+    require_gt!(randomness_timeout, 0, RaffleError::InvalidRandomnessTimeout);
+
+    **program_config = ProgramConfig::init(
+        raffle_fee,
+        proceeds_share,
+        randomness_timeout,
+        ctx.bumps.program_config,
+    );
+
+    emit_cpi!(ConfigUpdated {
+        raffle_fee,
+        proceeds_share,
+        randomness_timeout,
+    });
 
     Ok(())
 }
@@ -1,18 +1,55 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
 use anchor_spl::{
     associated_token::AssociatedToken,
     metadata::{mpl_token_metadata::instructions::TransferV1CpiBuilder, Metadata},
-    token::{transfer, Mint, Token, TokenAccount, Transfer},
+    token::Mint,
+    token_interface::{self, TokenInterface, TransferChecked},
 };
 
 use crate::{
-    state::{Entrants, EntryType, PaymentType, PrizeType, Raffle, Raffler},
-    RaffleError, FEES_WALLET, NATIVE_MINT,
+    events::RaffleCreated,
+    state::{
+        ConsolationPool, EndTimeExtension, Entrants, EntryType, FeeOverride, JackpotSeries,
+        PaymentType, PrizeType, ProgramConfig, Raffle, Raffler, RandomnessSource, UnclaimedPrize,
+    },
+    utils::{
+        get_transfer_fee, split_hook_accounts, transfer_checked_with_hooks, transfer_cnft,
+        transfer_core_asset, CnftArgs,
+    },
+    RaffleError, FEES_WALLET, NATIVE_MINT, STAKE_PROGRAM,
 };
 
+/// consolation pool deposited at creation, see claim_consolation
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct ConsolationArgs {
+    pub amount: u64,
+    /// number of transfer hook accounts for the consolation mint, passed last in the remaining accounts
+    pub hook_accounts: u8,
+}
+
+#[event_cpi]
 #[derive(Accounts)]
-#[instruction(prize_type: PrizeType)]
+#[instruction(
+    prize_type: PrizeType,
+    num_tickets: Option<u32>,
+    entry_type: EntryType,
+    ticket_price: Option<u64>,
+    start_time: Option<i64>,
+    duration: i64,
+    is_gated: bool,
+    max_entrants_pct: Option<u16>,
+    num_winners: Option<u8>
+)]
 pub struct InitRaffle<'info> {
+    #[account(
+        seeds = [b"program-config"],
+        bump = program_config.bump
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+
     #[account(
         seeds = [
             b"RAFFLE",
@@ -24,6 +61,22 @@ pub struct InitRaffle<'info> {
     )]
     pub raffler: Box<Account<'info, Raffler>>,
 
+    #[account(
+        seeds = [
+            b"RAFFLE",
+            raffler.key().as_ref(),
+            b"fee-override"
+        ],
+        bump = fee_override.bump
+    )]
+    pub fee_override: Option<Box<Account<'info, FeeOverride>>>,
+
+    /// CHECK: must match the raffler's linked staker to be exempt from the raffle fee
+    #[account(
+        owner = STAKE_PROGRAM
+    )]
+    pub staker: Option<AccountInfo<'info>>,
+
     #[account(
         init,
         seeds = [
@@ -32,27 +85,28 @@ pub struct InitRaffle<'info> {
             b"raffle"
         ],
         bump,
-        space = Raffle::LEN,
+        space = Raffle::space(num_winners.unwrap_or(1)),
         payer = authority
     )]
     pub raffle: Box<Account<'info, Raffle>>,
 
     #[account(zero)]
-    pub entrants: Box<Account<'info, Entrants>>,
+    pub entrants: AccountLoader<'info, Entrants>,
 
-    pub token_mint: Option<Box<Account<'info, Mint>>>,
+    pub token_mint: Option<Box<InterfaceAccount<'info, token_interface::Mint>>>,
 
     /// CHECK: explicit address check
-    #[account(address = FEES_WALLET)]
+    #[account(mut, address = FEES_WALLET)]
     pub fees_wallet: Option<AccountInfo<'info>>,
 
     #[account(
         init_if_needed,
         payer = authority,
         associated_token::mint = token_mint,
-        associated_token::authority = fees_wallet
+        associated_token::authority = fees_wallet,
+        associated_token::token_program = token_program
     )]
-    pub fees_wallet_token: Option<Box<Account<'info, TokenAccount>>>,
+    pub fees_wallet_token: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
 
     pub entry_collection_mint: Option<Box<Account<'info, Mint>>>,
 
@@ -61,16 +115,18 @@ pub struct InitRaffle<'info> {
         payer = authority,
         associated_token::mint = token_mint,
         associated_token::authority = raffle,
+        associated_token::token_program = token_program
     )]
-    pub token_vault: Option<Box<Account<'info, TokenAccount>>>,
+    pub token_vault: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
 
     #[account(
         init_if_needed,
         payer = authority,
         associated_token::mint = token_mint,
         associated_token::authority = treasury,
+        associated_token::token_program = token_program
     )]
-    pub treasury_token_account: Option<Box<Account<'info, TokenAccount>>>,
+    pub treasury_token_account: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
 
     /// CHECK: explicit address
     #[account(address = raffler.treasury)]
@@ -80,35 +136,129 @@ pub struct InitRaffle<'info> {
         constraint = matches!(prize_type, PrizeType::Token {amount: _}) || prize.supply == 1 @ RaffleError::TokenNotNFT,
         constraint = matches!(prize_type, PrizeType::Token {amount: _}) || prize.decimals == 0 @ RaffleError::TokenNotNFT
     )]
-    pub prize: Box<Account<'info, Mint>>,
+    pub prize: Option<Box<InterfaceAccount<'info, token_interface::Mint>>>,
 
     #[account(
         mut,
         associated_token::mint = prize,
-        associated_token::authority = authority
+        associated_token::authority = authority,
+        associated_token::token_program = prize_token_program
     )]
-    pub prize_token: Box<Account<'info, TokenAccount>>,
+    pub prize_token: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
 
     #[account(
         init_if_needed,
         payer = authority,
         associated_token::mint = prize,
-        associated_token::authority = raffle
+        associated_token::authority = raffle,
+        associated_token::token_program = prize_token_program
+    )]
+    pub prize_custody: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+
+    /// holds the lamports of a SOL prize
+    #[account(
+        mut,
+        seeds = [
+            b"RAFFLE",
+            raffle.key().as_ref(),
+            b"prize-vault"
+        ],
+        bump
+    )]
+    pub prize_vault: Option<SystemAccount<'info>>,
+
+    /// makes this raffle the series' latest round
+    #[account(
+        mut,
+        seeds = [
+            b"RAFFLE",
+            raffler.key().as_ref(),
+            jackpot_series.token_mint.as_ref(),
+            b"jackpot-series"
+        ],
+        bump = jackpot_series.bump,
+        has_one = raffler @ RaffleError::InvalidJackpotSeries
+    )]
+    pub jackpot_series: Option<Box<Account<'info, JackpotSeries>>>,
+
+    pub consolation_mint: Option<Box<InterfaceAccount<'info, token_interface::Mint>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = consolation_mint,
+        associated_token::authority = authority,
+        associated_token::token_program = consolation_token_program
+    )]
+    pub consolation_token: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        associated_token::mint = consolation_mint,
+        associated_token::authority = raffle,
+        associated_token::token_program = consolation_token_program
     )]
-    pub prize_custody: Box<Account<'info, TokenAccount>>,
+    pub consolation_custody: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
 
     #[account(mut)]
     pub authority: Signer<'info>,
 
     // System accounts
-    #[account(address = anchor_spl::token::ID)]
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
+    /// NFT prizes must use the legacy token program, token prizes can use either
+    pub prize_token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub metadata_program: Program<'info, Metadata>,
+    pub consolation_token_program: Option<Interface<'info, TokenInterface>>,
 }
 
 impl<'info> InitRaffle<'info> {
+    /// rafflers with a linked staker are exempt, otherwise any override replaces the program fee
+    pub fn raffle_fee(&self) -> u64 {
+        let is_staker = match (&self.staker, self.raffler.staker) {
+            (Some(staker), Some(linked_staker)) => staker.key() == linked_staker,
+            _ => false,
+        };
+
+        if is_staker {
+            return 0;
+        }
+
+        match &self.fee_override {
+            Some(fee_override) => fee_override.raffle_fee,
+            None => self.program_config.raffle_fee,
+        }
+    }
+
+    pub fn transfer_raffle_fee_ctx(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.authority.to_account_info(),
+            to: self
+                .fees_wallet
+                .as_ref()
+                .expect("fees_wallet expected")
+                .to_account_info(),
+        };
+
+        let cpi_program = self.system_program.to_account_info();
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+
+    pub fn transfer_prize_vault_ctx(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        let cpi_accounts = Transfer {
+            from: self.authority.to_account_info(),
+            to: self
+                .prize_vault
+                .as_ref()
+                .expect("prize_vault expected")
+                .to_account_info(),
+        };
+
+        let cpi_program = self.system_program.to_account_info();
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+
     pub fn transfer_nft(
         &self,
         metadata: &AccountInfo<'info>,
@@ -120,13 +270,25 @@ impl<'info> InitRaffle<'info> {
         auth_rules: Option<AccountInfo<'info>>,
     ) -> Result<()> {
         let metadata_program = &self.metadata_program;
-        let token = &self.prize_token.as_ref().to_account_info();
+        let token = &self
+            .prize_token
+            .as_ref()
+            .expect("prize_token expected")
+            .to_account_info();
         let token_owner = &self.authority.to_account_info();
-        let destination_token = &self.prize_custody.as_ref().to_account_info();
+        let destination_token = &self
+            .prize_custody
+            .as_ref()
+            .expect("prize_custody expected")
+            .to_account_info();
         let destination_owner = &self.raffle.to_account_info();
-        let mint = &self.prize.to_account_info();
+        let mint = &self
+            .prize
+            .as_ref()
+            .expect("prize expected")
+            .to_account_info();
         let system_program = &self.system_program.to_account_info();
-        let spl_token_program = &&self.token_program.to_account_info();
+        let spl_token_program = &&self.prize_token_program.to_account_info();
         let spl_ata_program = &self.associated_token_program.to_account_info();
 
         let mut cpi_transfer = TransferV1CpiBuilder::new(&metadata_program);
@@ -156,14 +318,57 @@ impl<'info> InitRaffle<'info> {
         Ok(())
     }
 
-    pub fn transfer_token_ctx(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
-        let cpi_accounts = Transfer {
-            from: self.prize_token.as_ref().to_account_info(),
-            to: self.prize_custody.as_ref().to_account_info(),
+    pub fn transfer_token_ctx(&self) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        let cpi_accounts = TransferChecked {
+            from: self
+                .prize_token
+                .as_ref()
+                .expect("prize_token expected")
+                .to_account_info(),
+            mint: self
+                .prize
+                .as_ref()
+                .expect("prize expected")
+                .to_account_info(),
+            to: self
+                .prize_custody
+                .as_ref()
+                .expect("prize_custody expected")
+                .to_account_info(),
             authority: self.authority.to_account_info(),
         };
 
-        let cpi_program = self.token_program.to_account_info();
+        let cpi_program = self.prize_token_program.to_account_info();
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+
+    pub fn transfer_consolation_ctx(
+        &self,
+    ) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        let cpi_accounts = TransferChecked {
+            from: self
+                .consolation_token
+                .as_ref()
+                .expect("consolation_token expected")
+                .to_account_info(),
+            mint: self
+                .consolation_mint
+                .as_ref()
+                .expect("consolation_mint expected")
+                .to_account_info(),
+            to: self
+                .consolation_custody
+                .as_ref()
+                .expect("consolation_custody expected")
+                .to_account_info(),
+            authority: self.authority.to_account_info(),
+        };
+
+        let cpi_program = self
+            .consolation_token_program
+            .as_ref()
+            .expect("consolation_token_program expected")
+            .to_account_info();
         CpiContext::new(cpi_program, cpi_accounts)
     }
 }
@@ -178,15 +383,109 @@ pub fn init_raffle_handler<'info>(
     duration: i64,
     is_gated: bool,
     max_entrant_pct: Option<u16>,
+    num_winners: Option<u8>,
+    max_entrant_tickets: Option<u32>,
+    is_compressed: bool,
+    prize_cnft: Option<CnftArgs>,
+    min_tickets: Option<u32>,
+    randomness_source: Option<RandomnessSource>,
+    claim_window: Option<i64>,
+    unclaimed_prize: Option<UnclaimedPrize>,
+    end_time_extension: Option<EndTimeExtension>,
+    consolation: Option<ConsolationArgs>,
 ) -> Result<()> {
-    let remaining_accounts = &mut ctx.remaining_accounts.iter();
+    let (remaining_accounts, consolation_hook_accounts) = split_hook_accounts(
+        ctx.remaining_accounts,
+        consolation.map_or(0, |consolation| consolation.hook_accounts),
+    )?;
+    let remaining_accounts = &mut remaining_accounts.iter();
     require_gte!(60 * 60 * 24 * 30, duration, RaffleError::RaffleTooLong);
+
+    if let Some(extension) = end_time_extension {
+        require!(
+            extension.window > 0 && extension.extension > 0 && extension.max_extension >= 0,
+            RaffleError::InvalidEndTimeExtension
+        );
+        // extensions can't take a raffle past the maximum duration either
+        require_gte!(
+            60 * 60 * 24 * 30,
+            duration
+                .checked_add(extension.max_extension)
+                .ok_or(RaffleError::ProgramAddError)?,
+            RaffleError::RaffleTooLong
+        );
+    }
     // require_gte!(duration, 60 * 5, RaffleError::RaffleTooShort);
 
+    let num_winners = num_winners.unwrap_or(1);
+    require_gt!(num_winners, 0, RaffleError::WinnersRequired);
+    require_gte!(
+        Raffle::MAX_WINNERS,
+        num_winners,
+        RaffleError::TooManyWinners
+    );
+
+    if !matches!(
+        prize_type,
+        PrizeType::Token { amount: _ }
+            | PrizeType::Sol { lamports: _ }
+            | PrizeType::Pot { winner_share: _ }
+    ) {
+        require_eq!(num_winners, 1, RaffleError::MultipleWinnersNft);
+    }
+
+    let randomness_source = randomness_source.unwrap_or(RandomnessSource::SwitchboardService);
+
+    if let Some(claim_window) = claim_window {
+        require_gt!(claim_window, 0, RaffleError::InvalidClaimWindow);
+    }
+
+    // the secret is only revealed at draw time
+    if let RandomnessSource::CommitReveal { commitment, secret } = randomness_source {
+        require!(
+            commitment != [0; 32] && secret.is_none(),
+            RaffleError::InvalidCommitmentReveal
+        );
+        // a draw that expires is refunded, see settle_commit_reveal. NFT entries are only
+        // recorded for refunds when the raffle has min_tickets
+        require!(
+            matches!(entry_type, EntryType::Spend)
+                && !is_compressed
+                && (ctx.accounts.entry_collection_mint.is_none() || min_tickets.is_some()),
+            RaffleError::CommitRevealUnsupported
+        );
+    }
+
+    let max_entrant_pct = max_entrant_pct.unwrap_or(10000);
+    require_gte!(10000, max_entrant_pct, RaffleError::InvalidMaxEntrantPct);
+
+    if max_entrant_tickets.is_some() {
+        require_gt!(
+            max_entrant_tickets.unwrap(),
+            0,
+            RaffleError::InvalidMaxEntrantTickets
+        );
+    }
+
     if num_tickets.is_some() {
         require_gte!(u32::MAX, num_tickets.unwrap(), RaffleError::TooManyTickets);
     }
 
+    if min_tickets.is_some() {
+        let min_tickets = min_tickets.unwrap();
+        require_gt!(min_tickets, 0, RaffleError::InvalidMinTickets);
+        require_gte!(
+            num_tickets.unwrap_or(u32::MAX),
+            min_tickets,
+            RaffleError::InvalidMinTickets
+        );
+        // refunds return the tokens or NFTs held by the raffle, burnt and staked entries can't be refunded
+        require!(
+            matches!(entry_type, EntryType::Spend) && !is_compressed,
+            RaffleError::MinTicketsUnsupported
+        );
+    }
+
     let current_time = Clock::get().unwrap().unix_timestamp;
 
     if start_time.is_some() {
@@ -219,12 +518,30 @@ pub fn init_raffle_handler<'info>(
         )
     }
 
+    if let EntryType::Stake { minimum_period } = entry_type {
+        require_gte!(minimum_period, 0, RaffleError::InvalidStakePeriod);
+    }
+
+    let raffle_fee = ctx.accounts.raffle_fee();
+
+    if raffle_fee > 0 {
+        require!(
+            ctx.accounts.fees_wallet.is_some(),
+            RaffleError::FeesWalletNeeded
+        );
+        transfer(ctx.accounts.transfer_raffle_fee_ctx(), raffle_fee)?;
+    }
+
     let gated_collection = if is_gated {
         Some(next_account_info(remaining_accounts)?)
     } else {
         None
     };
 
+    // cNFT and Core prizes read their accounts from here, see utils::cnft and utils::core_asset.
+    // Token prizes pass any transfer hook accounts here
+    let prize_accounts = remaining_accounts.as_slice();
+
     let prize_metadata = if prize_type == PrizeType::Nft {
         Some(next_account_info(remaining_accounts)?)
     } else {
@@ -244,23 +561,23 @@ pub fn init_raffle_handler<'info>(
     };
 
     let source_token_record = match next_account_info(remaining_accounts) {
-        Ok(val) => Some(val.to_account_info()),
-        Err(_) => None,
+        Ok(val) if prize_type == PrizeType::Nft => Some(val.to_account_info()),
+        _ => None,
     };
 
     let destination_token_record = match next_account_info(remaining_accounts) {
-        Ok(val) => Some(val.to_account_info()),
-        Err(_) => None,
+        Ok(val) if prize_type == PrizeType::Nft => Some(val.to_account_info()),
+        _ => None,
     };
 
     let auth_rules_program = match next_account_info(remaining_accounts) {
-        Ok(val) => Some(val.to_account_info()),
-        Err(_) => None,
+        Ok(val) if prize_type == PrizeType::Nft => Some(val.to_account_info()),
+        _ => None,
     };
 
     let auth_rules = match next_account_info(remaining_accounts) {
-        Ok(val) => Some(val.to_account_info()),
-        Err(_) => None,
+        Ok(val) if prize_type == PrizeType::Nft => Some(val.to_account_info()),
+        _ => None,
     };
 
     let raffle = &mut ctx.accounts.raffle;
@@ -269,10 +586,27 @@ pub fn init_raffle_handler<'info>(
     let end_time: i64 = start_time + duration;
 
     let payment_type = if ctx.accounts.entry_collection_mint.is_some() {
-        PaymentType::Nft {
-            collection: ctx.accounts.entry_collection_mint.as_ref().unwrap().key(),
+        let collection = ctx.accounts.entry_collection_mint.as_ref().unwrap().key();
+        if is_compressed {
+            require!(
+                !matches!(
+                    entry_type,
+                    EntryType::Burn {
+                        withold_burn_proceeds: true
+                    }
+                ),
+                RaffleError::BurnProceedsCnft
+            );
+            require!(
+                !matches!(entry_type, EntryType::Stake { minimum_period: _ }),
+                RaffleError::StakeCnft
+            );
+            PaymentType::Cnft { collection }
+        } else {
+            PaymentType::Nft { collection }
         }
     } else {
+        require!(!is_compressed, RaffleError::ExpectedEntryCollectionMint);
         let token_mint = ctx.accounts.token_mint.as_ref().unwrap();
         require!(
             ctx.accounts.treasury_token_account.is_some(),
@@ -286,15 +620,50 @@ pub fn init_raffle_handler<'info>(
         ) {
             require_keys_neq!(token_mint.key(), NATIVE_MINT, RaffleError::CannotBurnSOL);
         }
+        if matches!(entry_type, EntryType::Stake { minimum_period: _ }) {
+            require_keys_neq!(token_mint.key(), NATIVE_MINT, RaffleError::CannotStakeSOL);
+        }
         PaymentType::Token {
             token_mint: token_mint.key(),
             ticket_price: ticket_price.unwrap(),
         }
     };
 
+    // the pot is paid from ticket proceeds, so tickets must be bought and kept with tokens
+    if let PrizeType::Pot { winner_share } = prize_type {
+        require!(
+            winner_share > 0 && winner_share <= 10_000,
+            RaffleError::InvalidWinnerShare
+        );
+        require!(
+            matches!(payment_type, PaymentType::Token { .. })
+                && matches!(entry_type, EntryType::Spend),
+            RaffleError::PotRequiresTokenSpend
+        );
+    }
+
+    if let Some(jackpot_series) = &ctx.accounts.jackpot_series {
+        require!(
+            prize_type
+                == PrizeType::Pot {
+                    winner_share: jackpot_series.winner_share
+                }
+                && matches!(
+                    payment_type,
+                    PaymentType::Token { token_mint, .. } if token_mint == jackpot_series.token_mint
+                ),
+            RaffleError::InvalidJackpotSeries
+        );
+    }
+
     ***raffle = Raffle::init(
         ctx.accounts.raffler.key(),
-        ctx.accounts.prize.key(),
+        // cNFT and Core prizes are keyed by asset id once escrowed below
+        ctx.accounts
+            .prize
+            .as_ref()
+            .map(|prize| prize.key())
+            .unwrap_or_default(),
         prize_type,
         entry_type,
         payment_type,
@@ -302,13 +671,32 @@ pub fn init_raffle_handler<'info>(
         gated_collection.as_ref().map(|c| c.key()),
         start_time,
         end_time,
-        max_entrant_pct.unwrap_or(10000),
+        end_time_extension,
+        max_entrant_pct,
+        max_entrant_tickets,
+        min_tickets,
+        num_winners,
+        randomness_source,
+        claim_window,
+        unclaimed_prize.unwrap_or(UnclaimedPrize::Redraw),
         ctx.bumps.raffle,
-    );
+    )?;
+    raffle.sync_state(0, current_time)?;
+
+    if let Some(jackpot_series) = ctx.accounts.jackpot_series.as_mut() {
+        raffle.jackpot_series = Some(jackpot_series.key());
+        jackpot_series.rounds = jackpot_series
+            .rounds
+            .checked_add(1)
+            .ok_or(RaffleError::ProgramAddError)?;
+        jackpot_series.current_raffle = Some(raffle.key());
+    }
 
-    let entrants = &mut ctx.accounts.entrants;
+    let mut entrants = ctx.accounts.entrants.load_init()?;
     entrants.total = 0;
+    entrants.ranges = 0;
     entrants.max = num_tickets.unwrap_or(u32::MAX);
+    drop(entrants);
 
     match prize_type {
         PrizeType::Nft => ctx.accounts.transfer_nft(
@@ -320,6 +708,152 @@ pub fn init_raffle_handler<'info>(
             auth_rules_program,
             auth_rules,
         ),
-        PrizeType::Token { amount } => transfer(ctx.accounts.transfer_token_ctx(), amount),
+        PrizeType::Token { amount } => {
+            let prize = ctx.accounts.prize.as_ref().expect("prize expected");
+            let decimals = prize.decimals;
+            let transfer_fee = get_transfer_fee(&prize.to_account_info(), amount)?;
+
+            transfer_checked_with_hooks(
+                ctx.accounts
+                    .transfer_token_ctx()
+                    .with_remaining_accounts(prize_accounts.to_vec()),
+                amount,
+                decimals,
+            )?;
+
+            // record what actually landed in custody after any Token-2022 transfer fee
+            ctx.accounts.raffle.prize_type = PrizeType::Token {
+                amount: amount
+                    .checked_sub(transfer_fee)
+                    .ok_or(RaffleError::ProgramSubError)?,
+            };
+            Ok(())
+        }
+        PrizeType::Cnft => {
+            let asset_id = transfer_cnft(
+                prize_accounts,
+                &ctx.accounts.authority.to_account_info(),
+                &ctx.accounts.raffle.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                prize_cnft.ok_or(RaffleError::CnftArgsNeeded)?,
+                &[],
+            )?;
+            ctx.accounts.raffle.prize = asset_id;
+            Ok(())
+        }
+        PrizeType::CoreAsset => {
+            let authority = &ctx.accounts.authority.to_account_info();
+            let asset = transfer_core_asset(
+                prize_accounts,
+                authority,
+                authority,
+                &ctx.accounts.raffle.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                &[],
+            )?;
+            ctx.accounts.raffle.prize = asset;
+            Ok(())
+        }
+        PrizeType::Sol { lamports } => {
+            require_gt!(lamports, 0, RaffleError::InvalidPrize);
+
+            // the vault is a plain system account, so it also needs its rent exempt minimum
+            let rent = Rent::get()?.minimum_balance(0);
+            transfer(
+                ctx.accounts.transfer_prize_vault_ctx(),
+                lamports
+                    .checked_add(rent)
+                    .ok_or(RaffleError::ProgramAddError)?,
+            )?;
+
+            ctx.accounts.raffle.prize = ctx
+                .accounts
+                .prize_vault
+                .as_ref()
+                .expect("prize_vault expected")
+                .key();
+            Ok(())
+        }
+        PrizeType::Pot { winner_share: _ } => {
+            // winners are paid in the payment mint, keyed here so claim_prize can check it
+            if let PaymentType::Token { token_mint, .. } = ctx.accounts.raffle.payment_type {
+                ctx.accounts.raffle.prize = token_mint;
+            }
+            Ok(())
+        }
+    }?;
+
+    if let Some(ConsolationArgs {
+        amount: consolation_amount,
+        ..
+    }) = consolation
+    {
+        require_gt!(consolation_amount, 0, RaffleError::InvalidConsolationPool);
+
+        let consolation_mint = ctx
+            .accounts
+            .consolation_mint
+            .as_ref()
+            .expect("consolation_mint expected");
+        let raffle = &ctx.accounts.raffle;
+
+        // custody is the raffle's ATA, so it can't be shared with the prize or the proceeds
+        require_keys_neq!(
+            consolation_mint.key(),
+            raffle.prize,
+            RaffleError::InvalidConsolationPool
+        );
+        let proceeds_mint = match raffle.payment_type {
+            PaymentType::Token { token_mint, .. } => Some(token_mint),
+            PaymentType::Nft { .. } => Some(NATIVE_MINT),
+            PaymentType::Cnft { .. } => None,
+        };
+        require!(
+            proceeds_mint != Some(consolation_mint.key()),
+            RaffleError::InvalidConsolationPool
+        );
+
+        let decimals = consolation_mint.decimals;
+        let transfer_fee =
+            get_transfer_fee(&consolation_mint.to_account_info(), consolation_amount)?;
+        let mint = consolation_mint.key();
+
+        transfer_checked_with_hooks(
+            ctx.accounts
+                .transfer_consolation_ctx()
+                .with_remaining_accounts(consolation_hook_accounts.to_vec()),
+            consolation_amount,
+            decimals,
+        )?;
+
+        ctx.accounts.raffle.consolation = Some(ConsolationPool {
+            mint,
+            amount: consolation_amount
+                .checked_sub(transfer_fee)
+                .ok_or(RaffleError::ProgramSubError)?,
+            eligible_tickets: 0,
+            claimed_tickets: 0,
+        });
     }
+
+    let raffle = &ctx.accounts.raffle;
+    emit_cpi!(RaffleCreated {
+        raffle: raffle.key(),
+        raffler: raffle.raffler,
+        prize: raffle.prize,
+        prize_type: raffle.prize_type,
+        entry_type: raffle.entry_type,
+        payment_type: raffle.payment_type,
+        start_time: raffle.start_time,
+        end_time: raffle.end_time,
+        end_time_extension: raffle.end_time_extension,
+        max_tickets: num_tickets.unwrap_or(u32::MAX),
+        min_tickets: raffle.min_tickets,
+        num_winners: raffle.num_winners,
+        randomness_source: raffle.randomness_source,
+        jackpot_series: raffle.jackpot_series,
+        consolation: raffle.consolation,
+    });
+
+    Ok(())
 }
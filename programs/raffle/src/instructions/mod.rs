@@ -1,37 +1,79 @@
+pub use add_bundle_prize::*;
 pub use buy_ticket_burn_nft::*;
+pub use buy_ticket_cnft::*;
 pub use buy_ticket_send_nft::*;
 pub use buy_tickets_token::*;
+pub use cancel_raffle::*;
+pub use claim_bundle_prize::*;
+pub use claim_consolation::*;
 pub use claim_prize::*;
+pub use claim_refund::*;
+pub use close_entrant_tally::*;
+pub use collect_cnft::*;
 pub use collect_nft::*;
 pub use consume_randomness::*;
+pub use delete_fee_override::*;
 pub use delete_raffle::*;
 pub use delete_raffler::*;
 pub use draw_winner::*;
+pub use draw_winner_commit_reveal::*;
+pub use draw_winner_on_demand::*;
 pub use init::*;
+pub use init_jackpot_series::*;
 pub use init_program_config::*;
 pub use init_raffle::*;
 pub use recover_nft::*;
+pub use retry_draw::*;
+pub use roll_jackpot::*;
 pub use set_entrants_uri::*;
+pub use set_fee_override::*;
 pub use set_slugs::*;
+pub use settle_commit_reveal::*;
+pub use settle_on_demand::*;
+pub use stake_nft::*;
+pub use stake_token::*;
 pub use toggle_active::*;
+pub use unstake_nft::*;
+pub use unstake_token::*;
 pub use update_program_config::*;
 pub use update_raffler::*;
 
+pub mod add_bundle_prize;
 pub mod buy_ticket_burn_nft;
+pub mod buy_ticket_cnft;
 pub mod buy_ticket_send_nft;
 pub mod buy_tickets_token;
+pub mod cancel_raffle;
+pub mod claim_bundle_prize;
+pub mod claim_consolation;
 pub mod claim_prize;
+pub mod claim_refund;
+pub mod close_entrant_tally;
+pub mod collect_cnft;
 pub mod collect_nft;
 pub mod consume_randomness;
+pub mod delete_fee_override;
 pub mod delete_raffle;
 pub mod delete_raffler;
 pub mod draw_winner;
+pub mod draw_winner_commit_reveal;
+pub mod draw_winner_on_demand;
 pub mod init;
+pub mod init_jackpot_series;
 pub mod init_program_config;
 pub mod init_raffle;
 pub mod recover_nft;
+pub mod retry_draw;
+pub mod roll_jackpot;
 pub mod set_entrants_uri;
+pub mod set_fee_override;
 pub mod set_slugs;
+pub mod settle_commit_reveal;
+pub mod settle_on_demand;
+pub mod stake_nft;
+pub mod stake_token;
 pub mod toggle_active;
+pub mod unstake_nft;
+pub mod unstake_token;
 pub mod update_program_config;
 pub mod update_raffler;
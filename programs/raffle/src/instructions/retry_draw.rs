@@ -0,0 +1,147 @@
+use anchor_lang::prelude::*;
+use solana_randomness_service::{
+    cpi::accounts::SimpleRandomnessV1Request, program::SolanaRandomnessService,
+};
+
+use switchboard_solana::prelude::*;
+
+use crate::{
+    events::RandomnessRequested,
+    state::{Entrants, ProgramConfig, Raffle, RandomnessSource},
+    utils::request_randomness,
+    RaffleError,
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RetryDraw<'info> {
+    #[account(
+        seeds = [b"program-config"],
+        bump = program_config.bump
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+
+    /// The Solana Randomness Service program.
+    pub randomness_service: Program<'info, SolanaRandomnessService>,
+
+    /// The account that will be created on-chain to hold the replacement randomness request.
+    /// Used by the off-chain oracle to pickup the request and fulfill it.
+    /// CHECK: todo
+    #[account(
+        mut,
+        signer,
+        owner = system_program.key(),
+        constraint = randomness_request.data_len() == 0 && randomness_request.lamports() == 0,
+    )]
+    pub randomness_request: AccountInfo<'info>,
+
+    /// The TokenAccount that will store the funds for the randomness request.
+    /// CHECK: todo
+    #[account(
+        mut,
+        owner = system_program.key(),
+        constraint = randomness_escrow.data_len() == 0 && randomness_escrow.lamports() == 0,
+    )]
+    pub randomness_escrow: AccountInfo<'info>,
+
+    /// The randomness service's state account. Responsible for storing the
+    /// reward escrow and the cost per random byte.
+    #[account(
+        seeds = [b"STATE"],
+        bump = randomness_state.bump,
+        seeds::program = randomness_service.key(),
+    )]
+    pub randomness_state: Box<Account<'info, solana_randomness_service::State>>,
+
+    /// The token mint to use for paying for randomness requests.
+    #[account(address = NativeMint::ID)]
+    pub randomness_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        has_one = entrants,
+        seeds = [
+            b"RAFFLE",
+            entrants.key().as_ref(),
+            b"raffle"
+        ],
+        bump = raffle.bump
+    )]
+    pub raffle: Account<'info, Raffle>,
+
+    pub entrants: AccountLoader<'info, Entrants>,
+
+    /// The account that will pay for the randomness request.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The Solana System program. Used to allocate space on-chain for the randomness_request account.
+    pub system_program: Program<'info, System>,
+
+    /// The Solana Token program. Used to transfer funds to the randomness escrow.
+    pub token_program: Program<'info, Token>,
+    /// The Solana Associated Token program. Used to create the TokenAccount for the randomness escrow.
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> RetryDraw<'info> {
+    pub fn randomness_request_accounts(&self) -> SimpleRandomnessV1Request<'info> {
+        SimpleRandomnessV1Request {
+            request: self.randomness_request.to_account_info(),
+            escrow: self.randomness_escrow.to_account_info(),
+            state: self.randomness_state.to_account_info(),
+            mint: self.randomness_mint.to_account_info(),
+            payer: self.payer.to_account_info(),
+            system_program: self.system_program.to_account_info(),
+            token_program: self.token_program.to_account_info(),
+            associated_token_program: self.associated_token_program.to_account_info(),
+        }
+    }
+}
+
+/// Replaces a randomness request that the service never fulfilled. The stale request is
+/// unbound from the raffle, so a late callback for it is rejected by `consume_randomness`.
+/// The stale request and its escrow are accounts of the randomness service, paid for by
+/// whoever made the request, and are left for them to reclaim from the service.
+///
+/// Only SwitchboardService draws are retried here. An On-Demand randomness account is
+/// created by the caller rather than the raffle, so draw_winner_on_demand replaces a stale
+/// one after the same timeout, and a commit-reveal draw refunds its entrants if it expires.
+pub fn retry_draw_handler(
+    ctx: Context<RetryDraw>,
+    priority_fee: Option<u64>,
+) -> anchor_lang::prelude::Result<()> {
+    let priority_fee = priority_fee.unwrap_or(100);
+    let current_time = Clock::get().unwrap().unix_timestamp;
+    let raffle = &ctx.accounts.raffle;
+
+    require!(
+        raffle.randomness_source == RandomnessSource::SwitchboardService,
+        RaffleError::InvalidRandomnessSource
+    );
+
+    raffle
+        .assert_request_timed_out(ctx.accounts.program_config.randomness_timeout, current_time)?;
+
+    let stale_request = raffle.randomness_request;
+
+    request_randomness(
+        ctx.accounts.randomness_service.to_account_info(),
+        ctx.accounts.randomness_request_accounts(),
+        ctx.accounts.raffle.key(),
+        ctx.accounts.entrants.key(),
+        ctx.accounts.event_authority.key(),
+        priority_fee,
+    )?;
+
+    let raffle = &mut ctx.accounts.raffle;
+    raffle.record_randomness_request(ctx.accounts.randomness_request.key())?;
+
+    emit_cpi!(RandomnessRequested {
+        raffle: raffle.key(),
+        request: ctx.accounts.randomness_request.key(),
+        replaces: stale_request,
+    });
+
+    Ok(())
+}
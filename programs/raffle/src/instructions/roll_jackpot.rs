@@ -0,0 +1,351 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{
+        close_account, CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked,
+    },
+};
+
+use crate::{
+    events::JackpotRolled,
+    state::{
+        Entrants, JackpotSeries, PrizeBundle, ProgramConfig, Raffle, RaffleState, Raffler,
+        UnclaimedPrize,
+    },
+    utils::{harvest_withheld_fees, transfer_checked_with_hooks},
+    RaffleError, FEES_WALLET,
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RollJackpot<'info> {
+    #[account(
+        seeds = [b"program-config"],
+        bump
+    )]
+    pub program_config: Box<Account<'info, ProgramConfig>>,
+
+    #[account(
+        seeds = [
+            b"RAFFLE",
+            raffler.authority.as_ref(),
+            b"raffler"
+        ],
+        bump = raffler.bump,
+        has_one = authority,
+        has_one = treasury
+    )]
+    pub raffler: Box<Account<'info, Raffler>>,
+
+    #[account(
+        seeds = [
+            b"RAFFLE",
+            raffler.key().as_ref(),
+            token_mint.key().as_ref(),
+            b"jackpot-series"
+        ],
+        bump = jackpot_series.bump,
+        has_one = raffler,
+        has_one = token_mint
+    )]
+    pub jackpot_series: Box<Account<'info, JackpotSeries>>,
+
+    /// the round whose pot wasn't won
+    #[account(
+        mut,
+        seeds = [
+            b"RAFFLE",
+            entrants.key().as_ref(),
+            b"raffle"
+        ],
+        bump = raffle.bump,
+        has_one = entrants,
+        has_one = raffler,
+        constraint = raffle.jackpot_series == Some(jackpot_series.key()) @ RaffleError::InvalidJackpotSeries
+    )]
+    pub raffle: Box<Account<'info, Raffle>>,
+
+    #[account(mut)]
+    pub entrants: AccountLoader<'info, Entrants>,
+
+    /// the series' latest round, which receives the pot
+    #[account(
+        mut,
+        constraint = jackpot_series.current_raffle == Some(next_raffle.key()) @ RaffleError::InvalidJackpotSeries,
+        constraint = next_raffle.key() != raffle.key() @ RaffleError::NextRoundNotOpen
+    )]
+    pub next_raffle: Box<Account<'info, Raffle>>,
+
+    #[account(mut)]
+    pub token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = token_mint,
+        associated_token::authority = raffle,
+        associated_token::token_program = token_program
+    )]
+    pub proceeds_source: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = token_mint,
+        associated_token::authority = next_raffle,
+        associated_token::token_program = token_program
+    )]
+    pub next_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut, address = FEES_WALLET)]
+    pub fees_wallet: SystemAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = token_mint,
+        associated_token::authority = fees_wallet,
+        associated_token::token_program = token_program
+    )]
+    pub fees_wallet_token: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = token_mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program
+    )]
+    pub proceeds_destination: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"RAFFLE",
+            raffle.key().as_ref(),
+            b"prize-bundle"
+        ],
+        bump = prize_bundle.bump
+    )]
+    pub prize_bundle: Option<Box<Account<'info, PrizeBundle>>>,
+
+    pub treasury: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub authority: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RollJackpot<'info> {
+    fn transfer_from_vault_ctx(
+        &self,
+        to: &InterfaceAccount<'info, TokenAccount>,
+    ) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        let cpi_accounts = TransferChecked {
+            from: self.proceeds_source.to_account_info(),
+            mint: self.token_mint.to_account_info(),
+            to: to.to_account_info(),
+            authority: self.raffle.to_account_info(),
+        };
+
+        let cpi_program = self.token_program.to_account_info();
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+
+    pub fn close_proceeds_ctx(&self) -> CpiContext<'_, '_, '_, 'info, CloseAccount<'info>> {
+        let cpi_accounts = CloseAccount {
+            account: self.proceeds_source.to_account_info(),
+            destination: self.authority.to_account_info(),
+            authority: self.raffle.to_account_info(),
+        };
+        let cpi_program = self.token_program.to_account_info();
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+}
+
+/// Moves the pot of a series round that ended without it being won into the series' latest
+/// round. A round with no entrants, or whose winners can no longer claim, is closed out like
+/// a final claim_prize, with the treasury and fee cut taken if no winner has claimed yet.
+/// A refunding round keeps its proceeds for refunds and only passes on the pot it carried in
+pub fn roll_jackpot_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, RollJackpot<'info>>,
+) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp;
+    let total_tickets = ctx.accounts.entrants.load()?.total;
+    ctx.accounts
+        .raffle
+        .sync_state(total_tickets, current_time)?;
+
+    let next_raffle = &ctx.accounts.next_raffle;
+    require!(
+        !next_raffle.is_drawn()
+            && matches!(
+                next_raffle.state,
+                RaffleState::Scheduled
+                    | RaffleState::Live
+                    | RaffleState::SoldOut
+                    | RaffleState::AwaitingRandomness
+            ),
+        RaffleError::NextRoundNotOpen
+    );
+
+    let raffle = &ctx.accounts.raffle;
+    let is_refunding = raffle.state == RaffleState::Refunding;
+
+    if is_refunding {
+        require_gt!(raffle.carried_pot, 0, RaffleError::JackpotNotRollable);
+    } else {
+        let is_unsold = total_tickets == 0
+            && current_time >= raffle.end_time
+            && matches!(raffle.state, RaffleState::Live | RaffleState::Drawn);
+
+        // the same shares claim_prize would let the raffler reclaim
+        let is_unclaimed = total_tickets > 0
+            && raffle.state == RaffleState::Drawn
+            && raffle.claim_expired(current_time)
+            && (raffle.unclaimed_prize == UnclaimedPrize::Reclaim
+                || !raffle.can_redraw(total_tickets));
+
+        require!(is_unsold || is_unclaimed, RaffleError::JackpotNotRollable);
+    }
+
+    let entrants_key = ctx.accounts.entrants.key();
+    let bump = raffle.bump;
+    let authority_seed = &[
+        &b"RAFFLE"[..],
+        &entrants_key.as_ref(),
+        &b"raffle"[..],
+        &[bump],
+    ];
+    let decimals = ctx.accounts.token_mint.decimals;
+
+    // the proceeds are split on the first claim, so only if nobody has claimed yet
+    let (treasury_proceeds, fee) = if !is_refunding && raffle.winners.iter().all(|w| !w.claimed) {
+        raffle.split_proceeds(
+            ctx.accounts.proceeds_source.amount,
+            ctx.accounts.program_config.proceeds_share,
+        )?
+    } else {
+        (0, 0)
+    };
+
+    if fee > 0 {
+        transfer_checked_with_hooks(
+            ctx.accounts
+                .transfer_from_vault_ctx(&ctx.accounts.fees_wallet_token)
+                .with_signer(&[authority_seed])
+                .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+            fee,
+            decimals,
+        )?;
+    }
+
+    if treasury_proceeds > 0 {
+        transfer_checked_with_hooks(
+            ctx.accounts
+                .transfer_from_vault_ctx(&ctx.accounts.proceeds_destination)
+                .with_signer(&[authority_seed])
+                .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+            treasury_proceeds,
+            decimals,
+        )?;
+    }
+
+    ctx.accounts.proceeds_source.reload()?;
+
+    let pot = if is_refunding {
+        ctx.accounts.raffle.carried_pot
+    } else {
+        ctx.accounts.proceeds_source.amount
+    };
+
+    // record what actually landed in the next round after any Token-2022 transfer fee
+    let balance_before = ctx.accounts.next_vault.amount;
+
+    if pot > 0 {
+        transfer_checked_with_hooks(
+            ctx.accounts
+                .transfer_from_vault_ctx(&ctx.accounts.next_vault)
+                .with_signer(&[authority_seed])
+                .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+            pot,
+            decimals,
+        )?;
+    }
+
+    ctx.accounts.next_vault.reload()?;
+    let amount = ctx
+        .accounts
+        .next_vault
+        .amount
+        .checked_sub(balance_before)
+        .ok_or(RaffleError::ProgramSubError)?;
+
+    let next_raffle = &mut ctx.accounts.next_raffle;
+    next_raffle.carried_pot = next_raffle
+        .carried_pot
+        .checked_add(amount)
+        .ok_or(RaffleError::ProgramAddError)?;
+
+    let raffle = &mut ctx.accounts.raffle;
+    raffle.carried_pot = 0;
+
+    if !is_refunding {
+        for winner in raffle.winners.iter_mut() {
+            winner.claimed = true;
+        }
+        raffle.claimed = true;
+
+        // bundle items go back to the raffler through claim_bundle_prize
+        if raffle.bundle_items > 0 {
+            let prize_bundle = ctx
+                .accounts
+                .prize_bundle
+                .as_mut()
+                .ok_or(RaffleError::BundleNeeded)?;
+            prize_bundle.recipient = Some(ctx.accounts.authority.key());
+        }
+
+        if total_tickets == 0 {
+            raffle.close_unsold(total_tickets, current_time)?;
+        } else {
+            raffle.transition(RaffleState::Claimed)?;
+        }
+    }
+
+    emit_cpi!(JackpotRolled {
+        jackpot_series: ctx.accounts.jackpot_series.key(),
+        raffle: ctx.accounts.raffle.key(),
+        next_raffle: ctx.accounts.next_raffle.key(),
+        amount,
+        treasury_proceeds,
+        fee,
+    });
+
+    // entrants and the vault stay open while refunding so claim_refund keeps working
+    if is_refunding {
+        return Ok(());
+    }
+
+    ctx.accounts
+        .entrants
+        .close(ctx.accounts.fees_wallet.to_account_info())?;
+
+    harvest_withheld_fees(
+        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.token_mint.to_account_info(),
+        &ctx.accounts.proceeds_source.to_account_info(),
+    )?;
+
+    close_account(
+        ctx.accounts
+            .close_proceeds_ctx()
+            .with_signer(&[authority_seed]),
+    )
+}
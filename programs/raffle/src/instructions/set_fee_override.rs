@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    program::Raffle,
+    state::{FeeOverride, Raffler},
+    RaffleError,
+};
+
+#[derive(Accounts)]
+pub struct SetFeeOverride<'info> {
+    #[account(
+        seeds = [
+            b"RAFFLE",
+            raffler.authority.as_ref(),
+            b"raffler"
+        ],
+        bump = raffler.bump
+    )]
+    pub raffler: Account<'info, Raffler>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = FeeOverride::LEN,
+        seeds = [
+            b"RAFFLE",
+            raffler.key().as_ref(),
+            b"fee-override"
+        ],
+        bump
+    )]
+    pub fee_override: Account<'info, FeeOverride>,
+
+    #[account(
+        constraint = program.programdata_address()? == Some(program_data.key()) @ RaffleError::AdminOnly
+    )]
+    pub program: Program<'info, Raffle>,
+
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(authority.key()) @ RaffleError::AdminOnly
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn set_fee_override_handler(ctx: Context<SetFeeOverride>, raffle_fee: u64) -> Result<()> {
+    let fee_override = &mut ctx.accounts.fee_override;
+
+    **fee_override = FeeOverride::init(
+        ctx.accounts.raffler.key(),
+        raffle_fee,
+        ctx.bumps.fee_override,
+    );
+
+    Ok(())
+}
@@ -0,0 +1,95 @@
+use anchor_lang::{prelude::*, solana_program::sysvar};
+
+use crate::{
+    events::{DrawExpired, RandomnessFulfilled},
+    state::{Entrants, Raffle, RaffleState, RandomnessSource},
+    utils::{commit_reveal_randomness, find_slot_hash, SlotHashLookup},
+    RaffleError,
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SettleCommitReveal<'info> {
+    #[account(
+        mut,
+        has_one = entrants,
+        seeds = [
+            b"RAFFLE",
+            entrants.key().as_ref(),
+            b"raffle"
+        ],
+        bump = raffle.bump
+    )]
+    pub raffle: Account<'info, Raffle>,
+
+    pub entrants: AccountLoader<'info, Entrants>,
+
+    /// CHECK: address is checked, the sysvar is too large to deserialize so it is read in place
+    #[account(address = sysvar::slot_hashes::ID)]
+    pub slot_hashes: AccountInfo<'info>,
+}
+
+/// Settles a revealed commit-reveal draw with the hash of the first slot produced at or after
+/// the target. If the target has dropped out of the `SlotHashes` history the raffle refunds
+/// its entrants. A new target or a fresh draw would give whoever held back the settlement
+/// another chance at a result they prefer.
+pub fn settle_commit_reveal_handler(ctx: Context<SettleCommitReveal>) -> Result<()> {
+    let clock = Clock::get()?;
+    let raffle = &mut ctx.accounts.raffle;
+
+    require!(
+        raffle.state == RaffleState::AwaitingRandomness,
+        RaffleError::NotAwaitingRandomness
+    );
+
+    let RandomnessSource::CommitReveal {
+        secret: Some(secret),
+        ..
+    } = raffle.randomness_source
+    else {
+        return err!(RaffleError::InvalidRandomnessSource);
+    };
+
+    require_gt!(
+        clock.slot,
+        raffle.request_slot,
+        RaffleError::RandomnessNotResolved
+    );
+
+    let lookup = find_slot_hash(
+        &ctx.accounts.slot_hashes.try_borrow_data()?,
+        raffle.request_slot,
+    );
+
+    let slot_hash = match lookup {
+        SlotHashLookup::Found(slot, hash) => {
+            msg!("settling with slot {}", slot);
+            hash
+        }
+        SlotHashLookup::Pending => return err!(RaffleError::RandomnessNotResolved),
+        SlotHashLookup::Expired => {
+            msg!(
+                "slot {} has expired, refunding entrants",
+                raffle.request_slot
+            );
+            raffle.transition(RaffleState::Refunding)?;
+
+            emit_cpi!(DrawExpired {
+                raffle: raffle.key()
+            });
+            return Ok(());
+        }
+    };
+
+    let randomness = commit_reveal_randomness(secret, slot_hash);
+    raffle.settle_randomness(randomness, &ctx.accounts.entrants)?;
+
+    emit_cpi!(RandomnessFulfilled {
+        raffle: raffle.key(),
+        randomness,
+        winning_tickets: raffle.winners.iter().map(|w| w.ticket_index).collect(),
+        winning_entrants: raffle.winners.iter().map(|w| w.entrant).collect(),
+    });
+
+    Ok(())
+}
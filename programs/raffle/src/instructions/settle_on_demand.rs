@@ -0,0 +1,68 @@
+use anchor_lang::prelude::*;
+use switchboard_on_demand::accounts::RandomnessAccountData;
+
+use crate::{
+    events::RandomnessFulfilled,
+    state::{Entrants, Raffle},
+    RaffleError, SWITCHBOARD_ON_DEMAND,
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SettleOnDemand<'info> {
+    #[account(
+        mut,
+        has_one = entrants,
+        seeds = [
+            b"RAFFLE",
+            entrants.key().as_ref(),
+            b"raffle"
+        ],
+        bump = raffle.bump
+    )]
+    pub raffle: Account<'info, Raffle>,
+
+    pub entrants: AccountLoader<'info, Entrants>,
+
+    /// CHECK: owner is checked here and the data is parsed in the handler
+    #[account(owner = SWITCHBOARD_ON_DEMAND)]
+    pub randomness_account: AccountInfo<'info>,
+}
+
+pub fn settle_on_demand_handler(ctx: Context<SettleOnDemand>) -> Result<()> {
+    let clock = Clock::get()?;
+    let raffle = &mut ctx.accounts.raffle;
+
+    require!(
+        raffle.randomness_request == Some(ctx.accounts.randomness_account.key()),
+        RaffleError::RandomnessRequestMismatch
+    );
+
+    let randomness = {
+        let randomness_data =
+            RandomnessAccountData::parse(ctx.accounts.randomness_account.data.borrow())
+                .map_err(|_| ErrorCode::AccountDidNotDeserialize)?;
+
+        // the account can be recommitted by its authority, only the committed seed counts
+        require_eq!(
+            randomness_data.seed_slot,
+            raffle.request_slot,
+            RaffleError::RandomnessRequestMismatch
+        );
+
+        randomness_data
+            .get_value(&clock)
+            .map_err(|_| RaffleError::RandomnessNotResolved)?
+    };
+
+    raffle.settle_randomness(randomness, &ctx.accounts.entrants)?;
+
+    emit_cpi!(RandomnessFulfilled {
+        raffle: raffle.key(),
+        randomness,
+        winning_tickets: raffle.winners.iter().map(|w| w.ticket_index).collect(),
+        winning_entrants: raffle.winners.iter().map(|w| w.entrant).collect(),
+    });
+
+    Ok(())
+}
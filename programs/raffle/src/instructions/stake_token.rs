@@ -0,0 +1,235 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    metadata::{Metadata, MetadataAccount},
+    token::{Mint, TokenAccount},
+    token_interface::{self, TokenInterface, TransferChecked},
+};
+
+use crate::{
+    events::TicketsPurchased,
+    state::{EntrantTally, Entrants, EntryType, PaymentType, Raffle, Raffler, StakeRecord},
+    utils::{add_entrants, transfer_checked_with_hooks},
+    RaffleError,
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct StakeToken<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"RAFFLE",
+            entrants.key().as_ref(),
+            b"raffle"
+        ],
+        bump = raffle.bump,
+        has_one = raffler,
+        has_one = entrants,
+    )]
+    pub raffle: Box<Account<'info, Raffle>>,
+
+    #[account(
+        seeds = [
+            b"RAFFLE",
+            raffler.authority.as_ref(),
+            b"raffler"
+        ],
+        bump = raffler.bump
+    )]
+    pub raffler: Box<Account<'info, Raffler>>,
+
+    #[account(mut)]
+    pub entrants: AccountLoader<'info, Entrants>,
+
+    #[account(
+        init_if_needed,
+        payer = entrant,
+        space = EntrantTally::LEN,
+        seeds = [
+            b"RAFFLE",
+            raffle.key().as_ref(),
+            entrant.key().as_ref(),
+            b"entrant-tally"
+        ],
+        bump
+    )]
+    pub entrant_tally: Box<Account<'info, EntrantTally>>,
+
+    #[account(
+        init_if_needed,
+        payer = entrant,
+        space = StakeRecord::LEN,
+        seeds = [
+            b"RAFFLE",
+            raffle.key().as_ref(),
+            entrant.key().as_ref(),
+            token_mint.key().as_ref(),
+            b"stake"
+        ],
+        bump
+    )]
+    pub stake_record: Box<Account<'info, StakeRecord>>,
+
+    #[account(
+        address = match raffle.payment_type {
+            PaymentType::Token { token_mint, ticket_price: _ } => token_mint,
+            _ => return err!(RaffleError::TokenMintUnexpected)
+        } @ RaffleError::InvalidTokenMint
+    )]
+    pub token_mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = entrant,
+        associated_token::token_program = token_program
+    )]
+    pub token_source: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = entrant,
+        associated_token::mint = token_mint,
+        associated_token::authority = raffle,
+        associated_token::token_program = token_program
+    )]
+    pub token_vault: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+
+    pub gated_nft_mint: Option<Box<Account<'info, Mint>>>,
+
+    #[account(
+        seeds = [
+            b"metadata",
+            Metadata::id().as_ref(),
+            gated_nft_mint.as_ref().unwrap().key().as_ref()
+        ],
+        seeds::program = Metadata::id(),
+        bump,
+        constraint = match raffle.gated_collection {
+            Option::Some(val) => {
+                let coll = gated_nft_metadata.collection.as_ref().expect("Gated NFT collection not included");
+                val == coll.key && coll.verified
+            },
+            Option::None => true
+        }
+    )]
+    pub gated_nft_metadata: Option<Box<Account<'info, MetadataAccount>>>,
+
+    #[account(
+        associated_token::mint = gated_nft_mint,
+        associated_token::authority = entrant,
+        constraint = gated_nft_token.amount == 1 @ RaffleError::GatedRaffle
+    )]
+    pub gated_nft_token: Option<Box<Account<'info, TokenAccount>>>,
+
+    #[account(mut)]
+    pub entrant: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> StakeToken<'info> {
+    pub fn transfer_token_ctx(&self) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        let cpi_accounts = TransferChecked {
+            from: self.token_source.to_account_info(),
+            mint: self.token_mint.to_account_info(),
+            to: self.token_vault.to_account_info(),
+            authority: self.entrant.to_account_info(),
+        };
+
+        let cpi_program = self.token_program.to_account_info();
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+}
+
+pub fn stake_token_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, StakeToken<'info>>,
+    amount: u32,
+) -> Result<()> {
+    let raffle = &ctx.accounts.raffle;
+
+    if raffle.gated_collection.is_some() {
+        require!(
+            ctx.accounts.gated_nft_metadata.is_some(),
+            RaffleError::GatedRaffle
+        )
+    }
+
+    require!(
+        matches!(raffle.entry_type, EntryType::Stake { minimum_period: _ }),
+        RaffleError::InvalidInstruction
+    );
+
+    let ticket_price = match raffle.payment_type {
+        PaymentType::Token {
+            token_mint: _,
+            ticket_price,
+        } => ticket_price,
+        _ => return err!(RaffleError::TokenInstruction),
+    };
+
+    let cost = ticket_price
+        .checked_mul(amount as u64)
+        .ok_or(RaffleError::ProgramMulError)?;
+
+    // transfer hook extra accounts are passed as remaining accounts
+    transfer_checked_with_hooks(
+        ctx.accounts
+            .transfer_token_ctx()
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+        cost,
+        ctx.accounts.token_mint.decimals,
+    )?;
+
+    // only what reached the vault after any Token-2022 transfer fee can be withdrawn
+    let balance_before = ctx.accounts.token_vault.amount;
+    ctx.accounts.token_vault.reload()?;
+    let staked = ctx
+        .accounts
+        .token_vault
+        .amount
+        .checked_sub(balance_before)
+        .ok_or(RaffleError::ProgramSubError)?;
+
+    let stake_record = &mut ctx.accounts.stake_record;
+
+    if stake_record.raffle == Pubkey::default() {
+        ***stake_record = StakeRecord::init(
+            ctx.accounts.raffle.key(),
+            ctx.accounts.entrant.key(),
+            ctx.accounts.token_mint.key(),
+            ctx.bumps.stake_record,
+        );
+    }
+
+    // topping up restarts the lock on the whole stake
+    stake_record.amount = stake_record
+        .amount
+        .checked_add(staked)
+        .ok_or(RaffleError::ProgramAddError)?;
+    stake_record.staked_at = Clock::get().unwrap().unix_timestamp;
+
+    let first_ticket_index = add_entrants(
+        &mut ctx.accounts.raffle,
+        &ctx.accounts.entrants,
+        &mut ctx.accounts.entrant_tally,
+        ctx.bumps.entrant_tally,
+        ctx.accounts.entrant.to_account_info(),
+        ctx.accounts.system_program.to_account_info(),
+        amount,
+    )?;
+
+    emit_cpi!(TicketsPurchased {
+        raffle: ctx.accounts.raffle.key(),
+        entrant: ctx.accounts.entrant.key(),
+        amount,
+        cost: 0,
+        first_ticket_index,
+        end_time: ctx.accounts.raffle.end_time,
+    });
+
+    Ok(())
+}
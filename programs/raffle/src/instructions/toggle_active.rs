@@ -1,6 +1,6 @@
 use anchor_lang::prelude::*;
 
-use crate::{program::Raffle, state::Raffler, RaffleError};
+use crate::{events::RafflerUpdated, program::Raffle, state::Raffler, RaffleError};
 
 #[derive(Accounts)]
 pub struct ToggleActive<'info> {
@@ -26,11 +26,25 @@ pub struct ToggleActive<'info> {
     pub program_data: Account<'info, ProgramData>,
 
     pub authority: Signer<'info>,
+
+    /// CHECK: Only the event authority can invoke self-CPI. Added by hand rather than with
+    /// #[event_cpi] as the program account is already part of this instruction
+    #[account(seeds = [b"__event_authority"], bump)]
+    pub event_authority: AccountInfo<'info>,
 }
 
 pub fn toggle_active_handler(ctx: Context<ToggleActive>, is_active: bool) -> Result<()> {
     let raffler = &mut ctx.accounts.raffler;
 
     raffler.is_active = is_active;
+
+    emit_cpi!(RafflerUpdated {
+        raffler: raffler.key(),
+        authority: raffler.authority,
+        treasury: raffler.treasury,
+        staker: raffler.staker,
+        is_active: raffler.is_active,
+    });
+
     Ok(())
 }
@@ -0,0 +1,119 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+use crate::{
+    events::StakeWithdrawn,
+    state::{Raffle, StakeRecord},
+    utils::{assert_stake_withdrawable, transfer_checked_with_hooks},
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UnstakeToken<'info> {
+    #[account(
+        seeds = [
+            b"RAFFLE",
+            raffle.entrants.as_ref(),
+            b"raffle"
+        ],
+        bump = raffle.bump,
+    )]
+    pub raffle: Box<Account<'info, Raffle>>,
+
+    #[account(
+        mut,
+        close = entrant,
+        seeds = [
+            b"RAFFLE",
+            raffle.key().as_ref(),
+            entrant.key().as_ref(),
+            token_mint.key().as_ref(),
+            b"stake"
+        ],
+        bump = stake_record.bump,
+        has_one = raffle,
+        has_one = entrant,
+    )]
+    pub stake_record: Box<Account<'info, StakeRecord>>,
+
+    #[account(address = stake_record.mint)]
+    pub token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = token_mint,
+        associated_token::authority = raffle,
+        associated_token::token_program = token_program
+    )]
+    pub token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = entrant,
+        associated_token::mint = token_mint,
+        associated_token::authority = entrant,
+        associated_token::token_program = token_program
+    )]
+    pub token_destination: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub entrant: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> UnstakeToken<'info> {
+    pub fn transfer_token_ctx(&self) -> CpiContext<'_, '_, '_, 'info, TransferChecked<'info>> {
+        let cpi_accounts = TransferChecked {
+            from: self.token_vault.to_account_info(),
+            mint: self.token_mint.to_account_info(),
+            to: self.token_destination.to_account_info(),
+            authority: self.raffle.to_account_info(),
+        };
+
+        let cpi_program = self.token_program.to_account_info();
+        CpiContext::new(cpi_program, cpi_accounts)
+    }
+}
+
+pub fn unstake_token_handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, UnstakeToken<'info>>,
+) -> Result<()> {
+    let current_time = Clock::get()?.unix_timestamp;
+    let raffle = &ctx.accounts.raffle;
+
+    assert_stake_withdrawable(raffle, &ctx.accounts.stake_record, current_time)?;
+
+    let entrants_key = raffle.entrants;
+    let bump = raffle.bump;
+
+    let authority_seed = &[
+        &b"RAFFLE"[..],
+        &entrants_key.as_ref(),
+        &b"raffle"[..],
+        &[bump],
+    ];
+
+    transfer_checked_with_hooks(
+        ctx.accounts
+            .transfer_token_ctx()
+            .with_signer(&[authority_seed])
+            .with_remaining_accounts(ctx.remaining_accounts.to_vec()),
+        ctx.accounts.stake_record.amount,
+        ctx.accounts.token_mint.decimals,
+    )?;
+
+    emit_cpi!(StakeWithdrawn {
+        raffle: ctx.accounts.raffle.key(),
+        entrant: ctx.accounts.entrant.key(),
+        mint: ctx.accounts.token_mint.key(),
+        amount: ctx.accounts.stake_record.amount,
+    });
+
+    Ok(())
+}
@@ -1,6 +1,6 @@
 use anchor_lang::prelude::*;
 
-use crate::{program::Raffle, state::ProgramConfig, RaffleError};
+use crate::{events::ConfigUpdated, program::Raffle, state::ProgramConfig, RaffleError};
 
 #[derive(Accounts)]
 pub struct UpdateProgramConfig<'info> {
@@ -23,12 +23,18 @@ pub struct UpdateProgramConfig<'info> {
 
     #[account(mut)]
     pub authority: Signer<'info>,
+
+    /// CHECK: Only the event authority can invoke self-CPI. Added by hand rather than with
+    /// #[event_cpi] as the program account is already part of this instruction
+    #[account(seeds = [b"__event_authority"], bump)]
+    pub event_authority: AccountInfo<'info>,
 }
 
 pub fn update_program_config_handler(
     ctx: Context<UpdateProgramConfig>,
     raffle_fee: Option<u64>,
     proceeds_share: Option<u16>,
+    randomness_timeout: Option<i64>,
 ) -> Result<()> {
     let program_config = &mut ctx.accounts.program_config;
 
@@ -40,5 +46,17 @@ pub fn update_program_config_handler(
         program_config.proceeds_share = proceeds_share.unwrap();
     }
 
+    if randomness_timeout.is_some() {
+        let randomness_timeout = randomness_timeout.unwrap();
+        require_gt!(randomness_timeout, 0, RaffleError::InvalidRandomnessTimeout);
+        program_config.randomness_timeout = randomness_timeout;
+    }
+
+    emit_cpi!(ConfigUpdated {
+        raffle_fee: program_config.raffle_fee,
+        proceeds_share: program_config.proceeds_share,
+        randomness_timeout: program_config.randomness_timeout,
+    });
+
     Ok(())
 }
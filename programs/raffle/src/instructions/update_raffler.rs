@@ -1,7 +1,8 @@
 use anchor_lang::prelude::*;
 
-use crate::{state::Raffler, RaffleError, STAKE_PROGRAM};
+use crate::{events::RafflerUpdated, state::Raffler, RaffleError, STAKE_PROGRAM};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct UpdateRaffler<'info> {
     #[account(
@@ -71,5 +72,13 @@ pub fn update_raffler_hander(
     raffler.logo = logo;
     raffler.bg = bg;
 
+    emit_cpi!(RafflerUpdated {
+        raffler: raffler.key(),
+        authority: raffler.authority,
+        treasury: raffler.treasury,
+        staker: raffler.staker,
+        is_active: raffler.is_active,
+    });
+
     Ok(())
 }
@@ -2,17 +2,29 @@ use anchor_lang::{prelude::*, solana_program::pubkey};
 
 declare_id!("RAFFLv4sQoBPqLLqQvSHLRSFNnnoNekAbXfSegbQygF");
 
+mod events;
 mod instructions;
 mod state;
 mod utils;
 
+use self::state::EndTimeExtension;
 use self::state::EntryType;
 use self::state::PrizeType;
+use self::state::RandomnessSource;
+use self::state::UnclaimedPrize;
+use self::utils::CnftArgs;
 use instructions::*;
 
 pub const NATIVE_MINT: Pubkey = pubkey!("So11111111111111111111111111111111111111112");
 pub const FEES_WALLET: Pubkey = pubkey!("D7sZPRf5WRC6BpLsu6k3gwcwxZGxbTrFMyDvrMxkVeJP");
 pub const STAKE_PROGRAM: Pubkey = pubkey!("STAKEQkGBjkhCXabzB5cUbWgSSvbVJFEm2oEnyWzdKE");
+pub const NOOP_PROGRAM: Pubkey = pubkey!("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+pub const COMPRESSION_PROGRAM: Pubkey = pubkey!("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+pub const SWITCHBOARD_ON_DEMAND: Pubkey = pubkey!("SBondMDrcV3K4kxZR1HNVT7osZxAHVHgYXL5Ze1oMUv");
+/// how long after end_time stakes can be withdrawn from a raffle that was never drawn
+pub const STAKE_WITHDRAWAL_GRACE_PERIOD: i64 = 7 * 24 * 60 * 60;
+/// number of slots between a commit-reveal draw and the slot whose hash settles it
+pub const COMMIT_REVEAL_SLOT_DELAY: u64 = 4;
 
 #[program]
 pub mod raffle {
@@ -23,16 +35,18 @@ pub mod raffle {
         ctx: Context<InitProgramConfig>,
         raffle_fee: u64,
         proceeds_share: u16,
+        randomness_timeout: Option<i64>,
     ) -> Result<()> {
-        init_program_config_handler(ctx, raffle_fee, proceeds_share)
+        init_program_config_handler(ctx, raffle_fee, proceeds_share, randomness_timeout)
     }
 
     pub fn update_program_config(
         ctx: Context<UpdateProgramConfig>,
         raffle_fee: Option<u64>,
         proceeds_share: Option<u16>,
+        randomness_timeout: Option<i64>,
     ) -> Result<()> {
-        update_program_config_handler(ctx, raffle_fee, proceeds_share)
+        update_program_config_handler(ctx, raffle_fee, proceeds_share, randomness_timeout)
     }
 
     pub fn init(
@@ -55,6 +69,16 @@ pub mod raffle {
         duration: i64,
         is_gated: bool,
         max_entrants_pct: Option<u16>,
+        num_winners: Option<u8>,
+        max_entrant_tickets: Option<u32>,
+        is_compressed: bool,
+        prize_cnft: Option<CnftArgs>,
+        min_tickets: Option<u32>,
+        randomness_source: Option<RandomnessSource>,
+        claim_window: Option<i64>,
+        unclaimed_prize: Option<UnclaimedPrize>,
+        end_time_extension: Option<EndTimeExtension>,
+        consolation: Option<ConsolationArgs>,
     ) -> Result<()> {
         init_raffle_handler(
             ctx,
@@ -66,14 +90,66 @@ pub mod raffle {
             duration,
             is_gated,
             max_entrants_pct,
+            num_winners,
+            max_entrant_tickets,
+            is_compressed,
+            prize_cnft,
+            min_tickets,
+            randomness_source,
+            claim_window,
+            unclaimed_prize,
+            end_time_extension,
+            consolation,
         )
     }
 
+    pub fn add_bundle_prize<'info>(
+        ctx: Context<'_, '_, '_, 'info, AddBundlePrize<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        add_bundle_prize_handler(ctx, amount)
+    }
+
+    pub fn claim_bundle_prize<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimBundlePrize<'info>>,
+    ) -> Result<()> {
+        claim_bundle_prize_handler(ctx)
+    }
+
+    pub fn init_jackpot_series(ctx: Context<InitJackpotSeries>, winner_share: u16) -> Result<()> {
+        init_jackpot_series_handler(ctx, winner_share)
+    }
+
+    pub fn roll_jackpot<'info>(ctx: Context<'_, '_, '_, 'info, RollJackpot<'info>>) -> Result<()> {
+        roll_jackpot_handler(ctx)
+    }
+
+    pub fn claim_consolation<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimConsolation<'info>>,
+    ) -> Result<()> {
+        claim_consolation_handler(ctx)
+    }
+
+    pub fn close_entrant_tally(ctx: Context<CloseEntrantTally>) -> Result<()> {
+        close_entrant_tally_handler(ctx)
+    }
+
+    pub fn cancel_raffle<'info>(
+        ctx: Context<'_, '_, '_, 'info, CancelRaffle<'info>>,
+        prize_cnft: Option<CnftArgs>,
+        consolation_hook_accounts: u8,
+    ) -> Result<()> {
+        cancel_raffle_handler(ctx, prize_cnft, consolation_hook_accounts)
+    }
+
     pub fn delete_raffle(ctx: Context<DeleteRaffle>) -> Result<()> {
         delete_raffle_handler(ctx)
     }
 
-    pub fn buy_tickets_token(ctx: Context<BuyTicketsToken>, amount: u32) -> Result<()> {
+    pub fn buy_tickets_token<'info>(
+        ctx: Context<'_, '_, '_, 'info, BuyTicketsToken<'info>>,
+        amount: u32,
+    ) -> Result<()> {
         buy_tickets_token_handler(ctx, amount)
     }
     pub fn buy_ticket_send_nft(ctx: Context<BuyTicketSendNft>) -> Result<()> {
@@ -84,6 +160,37 @@ pub mod raffle {
         buy_ticket_burn_nft_handler(ctx)
     }
 
+    pub fn buy_ticket_cnft<'info>(
+        ctx: Context<'_, '_, '_, 'info, BuyTicketCnft<'info>>,
+        root: [u8; 32],
+        metadata_args: Vec<u8>,
+        nonce: u64,
+        index: u32,
+    ) -> Result<()> {
+        buy_ticket_cnft_handler(ctx, root, metadata_args, nonce, index)
+    }
+
+    pub fn stake_token<'info>(
+        ctx: Context<'_, '_, '_, 'info, StakeToken<'info>>,
+        amount: u32,
+    ) -> Result<()> {
+        stake_token_handler(ctx, amount)
+    }
+
+    pub fn stake_nft(ctx: Context<StakeNft>) -> Result<()> {
+        stake_nft_handler(ctx)
+    }
+
+    pub fn unstake_token<'info>(
+        ctx: Context<'_, '_, '_, 'info, UnstakeToken<'info>>,
+    ) -> Result<()> {
+        unstake_token_handler(ctx)
+    }
+
+    pub fn unstake_nft(ctx: Context<UnstakeNft>) -> Result<()> {
+        unstake_nft_handler(ctx)
+    }
+
     pub fn draw_winner(
         ctx: Context<DrawWinner>,
         uri: String,
@@ -92,15 +199,44 @@ pub mod raffle {
         draw_winner_handler(ctx, uri, priority_fee)
     }
 
+    pub fn retry_draw(ctx: Context<RetryDraw>, priority_fee: Option<u64>) -> Result<()> {
+        retry_draw_handler(ctx, priority_fee)
+    }
+
     pub fn consume_randomness(ctx: Context<ConsumeRandomness>, result: Vec<u8>) -> Result<()> {
         consume_randomness_handler(ctx, result)
     }
 
+    pub fn draw_winner_on_demand(ctx: Context<DrawWinnerOnDemand>, uri: String) -> Result<()> {
+        draw_winner_on_demand_handler(ctx, uri)
+    }
+
+    pub fn settle_on_demand(ctx: Context<SettleOnDemand>) -> Result<()> {
+        settle_on_demand_handler(ctx)
+    }
+
+    pub fn draw_winner_commit_reveal(
+        ctx: Context<DrawWinnerCommitReveal>,
+        uri: String,
+        secret: [u8; 32],
+    ) -> Result<()> {
+        draw_winner_commit_reveal_handler(ctx, uri, secret)
+    }
+
+    pub fn settle_commit_reveal(ctx: Context<SettleCommitReveal>) -> Result<()> {
+        settle_commit_reveal_handler(ctx)
+    }
+
+    pub fn claim_refund<'info>(ctx: Context<'_, '_, '_, 'info, ClaimRefund<'info>>) -> Result<()> {
+        claim_refund_handler(ctx)
+    }
+
     pub fn claim_prize<'info>(
         ctx: Context<'_, '_, '_, 'info, ClaimPrize<'info>>,
         ticket_index: u32,
+        prize_cnft: Option<CnftArgs>,
     ) -> Result<()> {
-        claim_prize_handler(ctx, ticket_index)
+        claim_prize_handler(ctx, ticket_index, prize_cnft)
     }
 
     pub fn set_slugs(ctx: Context<SetSlugs>, slugs: Vec<String>) -> Result<()> {
@@ -111,6 +247,17 @@ pub mod raffle {
         collect_nft_handler(ctx)
     }
 
+    pub fn collect_cnft<'info>(
+        ctx: Context<'_, '_, '_, 'info, CollectCnft<'info>>,
+        root: [u8; 32],
+        data_hash: [u8; 32],
+        creator_hash: [u8; 32],
+        nonce: u64,
+        index: u32,
+    ) -> Result<()> {
+        collect_cnft_handler(ctx, root, data_hash, creator_hash, nonce, index)
+    }
+
     pub fn recover_nft(ctx: Context<RecoverNft>) -> Result<()> {
         recover_nft_handler(ctx)
     }
@@ -136,6 +283,14 @@ pub mod raffle {
     pub fn toggle_active(ctx: Context<ToggleActive>, is_active: bool) -> Result<()> {
         toggle_active_handler(ctx, is_active)
     }
+
+    pub fn set_fee_override(ctx: Context<SetFeeOverride>, raffle_fee: u64) -> Result<()> {
+        set_fee_override_handler(ctx, raffle_fee)
+    }
+
+    pub fn delete_fee_override(ctx: Context<DeleteFeeOverride>) -> Result<()> {
+        delete_fee_override_handler(ctx)
+    }
 }
 
 #[error_code]
@@ -244,4 +399,136 @@ pub enum RaffleError {
     BgTooLong,
     #[msg("Only the raffle admin or system admin can perform this action")]
     AdminOrSystemAdmin,
+    #[msg("At least one winner is required")]
+    WinnersRequired,
+    #[msg("The max winners for a raffle is 50")]
+    TooManyWinners,
+    #[msg("NFT prizes can only have a single winner")]
+    MultipleWinnersNft,
+    #[msg("Error dividing numbers")]
+    ProgramDivError,
+    #[msg("This wallet has reached the maximum number of tickets for this raffle")]
+    MaxTicketsPerWallet,
+    #[msg("Max entrant percentage must be between 0 and 10,000 basis points")]
+    InvalidMaxEntrantPct,
+    #[msg("Max tickets per wallet must be greater than 0")]
+    InvalidMaxEntrantTickets,
+    #[msg("cannot set up a stake raffle with SOL")]
+    CannotStakeSOL,
+    #[msg("Minimum stake period cannot be negative")]
+    InvalidStakePeriod,
+    #[msg("This stake is still within its minimum staking period")]
+    StakeLocked,
+    #[msg("Winning stakes can only be withdrawn once the prize has been claimed")]
+    WinnerStakeLocked,
+    #[msg("Entrants account must be provided while prizes are unclaimed")]
+    EntrantsAccountNeeded,
+    #[msg("Invalid compressed NFT metadata")]
+    InvalidCnftMetadata,
+    #[msg("Cannot withold burn proceeds for a compressed NFT raffle")]
+    BurnProceedsCnft,
+    #[msg("Stake entries are not supported for compressed NFT raffles")]
+    StakeCnft,
+    #[msg("Invalid accounts provided for compressed NFT transfer")]
+    InvalidCnftAccounts,
+    #[msg("Invalid accounts provided for Core asset transfer")]
+    InvalidCoreAccounts,
+    #[msg("Compressed NFT proof args are required for a compressed NFT prize")]
+    CnftArgsNeeded,
+    #[msg("The provided asset is not the prize for this raffle")]
+    InvalidPrize,
+    #[msg("Fees wallet must be provided to pay the raffle fee")]
+    FeesWalletNeeded,
+    #[msg("Minimum tickets must be greater than 0 and no more than the ticket supply")]
+    InvalidMinTickets,
+    #[msg("Minimum tickets are only supported for spend raffles paid with tokens or NFTs")]
+    MinTicketsUnsupported,
+    #[msg("This raffle did not sell its minimum tickets and is refunding entrants")]
+    RaffleRefunding,
+    #[msg("This raffle is not refunding entrants")]
+    RaffleNotRefunding,
+    #[msg("NFT entry record must be provided for raffles with minimum tickets")]
+    NftEntryNeeded,
+    #[msg("A raffle can only be cancelled before any tickets are sold")]
+    RaffleHasEntrants,
+    #[msg("This action is not allowed in the raffle's current state")]
+    InvalidStateTransition,
+    #[msg("This raffle has been cancelled")]
+    RaffleCancelled,
+    #[msg("Ticket index is out of range for this raffle")]
+    InvalidTicketIndex,
+    #[msg("Randomness request does not match the one made for this raffle")]
+    RandomnessRequestMismatch,
+    #[msg("Randomness result must be 32 bytes")]
+    InvalidRandomnessLength,
+    #[msg("Randomness timeout must be greater than 0")]
+    InvalidRandomnessTimeout,
+    #[msg("This raffle is not awaiting randomness")]
+    NotAwaitingRandomness,
+    #[msg("The pending randomness request has not timed out yet")]
+    RandomnessRequestPending,
+    #[msg("This instruction does not match the raffle's randomness source")]
+    InvalidRandomnessSource,
+    #[msg("Commit-reveal raffles need a commitment, and the revealed secret must match it")]
+    InvalidCommitmentReveal,
+    #[msg("Randomness has not been revealed yet")]
+    RandomnessNotResolved,
+    #[msg("Randomness must be committed to the previous slot")]
+    StaleRandomnessCommitment,
+    #[msg("Claim window must be greater than 0")]
+    InvalidClaimWindow,
+    #[msg("The claim window for this prize has lapsed")]
+    ClaimWindowExpired,
+    #[msg("The claim window for this prize has not lapsed yet")]
+    ClaimWindowOpen,
+    #[msg("Unclaimed prizes of this raffle are reclaimed rather than re-drawn")]
+    RedrawNotEnabled,
+    #[msg("No tickets left to re-draw from")]
+    NoTicketsToRedraw,
+    #[msg("End time extension window and extension must be greater than 0")]
+    InvalidEndTimeExtension,
+    #[msg("Bundle prizes can only be added before the raffle starts")]
+    BundleLocked,
+    #[msg("This prize bundle is full")]
+    BundleFull,
+    #[msg("This raffle has a prize bundle that must be passed")]
+    BundleNeeded,
+    #[msg("The prize bundle has no recipient until the prize is claimed")]
+    BundleNotClaimed,
+    #[msg("Winner share must be between 1 and 10000 basis points")]
+    InvalidWinnerShare,
+    #[msg("Pot raffles must be paid for with tokens and use the spend entry type")]
+    PotRequiresTokenSpend,
+    #[msg("Jackpot series doesn't match this raffle")]
+    InvalidJackpotSeries,
+    #[msg("Unwon jackpots roll into the next round with roll_jackpot")]
+    JackpotMustRoll,
+    #[msg("This round's jackpot can still be won")]
+    JackpotNotRollable,
+    #[msg("The series' latest round is not open to a rolled jackpot")]
+    NextRoundNotOpen,
+    #[msg("Consolation pool needs an amount and a mint not used for the prize or proceeds")]
+    InvalidConsolationPool,
+    #[msg("This raffle has no consolation pool")]
+    NoConsolationPool,
+    #[msg("Winners can still be re-drawn")]
+    WinnersNotFinal,
+    #[msg("No non-winning tickets to claim a consolation share for")]
+    NoConsolationShare,
+    #[msg("This raffle has a consolation pool that must be passed")]
+    ConsolationPoolNeeded,
+    #[msg("NFT mint must be provided")]
+    NftMintNeeded,
+    #[msg("Entrant does not match the NFT entry record")]
+    InvalidEntrant,
+    #[msg("The raffler can still reveal the commit-reveal secret")]
+    RevealWindowOpen,
+    #[msg("Claim the consolation share before closing the entrant tally")]
+    ConsolationUnclaimed,
+    #[msg("Prize bundles can only be added to raffles with a single winner")]
+    BundleRequiresSingleWinner,
+    #[msg("Commit-reveal raffles refund an expired draw, so need entries that can be refunded")]
+    CommitRevealUnsupported,
+    #[msg("Token mint must be provided")]
+    TokenMintNeeded,
 }
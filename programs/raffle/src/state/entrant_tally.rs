@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+
+#[account]
+pub struct EntrantTally {
+    /// the raffle this tally belongs to (32)
+    pub raffle: Pubkey,
+    /// the wallet that bought the tickets (32)
+    pub entrant: Pubkey,
+    /// number of tickets bought by this wallet (4)
+    pub tickets: u32,
+    /// tokens this wallet's purchases added to the vault, net of transfer fees (8)
+    pub paid: u64,
+    /// has this wallet claimed its share of the consolation pool? (1)
+    pub consolation_claimed: bool,
+    /// bump for the entrant_tally PDA (1)
+    pub bump: u8,
+}
+
+impl EntrantTally {
+    pub const LEN: usize = 8 + 32 + 32 + 4 + 8 + 1 + 1;
+
+    pub fn init(raffle: Pubkey, entrant: Pubkey, bump: u8) -> Self {
+        Self {
+            raffle,
+            entrant,
+            tickets: 0,
+            paid: 0,
+            consolation_claimed: false,
+            bump,
+        }
+    }
+}
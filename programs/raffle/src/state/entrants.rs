@@ -1,39 +1,257 @@
-use std::cell::{Ref, RefMut};
+use std::mem::size_of;
 
 use anchor_lang::prelude::*;
 
 use crate::RaffleError;
 
-#[account]
+/// Tickets are stored as runs, each holding the entrant and the cumulative ticket count
+/// at the end of their purchase, so bulk purchases cost the same space as a single ticket.
+/// The runs live in the account data after this header and are accessed one slot at a time.
+#[account(zero_copy)]
 pub struct Entrants {
-    /// the current number of entrants
+    /// the current number of tickets sold
     pub total: u32,
-    /// the max nuber of entrants
+    /// the max nuber of tickets
     pub max: u32,
+    /// the number of ticket runs stored after the header
+    pub ranges: u32,
+}
+
+#[zero_copy]
+pub struct EntrantRange {
+    /// the entrant who bought this run of tickets
+    pub entrant: Pubkey,
+    /// cumulative ticket count at the end of this run
+    pub end: u32,
 }
 
 impl Entrants {
-    pub const BASE_SIZE: usize = 8 + 4 + 4;
+    pub const BASE_SIZE: usize = 8 + size_of::<Entrants>();
+    pub const RANGE_SIZE: usize = size_of::<EntrantRange>();
+
+    pub fn space(ranges: u32) -> usize {
+        Entrants::BASE_SIZE + Entrants::RANGE_SIZE * ranges as usize
+    }
+
+    pub fn load_range(entrants: &AccountLoader<Entrants>, range: u32) -> Result<EntrantRange> {
+        let start_index = Entrants::space(range);
+        let entrants_data = entrants.as_ref().try_borrow_data()?;
+        let slot = entrants_data
+            .get(start_index..start_index + Entrants::RANGE_SIZE)
+            .ok_or(ErrorCode::AccountDidNotDeserialize)?;
+
+        bytemuck::try_pod_read_unaligned(slot)
+            .map_err(|_| ErrorCode::AccountDidNotDeserialize.into())
+    }
+
+    fn store_range(
+        entrants: &AccountLoader<Entrants>,
+        range: u32,
+        entrant_range: &EntrantRange,
+    ) -> Result<()> {
+        let start_index = Entrants::space(range);
+        let mut entrants_data = entrants.as_ref().try_borrow_mut_data()?;
+        let slot = entrants_data
+            .get_mut(start_index..start_index + Entrants::RANGE_SIZE)
+            .ok_or(ErrorCode::AccountDidNotSerialize)?;
+
+        slot.copy_from_slice(bytemuck::bytes_of(entrant_range));
+        Ok(())
+    }
 
-    pub fn get_entrant(entrants_data: Ref<&mut [u8]>, index: usize) -> Pubkey {
-        let start_index = Entrants::BASE_SIZE + 32 * index;
-        Pubkey::try_from(&entrants_data[start_index..start_index + 32]).unwrap()
+    /// Resolves the owner of a ticket by binary searching for the first run ending after it
+    pub fn get_entrant(entrants: &AccountLoader<Entrants>, ticket_index: u32) -> Result<Pubkey> {
+        let (total, ranges) = {
+            let header = entrants.load()?;
+            (header.total, header.ranges)
+        };
+
+        require_gt!(total, ticket_index, RaffleError::InvalidTicketIndex);
+
+        let mut low = 0;
+        let mut high = ranges;
+
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if Entrants::load_range(entrants, mid)?.end > ticket_index {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+
+        require_gt!(ranges, low, RaffleError::InvalidTicketIndex);
+
+        Ok(Entrants::load_range(entrants, low)?.entrant)
+    }
+
+    /// Whether a purchase by this entrant can extend the last run rather than adding a new one
+    pub fn extends_last_range(entrants: &AccountLoader<Entrants>, entrant: Pubkey) -> Result<bool> {
+        let ranges = entrants.load()?.ranges;
+
+        Ok(ranges > 0 && Entrants::load_range(entrants, ranges - 1)?.entrant == entrant)
     }
 
     pub fn append_entrant(
-        &mut self,
-        mut entrants_data: RefMut<&mut [u8]>,
+        entrants: &AccountLoader<Entrants>,
         entrant: Pubkey,
+        amount: u32,
     ) -> Result<()> {
-        msg!("total {}, max {}", self.total, self.max);
-        if self.total >= self.max {
-            return err!(RaffleError::SoldOut);
+        let extends_last_range = Entrants::extends_last_range(entrants, entrant)?;
+
+        let (range, total) = {
+            let mut header = entrants.load_mut()?;
+            msg!("total {}, max {}", header.total, header.max);
+
+            let total = header
+                .total
+                .checked_add(amount)
+                .ok_or(RaffleError::ProgramAddError)?;
+
+            if total > header.max {
+                return err!(RaffleError::SoldOut);
+            }
+
+            if !extends_last_range {
+                header.ranges += 1;
+            }
+            header.total = total;
+
+            (header.ranges - 1, total)
+        };
+
+        Entrants::store_range(
+            entrants,
+            range,
+            &EntrantRange {
+                entrant,
+                end: total,
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anchor_lang::Discriminator;
+
+    use super::*;
+
+    /// an entrants account with room for `ranges` runs, as add_entrants leaves it after realloc
+    fn entrants_data(max: u32, ranges: u32) -> Vec<u8> {
+        let mut data = vec![0; Entrants::space(ranges)];
+        data[..8].copy_from_slice(&Entrants::DISCRIMINATOR);
+        data[12..16].copy_from_slice(&max.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn bulk_purchases_are_stored_as_one_run() {
+        let (key, owner, mut lamports) = (Pubkey::new_unique(), crate::ID, 0);
+        let mut data = entrants_data(10_000, 3);
+        let info = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+        let entrants = AccountLoader::<Entrants>::try_from(&info).unwrap();
+        let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        // a run costs the same 36 bytes whatever the purchase size
+        assert_eq!(Entrants::RANGE_SIZE, 36);
+
+        Entrants::append_entrant(&entrants, alice, 1000).unwrap();
+        assert!(Entrants::extends_last_range(&entrants, alice).unwrap());
+        Entrants::append_entrant(&entrants, alice, 500).unwrap();
+        assert!(!Entrants::extends_last_range(&entrants, bob).unwrap());
+        Entrants::append_entrant(&entrants, bob, 1).unwrap();
+        Entrants::append_entrant(&entrants, alice, 2).unwrap();
+
+        let header = entrants.load().unwrap();
+        assert_eq!((header.total, header.ranges), (1503, 3));
+        drop(header);
+
+        let ends: Vec<u32> = (0..3)
+            .map(|range| Entrants::load_range(&entrants, range).unwrap().end)
+            .collect();
+        assert_eq!(ends, [1500, 1501, 1503]);
+    }
+
+    #[test]
+    fn get_entrant_resolves_the_run_holding_a_ticket() {
+        let (key, owner, mut lamports) = (Pubkey::new_unique(), crate::ID, 0);
+        let mut data = entrants_data(100, 4);
+        let info = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+        let entrants = AccountLoader::<Entrants>::try_from(&info).unwrap();
+        let buyers: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+
+        for (buyer, amount) in buyers.iter().zip([3, 1, 10, 2]) {
+            Entrants::append_entrant(&entrants, *buyer, amount).unwrap();
         }
-        let current_index = Entrants::BASE_SIZE + 32 * self.total as usize;
-        let entrant_slice: &mut [u8] = &mut entrants_data[current_index..current_index + 32];
-        entrant_slice.copy_from_slice(&entrant.to_bytes());
-        self.total += 1;
 
-        Ok(())
+        // runs end at 3, 4, 14 and 16
+        for (ticket_index, buyer) in [(0, 0), (2, 0), (3, 1), (4, 2), (13, 2), (14, 3), (15, 3)] {
+            assert_eq!(
+                Entrants::get_entrant(&entrants, ticket_index).unwrap(),
+                buyers[buyer]
+            );
+        }
+    }
+
+    #[test]
+    fn accessors_error_instead_of_panicking() {
+        let (key, owner, mut lamports) = (Pubkey::new_unique(), crate::ID, 0);
+        let mut data = entrants_data(5, 1);
+        let info = AccountInfo::new(
+            &key,
+            false,
+            true,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+        let entrants = AccountLoader::<Entrants>::try_from(&info).unwrap();
+        let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        assert_eq!(
+            Entrants::get_entrant(&entrants, 0).unwrap_err(),
+            RaffleError::InvalidTicketIndex.into()
+        );
+
+        Entrants::append_entrant(&entrants, alice, 4).unwrap();
+        assert_eq!(
+            Entrants::append_entrant(&entrants, alice, 2).unwrap_err(),
+            RaffleError::SoldOut.into()
+        );
+        assert_eq!(
+            Entrants::get_entrant(&entrants, 4).unwrap_err(),
+            RaffleError::InvalidTicketIndex.into()
+        );
+
+        // the account only has room for one run until add_entrants reallocs it
+        assert_eq!(
+            Entrants::load_range(&entrants, 1).err(),
+            Some(ErrorCode::AccountDidNotDeserialize.into())
+        );
+        assert_eq!(
+            Entrants::append_entrant(&entrants, bob, 1).unwrap_err(),
+            ErrorCode::AccountDidNotSerialize.into()
+        );
     }
 }
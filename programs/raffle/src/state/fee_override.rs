@@ -0,0 +1,23 @@
+use anchor_lang::prelude::*;
+
+#[account]
+pub struct FeeOverride {
+    /// the raffler this override applies to (32)
+    pub raffler: Pubkey,
+    /// the amount in sol to set up a raffle, replaces ProgramConfig::raffle_fee (8)
+    pub raffle_fee: u64,
+    /// the bump of the fee_override account (1)
+    pub bump: u8,
+}
+
+impl FeeOverride {
+    pub const LEN: usize = 8 + 32 + 8 + 1;
+
+    pub fn init(raffler: Pubkey, raffle_fee: u64, bump: u8) -> Self {
+        Self {
+            raffler,
+            raffle_fee,
+            bump,
+        }
+    }
+}
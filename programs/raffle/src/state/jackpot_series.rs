@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+
+/// Links the consecutive pot raffles of a recurring lottery. Each raffle created with the
+/// series becomes its latest round, and `roll_jackpot` moves the pot of a round that ended
+/// with no entrants, or with its prize left unclaimed, into that round.
+#[account]
+pub struct JackpotSeries {
+    /// raffler running the series (32)
+    pub raffler: Pubkey,
+    /// mint tickets are paid in and the pot is held in (32)
+    pub token_mint: Pubkey,
+    /// basis points of each round's proceeds that go to the pot (2)
+    pub winner_share: u16,
+    /// number of rounds created so far (4)
+    pub rounds: u32,
+    /// the latest round, the only one a pot can roll into (1 + 32)
+    pub current_raffle: Option<Pubkey>,
+    /// bump (1)
+    pub bump: u8,
+}
+
+impl JackpotSeries {
+    pub const LEN: usize = 8 + 32 + 32 + 2 + 4 + (1 + 32) + 1;
+
+    pub fn init(raffler: Pubkey, token_mint: Pubkey, winner_share: u16, bump: u8) -> Self {
+        Self {
+            raffler,
+            token_mint,
+            winner_share,
+            rounds: 0,
+            current_raffle: None,
+            bump,
+        }
+    }
+}
@@ -1,9 +1,21 @@
+pub use entrant_tally::*;
 pub use entrants::*;
+pub use fee_override::*;
+pub use jackpot_series::*;
+pub use nft_entry::*;
+pub use prize_bundle::*;
 pub use program_config::*;
 pub use raffle::*;
 pub use raffler::*;
+pub use stake_record::*;
 
+pub mod entrant_tally;
 pub mod entrants;
+pub mod fee_override;
+pub mod jackpot_series;
+pub mod nft_entry;
+pub mod prize_bundle;
 pub mod program_config;
 pub mod raffle;
 pub mod raffler;
+pub mod stake_record;
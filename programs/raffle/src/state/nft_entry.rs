@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+
+#[account]
+pub struct NftEntry {
+    /// the raffle this NFT was entered into (32)
+    pub raffle: Pubkey,
+    /// the wallet that sent the NFT, and can reclaim it if the raffle refunds (32)
+    pub entrant: Pubkey,
+    /// mint of the NFT held by the raffle (32)
+    pub mint: Pubkey,
+    /// bump for the nft_entry PDA (1)
+    pub bump: u8,
+}
+
+impl NftEntry {
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 1;
+
+    pub fn init(raffle: Pubkey, entrant: Pubkey, mint: Pubkey, bump: u8) -> Self {
+        Self {
+            raffle,
+            entrant,
+            mint,
+            bump,
+        }
+    }
+}
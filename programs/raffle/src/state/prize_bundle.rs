@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct BundleItem {
+    /// mint of the token or NFT, held in the raffle's ATA for this mint (32)
+    pub mint: Pubkey,
+    /// amount held in custody, after any Token-2022 transfer fee (8)
+    pub amount: u64,
+    /// has this item been sent to the recipient? (1)
+    pub delivered: bool,
+}
+
+impl BundleItem {
+    pub const LEN: usize = 32 + 8 + 1;
+}
+
+/// Extra prizes delivered alongside a raffle's main prize. Items are deposited before
+/// the raffle starts, and once `claim_prize` settles who receives the bundle each item
+/// is delivered in its own `claim_bundle_prize` call so large bundles can span transactions.
+#[account]
+pub struct PrizeBundle {
+    /// the raffle this bundle belongs to (32)
+    pub raffle: Pubkey,
+    /// the raffle's entrants account, kept to sign for custody after a cancelled raffle closes (32)
+    pub entrants: Pubkey,
+    /// raffler authority who funded the bundle and receives the custody rent back (32)
+    pub authority: Pubkey,
+    /// who the items are delivered to, set when the main prize is claimed or the raffle is cancelled (1 + 32)
+    pub recipient: Option<Pubkey>,
+    /// the prizes in this bundle (4 + 41 * MAX_ITEMS)
+    pub items: Vec<BundleItem>,
+    /// bump for the raffle PDA (1)
+    pub raffle_bump: u8,
+    /// bump for the prize_bundle PDA (1)
+    pub bump: u8,
+}
+
+impl PrizeBundle {
+    pub const MAX_ITEMS: usize = 10;
+    pub const LEN: usize =
+        8 + 32 + 32 + 32 + (1 + 32) + (4 + BundleItem::LEN * PrizeBundle::MAX_ITEMS) + 1 + 1;
+
+    pub fn init(
+        raffle: Pubkey,
+        entrants: Pubkey,
+        authority: Pubkey,
+        raffle_bump: u8,
+        bump: u8,
+    ) -> Self {
+        Self {
+            raffle,
+            entrants,
+            authority,
+            recipient: None,
+            items: vec![],
+            raffle_bump,
+            bump,
+        }
+    }
+
+    pub fn undelivered(&self) -> usize {
+        self.items.iter().filter(|item| !item.delivered).count()
+    }
+}
@@ -6,6 +6,8 @@ pub struct ProgramConfig {
     pub raffle_fee: u64,
     /// the percentage in basis points of proceeds share (2)
     pub proceeds_share: u16,
+    /// seconds before an unfulfilled randomness request can be retried (8)
+    pub randomness_timeout: i64,
     /// a vector storing all slugs (4)
     pub slugs: Vec<String>,
     /// the bump of the program_config account (1)
@@ -13,16 +15,19 @@ pub struct ProgramConfig {
 }
 
 impl ProgramConfig {
-    pub const LEN: usize = 8 + 8 + 2 + 4 + 1;
+    pub const LEN: usize = 8 + 8 + 2 + 8 + 4 + 1;
+
+    pub const DEFAULT_RANDOMNESS_TIMEOUT: i64 = 60 * 60;
 
     pub fn current_len(&self) -> usize {
         ProgramConfig::LEN + (4 + 50) * self.slugs.len()
     }
 
-    pub fn init(raffle_fee: u64, proceeds_share: u16, bump: u8) -> Self {
+    pub fn init(raffle_fee: u64, proceeds_share: u16, randomness_timeout: i64, bump: u8) -> Self {
         Self {
             raffle_fee,
             proceeds_share,
+            randomness_timeout,
             slugs: vec![],
             bump,
         }
@@ -1,5 +1,11 @@
 use anchor_lang::prelude::*;
 
+use crate::{
+    state::Entrants,
+    utils::{eligible_tickets, winning_ticket_indices},
+    RaffleError,
+};
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
 pub enum EntryType {
     Spend,
@@ -24,7 +30,100 @@ pub enum PaymentType {
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq)]
 pub enum PrizeType {
     Nft,
-    Token { amount: u64 },
+    Token {
+        amount: u64,
+    },
+    Cnft,
+    CoreAsset,
+    /// native SOL held in the raffle's prize vault
+    Sol {
+        lamports: u64,
+    },
+    /// nothing is escrowed, winners share winner_share basis points of the ticket proceeds
+    Pot {
+        winner_share: u16,
+    },
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq)]
+pub enum RandomnessSource {
+    /// Switchboard randomness service, settled by its callback into consume_randomness
+    SwitchboardService,
+    /// Switchboard On-Demand randomness account, committed at draw and revealed by its oracle
+    SwitchboardOnDemand,
+    /// the raffler commits keccak(secret) at creation and reveals the secret at draw,
+    /// which is then mixed with the hash of a slot that hadn't been produced at reveal time
+    CommitReveal {
+        commitment: [u8; 32],
+        secret: Option<[u8; 32]>,
+    },
+}
+
+/// purchases close to the end push end_time out, so the final seconds can't be sniped
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct EndTimeExtension {
+    /// purchases made this many seconds or less before end_time extend the raffle
+    pub window: i64,
+    /// seconds each qualifying purchase adds to end_time
+    pub extension: i64,
+    /// the most end_time can be pushed past the original end time
+    pub max_extension: i64,
+}
+
+/// what happens to a prize share its winner didn't claim before the claim window lapsed
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq)]
+pub enum UnclaimedPrize {
+    /// draw a new winner from the tickets that haven't won
+    Redraw,
+    /// the raffler authority can claim the share back
+    Reclaim,
+}
+
+/// tokens deposited at creation and shared pro-rata between the tickets that didn't win
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct ConsolationPool {
+    /// mint of the pool, held in the raffle's ATA for this mint (32)
+    pub mint: Pubkey,
+    /// amount deposited, after any Token-2022 transfer fee (8)
+    pub amount: u64,
+    /// tickets that didn't win, set when the draw settles (4)
+    pub eligible_tickets: u32,
+    /// eligible tickets whose share has been claimed (4)
+    pub claimed_tickets: u32,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Debug)]
+pub enum RaffleState {
+    /// created, waiting for start_time
+    Scheduled,
+    /// accepting entries until end_time
+    Live,
+    /// every ticket has been sold, can be drawn before end_time
+    SoldOut,
+    /// a randomness request is in flight
+    AwaitingRandomness,
+    /// winners have been picked and can claim
+    Drawn,
+    /// every share of the prize has been claimed
+    Claimed,
+    /// cancelled by the raffler before any tickets were sold
+    Cancelled,
+    /// ended below min_tickets, or its commit-reveal draw expired, entrants can claim refunds
+    Refunding,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct Winner {
+    /// index of the winning ticket in the entrants account (4)
+    pub ticket_index: u32,
+    /// owner of the winning ticket, resolved when the draw settles (32)
+    pub entrant: Pubkey,
+    /// has this share of the prize been claimed? (1)
+    pub claimed: bool,
+}
+
+impl Winner {
+    pub const LEN: usize = 4 + 32 + 1;
 }
 
 #[account]
@@ -33,12 +132,28 @@ pub struct Raffle {
     pub raffler: Pubkey,
     /// the account the holds the entrants array (32)
     pub entrants: Pubkey,
-    /// mint address of the prize 32
+    /// mint address of the prize, or asset id for cNFT and Core prizes 32
     pub prize: Pubkey,
     /// type of prize (1 + 8)
     pub prize_type: PrizeType,
+    /// number of extra prizes in the raffle's prize bundle, see PrizeBundle (1)
+    pub bundle_items: u8,
+    /// jackpot series this raffle is a round of, see JackpotSeries (1 + 32)
+    pub jackpot_series: Option<Pubkey>,
+    /// pot rolled in from earlier rounds, paid to the winners without a fee or treasury cut (8)
+    pub carried_pot: u64,
+    /// optional pool for the non-winning tickets, see claim_consolation (1 + 32 + 8 + 4 + 4)
+    pub consolation: Option<ConsolationPool>,
     /// randomness from VRF (1 + 32)
     pub randomness: Option<[u8; 32]>,
+    /// where the randomness comes from, chosen at creation (1 + 32 + 1 + 32)
+    pub randomness_source: RandomnessSource,
+    /// randomness request awaiting fulfilment, set in draw_winner (1 + 32)
+    pub randomness_request: Option<Pubkey>,
+    /// slot the randomness request was made in, or the slot whose hash is mixed in for commit-reveal (8)
+    pub request_slot: u64,
+    /// timestamp of the randomness request, used to time out unfulfilled requests (8)
+    pub requested_at: i64,
     /// type of entry - Token or NFT (1 + 8)
     pub entry_type: EntryType,
     /// how do entrants pay for entries (1 + 32 + 8)
@@ -47,12 +162,34 @@ pub struct Raffle {
     pub gated_collection: Option<Pubkey>,
     /// timestamp of raffle start (8)
     pub start_time: i64,
-    /// timestamp of raffle end (8)
+    /// timestamp of raffle end, pushed out by late purchases when end_time_extension is set (8)
     pub end_time: i64,
-    /// has the prize been claimed? (1)
+    /// anti-sniping rule, None for a fixed end time (1 + 8 + 8 + 8)
+    pub end_time_extension: Option<EndTimeExtension>,
+    /// hard cap on end_time extensions (8)
+    pub max_end_time: i64,
+    /// have all shares of the prize been claimed? (1)
     pub claimed: bool,
+    /// lifecycle state, see RaffleState (1)
+    pub state: RaffleState,
+    /// number of winners to draw (1)
+    pub num_winners: u8,
+    /// winning tickets and their owners, populated when randomness is consumed (4 + 37 * num_winners)
+    pub winners: Vec<Winner>,
+    /// tickets whose share went unclaimed and was re-drawn, they can't win again (4 + 4 * num_winners * MAX_REDRAWS)
+    pub forfeited: Vec<u32>,
+    /// seconds winners have to claim once the draw settles, None for no deadline (1 + 8)
+    pub claim_window: Option<i64>,
+    /// what happens to shares left unclaimed when the claim window lapses (1)
+    pub unclaimed_prize: UnclaimedPrize,
+    /// when the current winners' claim window closes, 0 until drawn (8)
+    pub claim_deadline: i64,
     /// basis points of the maximum amount of tickets a single user can buy (2)
     pub max_entrant_pct: u16,
+    /// absolute maximum amount of tickets a single user can buy (1 + 4)
+    pub max_entrant_tickets: Option<u32>,
+    /// tickets that must be sold for the raffle to draw, otherwise entrants are refunded (1 + 4)
+    pub min_tickets: Option<u32>,
     /// uri link to offchain entrants log (4 + 63)
     pub uri: String,
     /// bump for the raffle PDA (1)
@@ -64,17 +201,418 @@ impl Raffle {
         + 32
         + 32
         + (1 + 32)
+        + 1
+        + (1 + 32)
+        + 8
+        + (1 + 32 + 8 + 4 + 4)
+        + (1 + 32)
+        + (1 + 32 + 1 + 32)
         + (1 + 32)
+        + 8
+        + 8
         + (1 + 32 + 8)
         + (1 + 8)
         + (1 + 32)
         + 8
         + 8
+        + (1 + 8 + 8 + 8)
+        + 8
         + 1
+        + 1
+        + 1
+        + 4
+        + 4
+        + (1 + 8)
+        + 1
+        + 8
         + 2
+        + (1 + 4)
+        + (1 + 4)
         + (4 + 63)
         + 1;
 
+    pub const MAX_WINNERS: u8 = 50;
+
+    /// how many times each share can be re-drawn on average, bounding the forfeited tickets
+    pub const MAX_REDRAWS: usize = 3;
+
+    pub fn space(num_winners: u8) -> usize {
+        Raffle::LEN + (Winner::LEN + 4 * Raffle::MAX_REDRAWS) * num_winners as usize
+    }
+
+    pub fn max_tickets_per_wallet(&self, max_tickets: u32) -> u32 {
+        let pct_cap = (max_tickets as u64 * self.max_entrant_pct as u64 / 10_000) as u32;
+        let pct_cap = pct_cap.max(1);
+
+        match self.max_entrant_tickets {
+            Some(max_entrant_tickets) => pct_cap.min(max_entrant_tickets),
+            None => pct_cap,
+        }
+    }
+
+    /// moves a raffle that has ended, or sold out, into AwaitingRandomness. A drawn raffle
+    /// whose claim window has lapsed is re-drawn for its unclaimed shares instead
+    pub fn begin_draw(&mut self, total_tickets: u32, current_time: i64) -> Result<()> {
+        if self.state == RaffleState::Drawn {
+            require!(self.claim_window.is_some(), RaffleError::WinnerAlreadyDrawn);
+            require!(
+                self.claim_expired(current_time),
+                RaffleError::ClaimWindowOpen
+            );
+            require!(
+                self.unclaimed_prize == UnclaimedPrize::Redraw,
+                RaffleError::RedrawNotEnabled
+            );
+            require!(
+                self.can_redraw(total_tickets),
+                RaffleError::NoTicketsToRedraw
+            );
+
+            let forfeited: Vec<u32> = self
+                .winners
+                .iter()
+                .filter(|w| !w.claimed)
+                .map(|w| w.ticket_index)
+                .collect();
+            self.forfeited.extend(forfeited);
+
+            return self.transition(RaffleState::AwaitingRandomness);
+        }
+
+        self.sync_state(total_tickets, current_time)?;
+
+        // sold out raffles can be drawn early, live ones have to wait for end_time
+        require!(
+            self.state != RaffleState::Scheduled
+                && !(self.state == RaffleState::Live && current_time < self.end_time),
+            RaffleError::RaffleNotEnded
+        );
+
+        self.transition(RaffleState::AwaitingRandomness)
+    }
+
+    /// a pending request can be replaced once the configured timeout has passed
+    pub fn assert_request_timed_out(&self, timeout: i64, current_time: i64) -> Result<()> {
+        require!(
+            self.state == RaffleState::AwaitingRandomness,
+            RaffleError::NotAwaitingRandomness
+        );
+
+        let expires_at = self
+            .requested_at
+            .checked_add(timeout)
+            .ok_or(RaffleError::ProgramAddError)?;
+
+        require_gte!(
+            current_time,
+            expires_at,
+            RaffleError::RandomnessRequestPending
+        );
+
+        Ok(())
+    }
+
+    /// closes out a raffle that ended without selling any tickets, which may never have been
+    /// drawn, once the raffler has taken the prize back or its pot has rolled on
+    pub fn close_unsold(&mut self, total_tickets: u32, current_time: i64) -> Result<()> {
+        require_eq!(total_tickets, 0, RaffleError::InvalidStateTransition);
+        require_gte!(current_time, self.end_time, RaffleError::RaffleNotEnded);
+
+        self.transition(RaffleState::Claimed)
+    }
+
+    /// a commit-reveal raffle whose secret hasn't been revealed by end_time plus the
+    /// randomness timeout can be drawn by anyone with Switchboard On-Demand instead
+    pub fn assert_reveal_missed(&self, timeout: i64, current_time: i64) -> Result<()> {
+        require!(
+            matches!(
+                self.randomness_source,
+                RandomnessSource::CommitReveal { secret: None, .. }
+            ),
+            RaffleError::InvalidRandomnessSource
+        );
+
+        let reveal_deadline = self
+            .end_time
+            .checked_add(timeout)
+            .ok_or(RaffleError::ProgramAddError)?;
+
+        require_gte!(current_time, reveal_deadline, RaffleError::RevealWindowOpen);
+
+        Ok(())
+    }
+
+    /// stores the randomness, whichever source it came from, then picks the winning tickets
+    /// and records who holds them so the winners can be read straight from the raffle.
+    /// On a re-draw only the unclaimed shares are replaced, and none of the current
+    /// winning tickets can win again
+    pub fn settle_randomness(
+        &mut self,
+        randomness: [u8; 32],
+        entrants: &AccountLoader<Entrants>,
+    ) -> Result<()> {
+        let current_time = Clock::get()?.unix_timestamp;
+        self.transition(RaffleState::Drawn)?;
+
+        self.randomness = Some(randomness);
+        self.randomness_request = None;
+
+        let total_tickets = entrants.load()?.total;
+
+        if total_tickets > 0 {
+            let excluded = self.excluded_tickets();
+            let num_winners = if self.winners.is_empty() {
+                self.num_winners as u32
+            } else {
+                self.unclaimed_winners() as u32
+            };
+
+            let drawn = winning_ticket_indices(randomness, total_tickets, num_winners, &excluded);
+
+            // begin_draw checked there are enough tickets for every unclaimed share
+            if !self.winners.is_empty() {
+                require_eq!(
+                    drawn.len(),
+                    num_winners as usize,
+                    RaffleError::NoTicketsToRedraw
+                );
+            }
+
+            let mut drawn = drawn
+                .into_iter()
+                .map(|ticket_index| {
+                    Ok(Winner {
+                        ticket_index,
+                        entrant: Entrants::get_entrant(entrants, ticket_index)?,
+                        claimed: false,
+                    })
+                })
+                .collect::<Result<Vec<Winner>>>()?
+                .into_iter();
+
+            if self.winners.is_empty() {
+                self.winners = drawn.collect();
+            } else {
+                for winner in self.winners.iter_mut().filter(|w| !w.claimed) {
+                    if let Some(replacement) = drawn.next() {
+                        *winner = replacement;
+                    }
+                }
+            }
+        }
+
+        let winning_tickets = self.winners.len() as u32;
+        if let Some(consolation) = self.consolation.as_mut() {
+            consolation.eligible_tickets = total_tickets.saturating_sub(winning_tickets);
+        }
+
+        if let Some(claim_window) = self.claim_window {
+            self.claim_deadline = current_time
+                .checked_add(claim_window)
+                .ok_or(RaffleError::ProgramAddError)?;
+        }
+
+        Ok(())
+    }
+
+    /// the fees wallet's and treasury's cut of the proceeds in a vault holding `balance`.
+    /// Pot raffles leave winner_share of the proceeds, and any carried pot, for the winners
+    pub fn split_proceeds(&self, balance: u64, fee_bp: u16) -> Result<(u64, u64)> {
+        let proceeds = balance
+            .checked_sub(self.carried_pot)
+            .ok_or(RaffleError::ProgramSubError)?;
+
+        let pot = match self.prize_type {
+            PrizeType::Pot { winner_share } => {
+                u64::try_from(proceeds as u128 * winner_share as u128 / 10_000).unwrap()
+            }
+            _ => 0,
+        };
+        let distributable = proceeds
+            .checked_sub(pot)
+            .ok_or(RaffleError::ProgramSubError)?;
+
+        let fee = u64::try_from(distributable as u128 * fee_bp as u128 / 10_000).unwrap();
+        let treasury_proceeds = distributable
+            .checked_sub(fee)
+            .ok_or(RaffleError::ProgramSubError)?;
+
+        Ok((treasury_proceeds, fee))
+    }
+
+    /// pushes end_time out when a purchase lands within the extension window, up to max_end_time
+    pub fn extend_end_time(&mut self, current_time: i64) -> Result<()> {
+        let Some(extension) = self.end_time_extension else {
+            return Ok(());
+        };
+
+        let window_start = self
+            .end_time
+            .checked_sub(extension.window)
+            .ok_or(RaffleError::ProgramSubError)?;
+
+        if current_time < window_start {
+            return Ok(());
+        }
+
+        self.end_time = self
+            .end_time
+            .checked_add(extension.extension)
+            .ok_or(RaffleError::ProgramAddError)?
+            .min(self.max_end_time);
+
+        Ok(())
+    }
+
+    /// the winning tickets can't be re-drawn any more, so the rest of the tickets are known
+    pub fn winners_final(&self) -> bool {
+        match self.state {
+            RaffleState::Claimed => true,
+            RaffleState::Drawn => {
+                self.claim_window.is_none()
+                    || self.unclaimed_prize == UnclaimedPrize::Reclaim
+                    || self.consolation.is_some_and(|consolation| {
+                        !self.can_redraw(consolation.eligible_tickets + self.winners.len() as u32)
+                    })
+            }
+            _ => false,
+        }
+    }
+
+    /// every unclaimed share can be re-drawn from tickets that have neither won nor forfeited
+    /// a share, without running out of room to record the forfeited tickets
+    pub fn can_redraw(&self, total_tickets: u32) -> bool {
+        let unclaimed = self.unclaimed_winners();
+        let eligible = eligible_tickets(total_tickets, &self.excluded_tickets()) as usize;
+
+        eligible >= unclaimed
+            && self.forfeited.len() + unclaimed <= self.num_winners as usize * Raffle::MAX_REDRAWS
+    }
+
+    /// tickets that can't win a re-drawn share, the current winners and every forfeited ticket.
+    /// begin_draw forfeits the unclaimed winners before they are replaced, so they are listed once
+    pub fn excluded_tickets(&self) -> Vec<u32> {
+        let mut excluded: Vec<u32> = self
+            .winners
+            .iter()
+            .map(|w| w.ticket_index)
+            .chain(self.forfeited.iter().copied())
+            .collect();
+        excluded.sort_unstable();
+        excluded.dedup();
+        excluded
+    }
+
+    /// the current winners' claim window has lapsed
+    pub fn claim_expired(&self, current_time: i64) -> bool {
+        self.claim_window.is_some() && current_time >= self.claim_deadline
+    }
+
+    /// binds the raffle to a randomness request so only that request can settle it
+    pub fn record_randomness_request(&mut self, request: Pubkey) -> Result<()> {
+        let clock = Clock::get()?;
+        self.randomness_request = Some(request);
+        self.request_slot = clock.slot;
+        self.requested_at = clock.unix_timestamp;
+        Ok(())
+    }
+
+    /// only the request recorded at draw time, made in the recorded slot, can settle the raffle
+    pub fn assert_randomness_request(&self, request: Pubkey, request_slot: u64) -> Result<()> {
+        require!(
+            self.randomness_request == Some(request) && self.request_slot == request_slot,
+            RaffleError::RandomnessRequestMismatch
+        );
+
+        Ok(())
+    }
+
+    pub fn unclaimed_winners(&self) -> usize {
+        self.winners.iter().filter(|w| !w.claimed).count()
+    }
+
+    /// a raffle that ends without selling its minimum tickets refunds entrants instead of drawing
+    pub fn is_refunding(&self, total_tickets: u32, current_time: i64) -> bool {
+        current_time >= self.end_time
+            && self
+                .min_tickets
+                .is_some_and(|min_tickets| total_tickets < min_tickets)
+    }
+
+    /// applies the transitions that only depend on the clock, handlers call this before reading the state
+    pub fn sync_state(&mut self, total_tickets: u32, current_time: i64) -> Result<()> {
+        if self.state == RaffleState::Scheduled && current_time >= self.start_time {
+            self.transition(RaffleState::Live)?;
+        }
+
+        if self.state == RaffleState::Live && self.is_refunding(total_tickets, current_time) {
+            self.transition(RaffleState::Refunding)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn transition(&mut self, next: RaffleState) -> Result<()> {
+        use RaffleState::*;
+
+        let is_valid = matches!(
+            (self.state, next),
+            (Scheduled, Live)
+                | (Scheduled | Live, Cancelled)
+                | (Live, SoldOut)
+                | (Live, Refunding)
+                // an unsold raffle is closed out without ever being drawn
+                | (Live, Claimed)
+                | (Live | SoldOut, AwaitingRandomness)
+                | (AwaitingRandomness, Drawn)
+                // a commit-reveal draw whose slot hash expired before it was settled
+                | (AwaitingRandomness, Refunding)
+                // unclaimed shares are re-drawn once the claim window lapses
+                | (Drawn, AwaitingRandomness)
+                | (Drawn, Claimed)
+        );
+
+        if !is_valid {
+            msg!(
+                "Invalid raffle state transition {:?} -> {:?}",
+                self.state,
+                next
+            );
+
+            return match self.state {
+                Cancelled => err!(RaffleError::RaffleCancelled),
+                Refunding => err!(RaffleError::RaffleRefunding),
+                Claimed => err!(RaffleError::AlreadyClaimed),
+                AwaitingRandomness | Drawn => err!(RaffleError::WinnerAlreadyDrawn),
+                _ => err!(RaffleError::InvalidStateTransition),
+            };
+        }
+
+        self.state = next;
+        Ok(())
+    }
+
+    /// winners have been picked, whether or not the prize has been claimed yet.
+    /// A raffle awaiting a re-draw has already been drawn once
+    pub fn is_drawn(&self) -> bool {
+        match self.state {
+            RaffleState::Drawn | RaffleState::Claimed => true,
+            RaffleState::AwaitingRandomness => self.randomness.is_some(),
+            _ => false,
+        }
+    }
+
+    pub fn assert_accepting_entries(&self, current_time: i64) -> Result<()> {
+        match self.state {
+            RaffleState::Scheduled => err!(RaffleError::NotStarted),
+            RaffleState::Live if current_time < self.end_time => Ok(()),
+            RaffleState::SoldOut => err!(RaffleError::SoldOut),
+            RaffleState::Cancelled => err!(RaffleError::RaffleCancelled),
+            RaffleState::Refunding => err!(RaffleError::RaffleRefunding),
+            _ => err!(RaffleError::Ended),
+        }
+    }
+
     pub fn init(
         raffler: Pubkey,
         prize: Pubkey,
@@ -85,24 +623,314 @@ impl Raffle {
         gated_collection: Option<Pubkey>,
         start_time: i64,
         end_time: i64,
+        end_time_extension: Option<EndTimeExtension>,
         max_entrant_pct: u16,
+        max_entrant_tickets: Option<u32>,
+        min_tickets: Option<u32>,
+        num_winners: u8,
+        randomness_source: RandomnessSource,
+        claim_window: Option<i64>,
+        unclaimed_prize: UnclaimedPrize,
         bump: u8,
-    ) -> Self {
-        Self {
+    ) -> Result<Self> {
+        let max_end_time = end_time
+            .checked_add(end_time_extension.map_or(0, |extension| extension.max_extension))
+            .ok_or(RaffleError::ProgramAddError)?;
+
+        Ok(Self {
             raffler,
             prize,
             prize_type,
+            bundle_items: 0,
+            jackpot_series: None,
+            carried_pot: 0,
+            consolation: None,
             entry_type,
             payment_type,
             randomness: None,
+            randomness_source,
+            randomness_request: None,
+            request_slot: 0,
+            requested_at: 0,
             entrants,
             gated_collection,
             start_time,
             end_time,
+            end_time_extension,
+            max_end_time,
             claimed: false,
+            state: RaffleState::Scheduled,
+            num_winners,
+            winners: vec![],
+            forfeited: vec![],
+            claim_window,
+            unclaimed_prize,
+            claim_deadline: 0,
             max_entrant_pct,
+            max_entrant_tickets,
+            min_tickets,
             uri: String::new(),
             bump,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// a single-winner token raffle running from 1,000 to 2,000 with no per-wallet limits
+    fn raffle() -> Raffle {
+        Raffle::init(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            PrizeType::Nft,
+            EntryType::Spend,
+            PaymentType::Token {
+                token_mint: Pubkey::new_unique(),
+                ticket_price: 1,
+            },
+            Pubkey::new_unique(),
+            None,
+            1_000,
+            2_000,
+            None,
+            10_000,
+            None,
+            None,
+            1,
+            RandomnessSource::SwitchboardService,
+            None,
+            UnclaimedPrize::Reclaim,
+            255,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn max_tickets_per_wallet_applies_the_tighter_cap() {
+        let mut raffle = raffle();
+        assert_eq!(raffle.max_tickets_per_wallet(1_000), 1_000);
+
+        raffle.max_entrant_pct = 2_500;
+        assert_eq!(raffle.max_tickets_per_wallet(1_000), 250);
+
+        raffle.max_entrant_tickets = Some(100);
+        assert_eq!(raffle.max_tickets_per_wallet(1_000), 100);
+
+        raffle.max_entrant_tickets = Some(400);
+        assert_eq!(raffle.max_tickets_per_wallet(1_000), 250);
+    }
+
+    #[test]
+    fn max_tickets_per_wallet_allows_at_least_one_ticket() {
+        let mut raffle = raffle();
+        raffle.max_entrant_pct = 1;
+
+        assert_eq!(raffle.max_tickets_per_wallet(10), 1);
+    }
+
+    const STATES: [RaffleState; 8] = [
+        RaffleState::Scheduled,
+        RaffleState::Live,
+        RaffleState::SoldOut,
+        RaffleState::AwaitingRandomness,
+        RaffleState::Drawn,
+        RaffleState::Claimed,
+        RaffleState::Cancelled,
+        RaffleState::Refunding,
+    ];
+
+    #[test]
+    fn transition_only_allows_lifecycle_edges() {
+        use RaffleState::*;
+
+        let allowed = [
+            (Scheduled, Live),
+            (Scheduled, Cancelled),
+            (Live, Cancelled),
+            (Live, SoldOut),
+            (Live, Refunding),
+            (Live, Claimed),
+            (Live, AwaitingRandomness),
+            (SoldOut, AwaitingRandomness),
+            (AwaitingRandomness, Drawn),
+            (AwaitingRandomness, Refunding),
+            (Drawn, AwaitingRandomness),
+            (Drawn, Claimed),
+        ];
+
+        for from in STATES {
+            for to in STATES {
+                let mut raffle = raffle();
+                raffle.state = from;
+
+                let result = raffle.transition(to);
+                if allowed.contains(&(from, to)) {
+                    assert!(result.is_ok(), "{:?} -> {:?} should be allowed", from, to);
+                    assert_eq!(raffle.state, to);
+                } else {
+                    assert!(result.is_err(), "{:?} -> {:?} should be rejected", from, to);
+                    assert_eq!(raffle.state, from);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn transition_rejects_with_the_current_state_error() {
+        let mut raffle = raffle();
+
+        for (state, error) in [
+            (RaffleState::Cancelled, RaffleError::RaffleCancelled),
+            (RaffleState::Refunding, RaffleError::RaffleRefunding),
+            (RaffleState::Claimed, RaffleError::AlreadyClaimed),
+            (RaffleState::Drawn, RaffleError::WinnerAlreadyDrawn),
+            (RaffleState::SoldOut, RaffleError::InvalidStateTransition),
+        ] {
+            raffle.state = state;
+            assert_eq!(
+                raffle.transition(RaffleState::Live).unwrap_err(),
+                error.into()
+            );
         }
     }
+
+    #[test]
+    fn sync_state_follows_the_clock() {
+        let mut raffle = raffle();
+        raffle.min_tickets = Some(5);
+
+        raffle.sync_state(0, 999).unwrap();
+        assert_eq!(raffle.state, RaffleState::Scheduled);
+
+        raffle.sync_state(0, 1_000).unwrap();
+        assert_eq!(raffle.state, RaffleState::Live);
+
+        raffle.sync_state(5, 2_000).unwrap();
+        assert_eq!(raffle.state, RaffleState::Live);
+
+        raffle.sync_state(4, 2_000).unwrap();
+        assert_eq!(raffle.state, RaffleState::Refunding);
+    }
+
+    #[test]
+    fn sync_state_refunds_a_raffle_that_ended_before_anyone_looked() {
+        let mut raffle = raffle();
+        raffle.min_tickets = Some(1);
+
+        raffle.sync_state(0, 3_000).unwrap();
+        assert_eq!(raffle.state, RaffleState::Refunding);
+    }
+
+    #[test]
+    fn begin_draw_waits_for_the_end_unless_sold_out() {
+        let mut live = raffle();
+
+        assert_eq!(
+            live.begin_draw(1, 1_500).unwrap_err(),
+            RaffleError::RaffleNotEnded.into()
+        );
+        assert_eq!(live.state, RaffleState::Live);
+
+        live.begin_draw(1, 2_000).unwrap();
+        assert_eq!(live.state, RaffleState::AwaitingRandomness);
+
+        let mut sold_out = raffle();
+        sold_out.state = RaffleState::SoldOut;
+        sold_out.begin_draw(1, 1_500).unwrap();
+        assert_eq!(sold_out.state, RaffleState::AwaitingRandomness);
+    }
+
+    #[test]
+    fn close_unsold_needs_an_ended_raffle_without_tickets() {
+        let mut live = raffle();
+        live.state = RaffleState::Live;
+
+        assert_eq!(
+            live.close_unsold(0, 1_999).unwrap_err(),
+            RaffleError::RaffleNotEnded.into()
+        );
+        assert_eq!(
+            live.close_unsold(1, 2_000).unwrap_err(),
+            RaffleError::InvalidStateTransition.into()
+        );
+
+        live.close_unsold(0, 2_000).unwrap();
+        assert_eq!(live.state, RaffleState::Claimed);
+
+        let mut cancelled = raffle();
+        cancelled.state = RaffleState::Cancelled;
+        assert_eq!(
+            cancelled.close_unsold(0, 2_000).unwrap_err(),
+            RaffleError::RaffleCancelled.into()
+        );
+    }
+
+    #[test]
+    fn extend_end_time_only_extends_purchases_in_the_window() {
+        let mut raffle = raffle();
+        raffle.extend_end_time(1_999).unwrap();
+        assert_eq!(raffle.end_time, 2_000);
+
+        raffle.end_time_extension = Some(EndTimeExtension {
+            window: 60,
+            extension: 120,
+            max_extension: 300,
+        });
+        raffle.max_end_time = 2_300;
+
+        raffle.extend_end_time(1_939).unwrap();
+        assert_eq!(raffle.end_time, 2_000);
+
+        raffle.extend_end_time(1_940).unwrap();
+        assert_eq!(raffle.end_time, 2_120);
+    }
+
+    #[test]
+    fn extend_end_time_stops_at_the_cap() {
+        let mut raffle = raffle();
+        raffle.end_time_extension = Some(EndTimeExtension {
+            window: 60,
+            extension: 120,
+            max_extension: 300,
+        });
+        raffle.max_end_time = 2_300;
+
+        let end_times: Vec<i64> = [1_990, 2_100, 2_200, 2_290]
+            .into_iter()
+            .map(|current_time| {
+                raffle.extend_end_time(current_time).unwrap();
+                raffle.end_time
+            })
+            .collect();
+
+        assert_eq!(end_times, [2_120, 2_240, 2_300, 2_300]);
+    }
+
+    #[test]
+    fn assert_randomness_request_only_accepts_the_recorded_request() {
+        let mut raffle = raffle();
+        let request = Pubkey::new_unique();
+
+        assert_eq!(
+            raffle.assert_randomness_request(request, 0).unwrap_err(),
+            RaffleError::RandomnessRequestMismatch.into()
+        );
+
+        raffle.randomness_request = Some(request);
+        raffle.request_slot = 42;
+
+        raffle.assert_randomness_request(request, 42).unwrap();
+        assert_eq!(
+            raffle
+                .assert_randomness_request(Pubkey::new_unique(), 42)
+                .unwrap_err(),
+            RaffleError::RandomnessRequestMismatch.into()
+        );
+        assert_eq!(
+            raffle.assert_randomness_request(request, 41).unwrap_err(),
+            RaffleError::RandomnessRequestMismatch.into()
+        );
+    }
 }
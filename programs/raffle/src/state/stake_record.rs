@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+
+#[account]
+pub struct StakeRecord {
+    /// the raffle the stake was entered into (32)
+    pub raffle: Pubkey,
+    /// the wallet that owns the stake (32)
+    pub entrant: Pubkey,
+    /// mint of the staked token or NFT (32)
+    pub mint: Pubkey,
+    /// amount currently held in escrow (8)
+    pub amount: u64,
+    /// timestamp of the most recent deposit (8)
+    pub staked_at: i64,
+    /// bump for the stake_record PDA (1)
+    pub bump: u8,
+}
+
+impl StakeRecord {
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 8 + 8 + 1;
+
+    pub fn init(raffle: Pubkey, entrant: Pubkey, mint: Pubkey, bump: u8) -> Self {
+        Self {
+            raffle,
+            entrant,
+            mint,
+            amount: 0,
+            staked_at: 0,
+            bump,
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use anchor_lang::prelude::*;
+use mpl_bubblegum::{instructions::TransferCpiBuilder, utils::get_asset_id};
+
+use crate::{RaffleError, COMPRESSION_PROGRAM, NOOP_PROGRAM};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct CnftArgs {
+    pub root: [u8; 32],
+    pub data_hash: [u8; 32],
+    pub creator_hash: [u8; 32],
+    pub nonce: u64,
+    pub index: u32,
+}
+
+/// Transfers a compressed NFT using accounts passed as remaining accounts in the order
+/// tree_config, merkle_tree, bubblegum_program, log_wrapper, compression_program, ...proof.
+/// Returns the asset id of the transferred leaf.
+pub fn transfer_cnft<'info>(
+    cnft_accounts: &[AccountInfo<'info>],
+    leaf_owner: &AccountInfo<'info>,
+    new_leaf_owner: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    args: CnftArgs,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<Pubkey> {
+    let cnft_accounts = &mut cnft_accounts.iter();
+    let tree_config = next_account_info(cnft_accounts)?;
+    let merkle_tree = next_account_info(cnft_accounts)?;
+    let bubblegum_program = next_account_info(cnft_accounts)?;
+    let log_wrapper = next_account_info(cnft_accounts)?;
+    let compression_program = next_account_info(cnft_accounts)?;
+
+    require_keys_eq!(
+        bubblegum_program.key(),
+        mpl_bubblegum::ID,
+        RaffleError::InvalidCnftAccounts
+    );
+    require_keys_eq!(
+        log_wrapper.key(),
+        NOOP_PROGRAM,
+        RaffleError::InvalidCnftAccounts
+    );
+    require_keys_eq!(
+        compression_program.key(),
+        COMPRESSION_PROGRAM,
+        RaffleError::InvalidCnftAccounts
+    );
+
+    let proof_accounts: Vec<(&AccountInfo<'info>, bool, bool)> = cnft_accounts
+        .as_slice()
+        .iter()
+        .map(|node| (node, false, false))
+        .collect();
+
+    let mut cpi_transfer = TransferCpiBuilder::new(bubblegum_program);
+
+    cpi_transfer
+        .tree_config(tree_config)
+        .leaf_owner(leaf_owner, true)
+        .leaf_delegate(leaf_owner, false)
+        .new_leaf_owner(new_leaf_owner)
+        .merkle_tree(merkle_tree)
+        .log_wrapper(log_wrapper)
+        .compression_program(compression_program)
+        .system_program(system_program)
+        .root(args.root)
+        .data_hash(args.data_hash)
+        .creator_hash(args.creator_hash)
+        .nonce(args.nonce)
+        .index(args.index)
+        .add_remaining_accounts(&proof_accounts);
+
+    // performs the CPI
+    cpi_transfer.invoke_signed(signer_seeds)?;
+
+    Ok(get_asset_id(&merkle_tree.key(), args.nonce))
+}
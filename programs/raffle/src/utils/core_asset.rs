@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+use mpl_core::instructions::TransferV1CpiBuilder;
+
+use crate::RaffleError;
+
+/// Transfers a Core asset using accounts passed as remaining accounts in the order
+/// asset, core_program, and optionally the asset's collection.
+/// Returns the address of the transferred asset.
+pub fn transfer_core_asset<'info>(
+    core_accounts: &[AccountInfo<'info>],
+    payer: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    new_owner: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<Pubkey> {
+    let core_accounts = &mut core_accounts.iter();
+    let asset = next_account_info(core_accounts)?;
+    let core_program = next_account_info(core_accounts)?;
+    let collection = next_account_info(core_accounts).ok();
+
+    require_keys_eq!(
+        core_program.key(),
+        mpl_core::ID,
+        RaffleError::InvalidCoreAccounts
+    );
+    require_keys_eq!(*asset.owner, mpl_core::ID, RaffleError::InvalidCoreAccounts);
+
+    let mut cpi_transfer = TransferV1CpiBuilder::new(core_program);
+
+    cpi_transfer
+        .asset(asset)
+        .collection(collection)
+        .payer(payer)
+        .authority(Some(authority))
+        .new_owner(new_owner)
+        .system_program(Some(system_program));
+
+    // performs the CPI
+    cpi_transfer.invoke_signed(signer_seeds)?;
+
+    Ok(asset.key())
+}
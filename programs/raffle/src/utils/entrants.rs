@@ -1,20 +1,60 @@
 use anchor_lang::prelude::*;
 use solana_program::system_instruction;
 
-use crate::{state::Entrants, RaffleError};
+use crate::{
+    state::{EntrantTally, Entrants, Raffle, RaffleState},
+    RaffleError,
+};
 
 pub fn add_entrants<'info>(
-    entrants: &mut Account<'info, Entrants>,
+    raffle: &mut Account<'info, Raffle>,
+    entrants: &AccountLoader<'info, Entrants>,
+    entrant_tally: &mut Account<'info, EntrantTally>,
+    entrant_tally_bump: u8,
     entrant_account_info: AccountInfo<'info>,
     system_program_account_info: AccountInfo<'info>,
     amount: u32,
-) -> Result<()> {
+) -> Result<u32> {
+    let current_time = Clock::get().unwrap().unix_timestamp;
+    let (total, max, ranges) = {
+        let entrants = entrants.load()?;
+        (entrants.total, entrants.max, entrants.ranges)
+    };
+    raffle.sync_state(total, current_time)?;
+    raffle.assert_accepting_entries(current_time)?;
+
+    // first purchase for this wallet, the tally was just created
+    if entrant_tally.raffle == Pubkey::default() {
+        **entrant_tally =
+            EntrantTally::init(raffle.key(), entrant_account_info.key(), entrant_tally_bump);
+    }
+
+    let tickets = entrant_tally
+        .tickets
+        .checked_add(amount)
+        .ok_or(RaffleError::ProgramAddError)?;
+
+    require_gte!(
+        raffle.max_tickets_per_wallet(max),
+        tickets,
+        RaffleError::MaxTicketsPerWallet
+    );
+
+    entrant_tally.tickets = tickets;
+
     let entrants_account_info: AccountInfo<'info> = entrants.to_account_info();
-    let required_size = 8 + 4 + 4 + (entrants.total + amount) * 32;
-    let required_lamports = Rent::get().unwrap().minimum_balance(required_size as usize);
-    let current_lamports = entrants_account_info.lamports();
     let entrant_key = entrant_account_info.key();
 
+    // repeat purchases by the last buyer extend their run, anyone else needs space for a new one
+    let ranges = if Entrants::extends_last_range(entrants, entrant_key)? {
+        ranges
+    } else {
+        ranges + 1
+    };
+    let required_size = Entrants::space(ranges);
+    let required_lamports = Rent::get().unwrap().minimum_balance(required_size);
+    let current_lamports = entrants_account_info.lamports();
+
     msg!(
         "required_size {}, required_lamports {} current_lamports {}",
         required_size,
@@ -39,17 +79,23 @@ pub fn add_entrants<'info>(
                 system_program_account_info,
             ],
         )?;
-
-        entrants
-            .to_account_info()
-            .realloc(required_size as usize, false)?;
     }
 
-    for _ in 0..amount {
-        entrants.append_entrant(entrants_account_info.data.borrow_mut(), entrant_key)?;
+    if required_size > entrants_account_info.data_len() {
+        entrants_account_info.realloc(required_size, false)?;
     }
 
-    msg!("Total entrants: {}", entrants.total);
+    Entrants::append_entrant(entrants, entrant_key, amount)?;
+
+    let total = entrants.load()?.total;
+    msg!("Total entrants: {}", total);
+
+    if total >= max {
+        raffle.transition(RaffleState::SoldOut)?;
+    } else {
+        raffle.extend_end_time(current_time)?;
+    }
 
-    Ok(())
+    // index of the first ticket in this purchase
+    Ok(total - amount)
 }
@@ -1,5 +1,17 @@
+pub use cnft::*;
+pub use core_asset::*;
 pub use entrants::*;
+pub use prize_vault::*;
+pub use randomness_service::*;
 pub use randomness_tools::*;
+pub use stake::*;
+pub use token::*;
 
+pub mod cnft;
+pub mod core_asset;
 pub mod entrants;
+pub mod prize_vault;
+pub mod randomness_service;
 pub mod randomness_tools;
+pub mod stake;
+pub mod token;
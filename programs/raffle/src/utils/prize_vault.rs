@@ -0,0 +1,41 @@
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
+
+/// Pays lamports out of a raffle's SOL prize vault, the system account PDA
+/// seeded by `[b"RAFFLE", raffle, b"prize-vault"]`
+pub fn transfer_from_prize_vault<'info>(
+    prize_vault: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    raffle: Pubkey,
+    bump: u8,
+    lamports: u64,
+) -> Result<()> {
+    let vault_seed = &[
+        &b"RAFFLE"[..],
+        raffle.as_ref(),
+        &b"prize-vault"[..],
+        &[bump],
+    ];
+
+    transfer(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            Transfer {
+                from: prize_vault.clone(),
+                to: destination.clone(),
+            },
+            &[vault_seed],
+        ),
+        lamports,
+    )
+}
+
+/// Lamports in the vault above its rent exempt minimum, which is what winners share
+pub fn prize_vault_balance(prize_vault: &AccountInfo) -> Result<u64> {
+    Ok(prize_vault
+        .lamports()
+        .saturating_sub(Rent::get()?.minimum_balance(0)))
+}
@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+use solana_randomness_service::{cpi::accounts::SimpleRandomnessV1Request, TransactionOptions};
+use switchboard_solana::get_ixn_discriminator;
+
+use crate::ID;
+
+/// Requests 32 bytes from the Switchboard randomness service. The service settles the
+/// request by calling back into `consume_randomness` with the raffle's accounts.
+pub fn request_randomness<'info>(
+    randomness_service: AccountInfo<'info>,
+    request_accounts: SimpleRandomnessV1Request<'info>,
+    raffle: Pubkey,
+    entrants: Pubkey,
+    event_authority: Pubkey,
+    priority_fee: u64,
+) -> Result<()> {
+    let state = request_accounts.state.key();
+    let request = request_accounts.request.key();
+
+    solana_randomness_service::cpi::simple_randomness_v1(
+        CpiContext::new(randomness_service, request_accounts),
+        32,
+        solana_randomness_service::Callback {
+            program_id: ID,
+            accounts: vec![
+                AccountMeta::new_readonly(state, true).into(),
+                AccountMeta::new_readonly(request, false).into(),
+                AccountMeta::new(raffle, false).into(),
+                AccountMeta::new_readonly(entrants, false).into(),
+                AccountMeta::new_readonly(event_authority, false).into(),
+                AccountMeta::new_readonly(ID, false).into(),
+            ],
+            ix_data: get_ixn_discriminator("consume_randomness").to_vec(),
+        },
+        Some(TransactionOptions {
+            compute_units: Some(1_000_000),
+            compute_unit_price: Some(priority_fee),
+        }),
+    )
+}
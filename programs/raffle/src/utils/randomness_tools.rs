@@ -1,12 +1,324 @@
 use anchor_lang::solana_program::keccak;
 
-pub fn expand_randomness(randomness: [u8; 32]) -> u32 {
-    let mut hasher = keccak::Hasher::default();
-    hasher.hash(&randomness);
-
-    u32::from_le_bytes(
-        hasher.result().to_bytes()[0..4]
-            .try_into()
-            .expect("slice with incorrect length"),
-    )
+/// Deterministic stream of u64s expanded from a single randomness result, so a draw can
+/// be reproduced by anyone holding the raffle's stored randomness.
+///
+/// Block `n` of the stream is `keccak256(randomness || n as u32 little endian)`, and each
+/// block is consumed as four little endian u64 words in order.
+pub struct RandomnessStream {
+    randomness: [u8; 32],
+    counter: u32,
+    block: [u8; 32],
+    offset: usize,
+}
+
+impl RandomnessStream {
+    pub fn new(randomness: [u8; 32]) -> Self {
+        RandomnessStream {
+            randomness,
+            counter: 0,
+            block: [0; 32],
+            // forces the first block to be hashed on the first read
+            offset: 32,
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        if self.offset == 32 {
+            self.block = keccak::hashv(&[&self.randomness, &self.counter.to_le_bytes()]).to_bytes();
+            self.counter = self.counter.wrapping_add(1);
+            self.offset = 0;
+        }
+
+        let word = u64::from_le_bytes(
+            self.block[self.offset..self.offset + 8]
+                .try_into()
+                .expect("slice with incorrect length"),
+        );
+        self.offset += 8;
+        word
+    }
+
+    /// Uniform value in `0..bound` by rejection sampling. Words below `2^64 mod bound` are
+    /// discarded so every remaining residue is equally likely, which a plain modulo is not.
+    pub fn next_below(&mut self, bound: u32) -> u32 {
+        assert!(bound > 0, "bound must be greater than 0");
+        let bound = bound as u64;
+        let threshold = bound.wrapping_neg() % bound;
+
+        loop {
+            let word = self.next_u64();
+            if word >= threshold {
+                return (word % bound) as u32;
+            }
+        }
+    }
+}
+
+/// Number of tickets in `0..total` that aren't in `excluded`. Each excluded ticket is
+/// counted once, however often it is listed, and tickets past the total are ignored.
+pub fn eligible_tickets(total: u32, excluded: &[u32]) -> u32 {
+    let mut excluded: Vec<u32> = excluded.iter().copied().filter(|t| *t < total).collect();
+    excluded.sort_unstable();
+    excluded.dedup();
+
+    total - excluded.len() as u32
+}
+
+/// Derives `num_winners` distinct ticket indices from a single randomness result.
+/// Indices are read from a `RandomnessStream` with `next_below(total)`, and an index
+/// that has already won is discarded and redrawn, so each ticket is equally likely
+/// to fill every winning position. `excluded` tickets, such as the winners being
+/// replaced in a re-draw, are discarded in the same way.
+pub fn winning_ticket_indices(
+    randomness: [u8; 32],
+    total: u32,
+    num_winners: u32,
+    excluded: &[u32],
+) -> Vec<u32> {
+    let num_winners = num_winners.min(eligible_tickets(total, excluded));
+    let mut stream = RandomnessStream::new(randomness);
+    let mut winners: Vec<u32> = Vec::with_capacity(num_winners as usize);
+
+    while winners.len() < num_winners as usize {
+        let index = stream.next_below(total);
+        if !winners.contains(&index) && !excluded.contains(&index) {
+            winners.push(index);
+        }
+    }
+
+    winners
+}
+
+/// Result of looking up the slot hash that settles a commit-reveal draw
+pub enum SlotHashLookup {
+    /// the first slot at or after the target, with its hash
+    Found(u64, [u8; 32]),
+    /// no slot at or after the target has been recorded yet
+    Pending,
+    /// the target has fallen out of the sysvar's history, so the first slot after it is unknown
+    Expired,
+}
+
+/// Looks up the first slot at or after `target_slot` in raw `SlotHashes` sysvar data, which
+/// is a u64 length followed by (slot, hash) entries ordered newest first. Skipped slots have
+/// no entry, so the next produced slot settles the draw instead.
+pub fn find_slot_hash(slot_hashes: &[u8], target_slot: u64) -> SlotHashLookup {
+    const ENTRY_SIZE: usize = 8 + 32;
+
+    let read_entry = |index: usize| -> Option<(u64, [u8; 32])> {
+        let start = 8 + index * ENTRY_SIZE;
+        let entry = slot_hashes.get(start..start + ENTRY_SIZE)?;
+        Some((
+            u64::from_le_bytes(entry[0..8].try_into().ok()?),
+            entry[8..].try_into().ok()?,
+        ))
+    };
+
+    let len = slot_hashes
+        .get(0..8)
+        .and_then(|len| len.try_into().ok())
+        .map(u64::from_le_bytes)
+        .unwrap_or_default() as usize;
+
+    // walk from the oldest entry towards the newest
+    for index in (0..len).rev() {
+        let Some((slot, hash)) = read_entry(index) else {
+            return SlotHashLookup::Pending;
+        };
+
+        if slot >= target_slot {
+            // if even the oldest entry is past the target, earlier slots may have dropped out
+            if index == len - 1 && slot > target_slot {
+                return SlotHashLookup::Expired;
+            }
+            return SlotHashLookup::Found(slot, hash);
+        }
+    }
+
+    SlotHashLookup::Pending
+}
+
+/// Mixes the raffler's revealed secret with a slot hash neither party could know at reveal time
+pub fn commit_reveal_randomness(secret: [u8; 32], slot_hash: [u8; 32]) -> [u8; 32] {
+    keccak::hashv(&[&secret, &slot_hash]).to_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sequential() -> [u8; 32] {
+        let mut randomness = [0u8; 32];
+        for (i, byte) in randomness.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        randomness
+    }
+
+    /// raw SlotHashes sysvar data, entries given newest first
+    fn slot_hashes(entries: &[(u64, u8)]) -> Vec<u8> {
+        let mut data = (entries.len() as u64).to_le_bytes().to_vec();
+        for (slot, hash) in entries {
+            data.extend_from_slice(&slot.to_le_bytes());
+            data.extend_from_slice(&[*hash; 32]);
+        }
+        data
+    }
+
+    #[test]
+    fn stream_matches_fixed_vector() {
+        let mut stream = RandomnessStream::new([0; 32]);
+        let words: Vec<u64> = (0..5).map(|_| stream.next_u64()).collect();
+
+        // the fifth word comes from the second block
+        assert_eq!(
+            words,
+            [
+                15589685029427180148,
+                13335784868504167063,
+                18176509182764399806,
+                1958065747258641393,
+                1983417590045772508
+            ]
+        );
+    }
+
+    #[test]
+    fn next_below_stays_in_bounds() {
+        let mut stream = RandomnessStream::new([3; 32]);
+
+        for bound in [1, 2, 7, 1000, u32::MAX] {
+            for _ in 0..100 {
+                assert!(stream.next_below(bound) < bound);
+            }
+        }
+    }
+
+    #[test]
+    fn winning_ticket_indices_match_fixed_vectors() {
+        assert_eq!(
+            winning_ticket_indices(sequential(), 1000, 5, &[]),
+            [99, 174, 875, 231, 486]
+        );
+        assert_eq!(
+            winning_ticket_indices([7; 32], 3_000_000_000, 3, &[]),
+            [534839736, 995315358, 2649050750]
+        );
+    }
+
+    #[test]
+    fn winning_ticket_indices_picks_every_ticket_once() {
+        let winners = winning_ticket_indices([255; 32], 10, 10, &[]);
+
+        assert_eq!(winners, [7, 1, 4, 5, 9, 8, 0, 2, 6, 3]);
+    }
+
+    #[test]
+    fn winning_ticket_indices_skips_excluded_tickets() {
+        // the same stream as the fixed vector, with two of its winners excluded
+        assert_eq!(
+            winning_ticket_indices(sequential(), 1000, 5, &[99, 875]),
+            [174, 231, 486, 91, 513]
+        );
+
+        let winners = winning_ticket_indices([255; 32], 10, 10, &[7, 3]);
+        assert_eq!(winners, [1, 4, 5, 9, 8, 0, 2, 6]);
+    }
+
+    #[test]
+    fn eligible_tickets_counts_each_excluded_ticket_once() {
+        assert_eq!(eligible_tickets(5, &[]), 5);
+        assert_eq!(eligible_tickets(5, &[0, 1, 0, 1]), 3);
+        assert_eq!(eligible_tickets(5, &[4, 4, 9]), 4);
+        assert_eq!(eligible_tickets(2, &[0, 1, 1]), 0);
+    }
+
+    #[test]
+    fn winning_ticket_indices_redraws_with_forfeited_winners_listed_twice() {
+        // a re-draw excludes the current winners and the forfeited tickets, and an
+        // unclaimed winner is in both lists. 2 tickets, 1 unclaimed: ticket 0 is left
+        assert_eq!(winning_ticket_indices([3; 32], 2, 1, &[1, 1]), [0]);
+
+        // unclaimed <= eligible < 2 * unclaimed: 2 unclaimed shares, 3 eligible tickets
+        let excluded = [0, 1, 0, 1];
+        let winners = winning_ticket_indices([42; 32], 5, 2, &excluded);
+
+        assert_eq!(winners.len(), 2);
+        for winner in &winners {
+            assert!([2, 3, 4].contains(winner));
+        }
+        assert_ne!(winners[0], winners[1]);
+
+        // exactly as many eligible tickets as unclaimed shares
+        let mut winners = winning_ticket_indices([9; 32], 4, 2, &[0, 1, 1, 0]);
+        winners.sort_unstable();
+        assert_eq!(winners, [2, 3]);
+    }
+
+    #[test]
+    fn winning_ticket_indices_are_distinct_and_in_bounds() {
+        let excluded = [0, 2, 4, 6, 8];
+        let winners = winning_ticket_indices([42; 32], 20, 15, &excluded);
+
+        assert_eq!(winners.len(), 15);
+        for (i, winner) in winners.iter().enumerate() {
+            assert!(*winner < 20);
+            assert!(!excluded.contains(winner));
+            assert!(!winners[i + 1..].contains(winner));
+        }
+    }
+
+    #[test]
+    fn winning_ticket_indices_clamps_to_eligible_tickets() {
+        // excluded tickets past the total don't count against it
+        assert_eq!(winning_ticket_indices([1; 32], 5, 10, &[1, 3, 9]).len(), 3);
+        assert!(winning_ticket_indices([1; 32], 2, 3, &[0, 1]).is_empty());
+    }
+
+    #[test]
+    fn find_slot_hash_finds_target_slot() {
+        let data = slot_hashes(&[(103, 3), (102, 2), (101, 1), (100, 0)]);
+
+        assert!(matches!(
+            find_slot_hash(&data, 102),
+            SlotHashLookup::Found(102, hash) if hash == [2; 32]
+        ));
+        // the oldest entry settles its own slot, it has not expired
+        assert!(matches!(
+            find_slot_hash(&data, 100),
+            SlotHashLookup::Found(100, hash) if hash == [0; 32]
+        ));
+    }
+
+    #[test]
+    fn find_slot_hash_uses_next_slot_after_a_skip() {
+        let data = slot_hashes(&[(105, 5), (103, 3), (100, 0)]);
+
+        assert!(matches!(
+            find_slot_hash(&data, 101),
+            SlotHashLookup::Found(103, hash) if hash == [3; 32]
+        ));
+    }
+
+    #[test]
+    fn find_slot_hash_waits_for_future_slots() {
+        let data = slot_hashes(&[(103, 3), (102, 2)]);
+
+        assert!(matches!(
+            find_slot_hash(&data, 104),
+            SlotHashLookup::Pending
+        ));
+        assert!(matches!(find_slot_hash(&[], 104), SlotHashLookup::Pending));
+    }
+
+    #[test]
+    fn find_slot_hash_expires_old_targets() {
+        let data = slot_hashes(&[(103, 3), (102, 2)]);
+
+        assert!(matches!(
+            find_slot_hash(&data, 101),
+            SlotHashLookup::Expired
+        ));
+    }
 }
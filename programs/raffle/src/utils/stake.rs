@@ -0,0 +1,173 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    state::{EntryType, Raffle, RaffleState, StakeRecord},
+    RaffleError, STAKE_WITHDRAWAL_GRACE_PERIOD,
+};
+
+pub fn assert_stake_withdrawable(
+    raffle: &Raffle,
+    stake_record: &StakeRecord,
+    current_time: i64,
+) -> Result<()> {
+    let minimum_period = match raffle.entry_type {
+        EntryType::Stake { minimum_period } => minimum_period,
+        _ => return err!(RaffleError::InvalidInstruction),
+    };
+
+    // nothing will be drawn, stakes can leave straight away
+    if matches!(
+        raffle.state,
+        RaffleState::Cancelled | RaffleState::Refunding
+    ) {
+        return Ok(());
+    }
+
+    // a raffle that was never drawn can't hold stakes forever
+    let grace_period_ends_at = raffle
+        .end_time
+        .checked_add(STAKE_WITHDRAWAL_GRACE_PERIOD)
+        .ok_or(RaffleError::ProgramAddError)?;
+
+    if !raffle.is_drawn() && current_time >= grace_period_ends_at {
+        return Ok(());
+    }
+
+    require!(raffle.is_drawn(), RaffleError::NotDrawn);
+
+    let unlocks_at = stake_record
+        .staked_at
+        .checked_add(minimum_period)
+        .ok_or(RaffleError::ProgramAddError)?;
+
+    require_gte!(current_time, unlocks_at, RaffleError::StakeLocked);
+
+    if raffle.claimed {
+        return Ok(());
+    }
+
+    // winning stakes stay in escrow until that winner's prize has been claimed
+    require!(
+        !raffle
+            .winners
+            .iter()
+            .any(|w| !w.claimed && w.entrant == stake_record.entrant),
+        RaffleError::WinnerStakeLocked
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{PaymentType, PrizeType, RandomnessSource, UnclaimedPrize, Winner};
+
+    /// a drawn stake raffle that ended at 2,000, with a day's minimum staking period
+    fn drawn_raffle(winner: Pubkey) -> Raffle {
+        let mut raffle = Raffle::init(
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            PrizeType::Nft,
+            EntryType::Stake {
+                minimum_period: 86_400,
+            },
+            PaymentType::Token {
+                token_mint: Pubkey::new_unique(),
+                ticket_price: 1,
+            },
+            Pubkey::new_unique(),
+            None,
+            1_000,
+            2_000,
+            None,
+            10_000,
+            None,
+            None,
+            1,
+            RandomnessSource::SwitchboardService,
+            None,
+            UnclaimedPrize::Reclaim,
+            255,
+        )
+        .unwrap();
+        raffle.state = RaffleState::Drawn;
+        raffle.winners = vec![Winner {
+            ticket_index: 0,
+            entrant: winner,
+            claimed: false,
+        }];
+        raffle
+    }
+
+    fn stake(entrant: Pubkey, staked_at: i64) -> StakeRecord {
+        let mut stake_record =
+            StakeRecord::init(Pubkey::new_unique(), entrant, Pubkey::new_unique(), 255);
+        stake_record.amount = 100;
+        stake_record.staked_at = staked_at;
+        stake_record
+    }
+
+    #[test]
+    fn stakes_unlock_after_the_draw_and_minimum_period() {
+        let winner = Pubkey::new_unique();
+        let mut raffle = drawn_raffle(winner);
+        let loser = stake(Pubkey::new_unique(), 1_500);
+
+        assert_eq!(
+            assert_stake_withdrawable(&raffle, &loser, 87_899).unwrap_err(),
+            RaffleError::StakeLocked.into()
+        );
+        assert_stake_withdrawable(&raffle, &loser, 87_900).unwrap();
+
+        raffle.state = RaffleState::Live;
+        assert_eq!(
+            assert_stake_withdrawable(&raffle, &loser, 87_900).unwrap_err(),
+            RaffleError::NotDrawn.into()
+        );
+    }
+
+    #[test]
+    fn winning_stakes_wait_for_the_prize_claim() {
+        let winner = Pubkey::new_unique();
+        let mut raffle = drawn_raffle(winner);
+        let winning_stake = stake(winner, 1_500);
+
+        assert_eq!(
+            assert_stake_withdrawable(&raffle, &winning_stake, 90_000).unwrap_err(),
+            RaffleError::WinnerStakeLocked.into()
+        );
+
+        raffle.winners[0].claimed = true;
+        assert_stake_withdrawable(&raffle, &winning_stake, 90_000).unwrap();
+    }
+
+    #[test]
+    fn stakes_leave_raffles_that_wont_be_drawn() {
+        let mut raffle = drawn_raffle(Pubkey::new_unique());
+        let stake_record = stake(Pubkey::new_unique(), 1_500);
+
+        for state in [RaffleState::Cancelled, RaffleState::Refunding] {
+            raffle.state = state;
+            assert_stake_withdrawable(&raffle, &stake_record, 1_600).unwrap();
+        }
+
+        // never drawn, so free once the grace period after end_time passes
+        raffle.state = RaffleState::Live;
+        assert_eq!(
+            assert_stake_withdrawable(
+                &raffle,
+                &stake_record,
+                2_000 + STAKE_WITHDRAWAL_GRACE_PERIOD - 1
+            )
+            .unwrap_err(),
+            RaffleError::NotDrawn.into()
+        );
+        assert_stake_withdrawable(
+            &raffle,
+            &stake_record,
+            2_000 + STAKE_WITHDRAWAL_GRACE_PERIOD,
+        )
+        .unwrap();
+    }
+}
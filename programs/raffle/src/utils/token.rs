@@ -0,0 +1,93 @@
+use anchor_lang::{prelude::*, solana_program::program::invoke};
+use anchor_spl::{
+    token_2022::spl_token_2022::{
+        extension::{
+            transfer_fee::{
+                instruction::harvest_withheld_tokens_to_mint, TransferFeeAmount, TransferFeeConfig,
+            },
+            BaseStateWithExtensions, StateWithExtensions,
+        },
+        onchain::invoke_transfer_checked,
+        state::{Account as SplAccount, Mint as SplMint},
+    },
+    token_interface::TransferChecked,
+};
+
+use crate::RaffleError;
+
+/// Performs a `transfer_checked` against either token program. Any transfer hook accounts
+/// for the mint are resolved from the context's remaining accounts, so a superset can be passed.
+pub fn transfer_checked_with_hooks<'info>(
+    ctx: CpiContext<'_, '_, '_, 'info, TransferChecked<'info>>,
+    amount: u64,
+    decimals: u8,
+) -> Result<()> {
+    invoke_transfer_checked(
+        ctx.program.key,
+        ctx.accounts.from,
+        ctx.accounts.mint,
+        ctx.accounts.to,
+        ctx.accounts.authority,
+        &ctx.remaining_accounts,
+        amount,
+        decimals,
+        ctx.signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+/// Splits the last `count` accounts off, for when a second mint's transfer hook accounts
+/// follow accounts the instruction reads by position, like a cNFT proof
+pub fn split_hook_accounts<'a, 'info>(
+    accounts: &'a [AccountInfo<'info>],
+    count: u8,
+) -> Result<(&'a [AccountInfo<'info>], &'a [AccountInfo<'info>])> {
+    let split = accounts
+        .len()
+        .checked_sub(count as usize)
+        .ok_or(ErrorCode::AccountNotEnoughKeys)?;
+
+    Ok(accounts.split_at(split))
+}
+
+/// Returns the fee that will be withheld when transferring `amount` of `mint` in the current epoch.
+/// Mints without the transfer fee extension always return 0.
+pub fn get_transfer_fee(mint: &AccountInfo, amount: u64) -> Result<u64> {
+    let mint_data = mint.try_borrow_data()?;
+    let mint = StateWithExtensions::<SplMint>::unpack(&mint_data)?;
+
+    match mint.get_extension::<TransferFeeConfig>() {
+        Ok(transfer_fee_config) => Ok(transfer_fee_config
+            .calculate_epoch_fee(Clock::get()?.epoch, amount)
+            .ok_or(RaffleError::ProgramMulError)?),
+        Err(_) => Ok(0),
+    }
+}
+
+/// Token-2022 refuses to close an account holding withheld transfer fees, so these are
+/// harvested to the mint first. This is permissionless and a no-op for other accounts.
+pub fn harvest_withheld_fees<'info>(
+    token_program: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    token_account: &AccountInfo<'info>,
+) -> Result<()> {
+    let has_withheld_fees = {
+        let account_data = token_account.try_borrow_data()?;
+        let account = StateWithExtensions::<SplAccount>::unpack(&account_data)?;
+
+        match account.get_extension::<TransferFeeAmount>() {
+            Ok(transfer_fee_amount) => u64::from(transfer_fee_amount.withheld_amount) > 0,
+            Err(_) => false,
+        }
+    };
+
+    if !has_withheld_fees {
+        return Ok(());
+    }
+
+    invoke(
+        &harvest_withheld_tokens_to_mint(token_program.key, mint.key, &[token_account.key])?,
+        &[mint.clone(), token_account.clone(), token_program.clone()],
+    )
+    .map_err(Into::into)
+}
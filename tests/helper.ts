@@ -3,7 +3,7 @@ import { Keypair, KeypairSigner, generateSigner, sol } from "@metaplex-foundatio
 import { Raffle } from "../target/types/raffle"
 import { umi } from "./helpers/umi"
 import { toWeb3JsKeypair } from "@metaplex-foundation/umi-web3js-adapters"
-import { findProgramConfigPda, findProgramDataAddress } from "./helpers/pdas"
+import { findEventAuthorityPda, findProgramConfigPda, findProgramDataAddress } from "./helpers/pdas"
 import { RandomnessService } from "@switchboard-xyz/solana-randomness-service"
 
 const RAFFLE_FEE = 0.01 * anchor.web3.LAMPORTS_PER_SOL
@@ -16,9 +16,10 @@ anchor.setProvider(provider)
 before(async () => {
   randomnessService = await RandomnessService.fromProvider(provider)
   await adminProgram.methods
-    .initProgramConfig(new anchor.BN(RAFFLE_FEE.toString()), PROCEEDS_PERCENTAGE)
+    .initProgramConfig(new anchor.BN(RAFFLE_FEE.toString()), PROCEEDS_PERCENTAGE, null)
     .accounts({
       programConfig: findProgramConfigPda(),
+      eventAuthority: findEventAuthorityPda(),
       program: adminProgram.programId,
       programData: findProgramDataAddress(),
     })
@@ -0,0 +1,128 @@
+import {
+  MetadataArgsArgs,
+  TokenProgramVersion,
+  TokenStandard,
+  createTree,
+  fetchMerkleTree,
+  findLeafAssetIdPda,
+  getCurrentRoot,
+  getMerkleProof,
+  getMerkleRoot,
+  hashLeaf,
+  hashMetadataCreators,
+  hashMetadataData,
+  mintToCollectionV1,
+  mintV1,
+} from "@metaplex-foundation/mpl-bubblegum"
+import { PublicKey, Umi, generateSigner, none, publicKey, some } from "@metaplex-foundation/umi"
+
+const MAX_DEPTH = 5
+const EMPTY_LEAF = publicKey(new Uint8Array(32))
+
+// tests have no DAS indexer, so leaves are tracked here to build proofs
+export type CnftTree = {
+  merkleTree: PublicKey
+  leaves: PublicKey[]
+}
+
+export type Cnft = {
+  merkleTree: PublicKey
+  assetId: PublicKey
+  leafIndex: number
+  owner: PublicKey
+  metadata: MetadataArgsArgs
+}
+
+export async function createCnftTree(umi: Umi): Promise<CnftTree> {
+  const merkleTree = generateSigner(umi)
+  await (
+    await createTree(umi, {
+      merkleTree,
+      maxDepth: MAX_DEPTH,
+      maxBufferSize: 8,
+    })
+  ).sendAndConfirm(umi)
+
+  return { merkleTree: merkleTree.publicKey, leaves: [] }
+}
+
+export async function createCnft(umi: Umi, tree: CnftTree, owner: PublicKey, collection?: PublicKey): Promise<Cnft> {
+  const leafIndex = tree.leaves.length
+  const metadata: MetadataArgsArgs = {
+    name: "Test cNFT",
+    symbol: "",
+    uri: "",
+    sellerFeeBasisPoints: 0,
+    primarySaleHappened: false,
+    isMutable: true,
+    editionNonce: none(),
+    tokenStandard: some(TokenStandard.NonFungible),
+    collection: collection ? some({ key: collection, verified: false }) : none(),
+    uses: none(),
+    tokenProgramVersion: TokenProgramVersion.Original,
+    creators: [],
+  }
+
+  if (collection) {
+    await mintToCollectionV1(umi, {
+      leafOwner: owner,
+      merkleTree: tree.merkleTree,
+      collectionMint: collection,
+      metadata,
+    }).sendAndConfirm(umi)
+    // bubblegum verifies the collection as it mints
+    metadata.collection = some({ key: collection, verified: true })
+  } else {
+    await mintV1(umi, {
+      leafOwner: owner,
+      merkleTree: tree.merkleTree,
+      metadata,
+    }).sendAndConfirm(umi)
+  }
+
+  const cnft: Cnft = {
+    merkleTree: tree.merkleTree,
+    assetId: findLeafAssetIdPda(umi, { merkleTree: tree.merkleTree, leafIndex })[0],
+    leafIndex,
+    owner,
+    metadata,
+  }
+  tree.leaves.push(leafHash(umi, cnft))
+  return cnft
+}
+
+function leafHash(umi: Umi, cnft: Cnft) {
+  return publicKey(
+    hashLeaf(umi, {
+      merkleTree: cnft.merkleTree,
+      owner: cnft.owner,
+      leafIndex: cnft.leafIndex,
+      metadata: cnft.metadata,
+    })
+  )
+}
+
+// call once a transfer lands, a burnt leaf is emptied
+export function setCnftOwner(umi: Umi, tree: CnftTree, cnft: Cnft, owner: PublicKey | null) {
+  if (owner) {
+    cnft.owner = owner
+    tree.leaves[cnft.leafIndex] = leafHash(umi, cnft)
+  } else {
+    tree.leaves[cnft.leafIndex] = EMPTY_LEAF
+  }
+}
+
+export function getCnftProof(tree: CnftTree, cnft: Cnft) {
+  return {
+    root: getMerkleRoot(tree.leaves, MAX_DEPTH),
+    proof: getMerkleProof(tree.leaves, MAX_DEPTH, tree.leaves[cnft.leafIndex], cnft.leafIndex),
+    dataHash: hashMetadataData(cnft.metadata),
+    creatorHash: hashMetadataCreators(cnft.metadata.creators),
+  }
+}
+
+// the on-chain root only matches when every tracked leaf, and so every owner, is current
+export async function isCnftTreeInSync(umi: Umi, tree: CnftTree) {
+  const account = await fetchMerkleTree(umi, tree.merkleTree)
+  return getCurrentRoot(account.tree) === getMerkleRoot(tree.leaves, MAX_DEPTH)
+}
@@ -0,0 +1,63 @@
+import * as anchor from "@coral-xyz/anchor"
+import { PublicKey } from "@metaplex-foundation/umi"
+import { fromWeb3JsPublicKey, toWeb3JsPublicKey } from "@metaplex-foundation/umi-web3js-adapters"
+import {
+  ExtensionType,
+  TOKEN_2022_PROGRAM_ID,
+  createAssociatedTokenAccountIdempotentInstruction,
+  createInitializeMintInstruction,
+  createInitializeTransferFeeConfigInstruction,
+  createMintToInstruction,
+  getAssociatedTokenAddressSync,
+  getMintLen,
+} from "@solana/spl-token"
+import { adminProgram } from "../helper"
+
+// a Token-2022 mint withholding transferFeeBp of every transfer, minted to each owner
+export async function createTransferFeeToken(
+  transferFeeBp: number,
+  amount: bigint,
+  decimals: number,
+  owners: PublicKey[]
+): Promise<PublicKey> {
+  const provider = adminProgram.provider as anchor.AnchorProvider
+  const authority = provider.wallet.publicKey
+  const mint = anchor.web3.Keypair.generate()
+  const mintLen = getMintLen([ExtensionType.TransferFeeConfig])
+
+  const tx = new anchor.web3.Transaction().add(
+    anchor.web3.SystemProgram.createAccount({
+      fromPubkey: authority,
+      newAccountPubkey: mint.publicKey,
+      space: mintLen,
+      lamports: await provider.connection.getMinimumBalanceForRentExemption(mintLen),
+      programId: TOKEN_2022_PROGRAM_ID,
+    }),
+    createInitializeTransferFeeConfigInstruction(
+      mint.publicKey,
+      authority,
+      authority,
+      transferFeeBp,
+      BigInt("18446744073709551615"),
+      TOKEN_2022_PROGRAM_ID
+    ),
+    createInitializeMintInstruction(mint.publicKey, decimals, authority, null, TOKEN_2022_PROGRAM_ID)
+  )
+
+  for (const owner of owners) {
+    const token = getAssociatedTokenAddressSync(mint.publicKey, toWeb3JsPublicKey(owner), false, TOKEN_2022_PROGRAM_ID)
+    tx.add(
+      createAssociatedTokenAccountIdempotentInstruction(
+        authority,
+        token,
+        toWeb3JsPublicKey(owner),
+        mint.publicKey,
+        TOKEN_2022_PROGRAM_ID
+      ),
+      createMintToInstruction(mint.publicKey, token, authority, amount, [], TOKEN_2022_PROGRAM_ID)
+    )
+  }
+
+  await provider.sendAndConfirm(tx, [mint])
+  return fromWeb3JsPublicKey(mint.publicKey)
+}
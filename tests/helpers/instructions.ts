@@ -1,11 +1,26 @@
 import * as anchor from "@coral-xyz/anchor"
-import { KeypairSigner, PublicKey, generateSigner, publicKey, unwrapOptionRecursively } from "@metaplex-foundation/umi"
+import {
+  KeypairSigner,
+  PublicKey,
+  generateSigner,
+  publicKey,
+  publicKeyBytes,
+  unwrapOptionRecursively,
+} from "@metaplex-foundation/umi"
 import { adminProgram, createNewUser, programPaidBy } from "../helper"
 import {
+  findEntrantTallyPda,
+  findEventAuthorityPda,
+  findFeeOverridePda,
+  findJackpotSeriesPda,
+  findNftEntryPda,
+  findPrizeBundlePda,
+  findPrizeVaultPda,
   findProgramConfigPda,
   findProgramDataAddress,
   findRafflePda,
   findRafflerPda,
+  findStakeRecordPda,
   getTokenAccount,
   getTokenRecordPda,
   nativeMint,
@@ -21,10 +36,18 @@ import {
   findMetadataPda,
 } from "@metaplex-foundation/mpl-token-metadata"
 import { MPL_TOKEN_AUTH_RULES_PROGRAM_ID } from "@metaplex-foundation/mpl-token-auth-rules"
+import {
+  MPL_BUBBLEGUM_PROGRAM_ID,
+  SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+  SPL_NOOP_PROGRAM_ID,
+  findTreeConfigPda,
+  getMetadataArgsSerializer,
+} from "@metaplex-foundation/mpl-bubblegum"
 import { RandomnessService, SimpleRandomnessV1SettledEvent } from "@switchboard-xyz/solana-randomness-service"
 import { assert } from "chai"
-import { FEES_WALLET, expandRandomness, getEntrantsArray } from "./utils"
+import { FEES_WALLET, getTokenProgram } from "./utils"
 import { Raffle } from "../../target/types/raffle"
+import { Cnft, CnftTree, getCnftProof, setCnftOwner } from "./create-cnft"
 
 export async function createRaffloor(
   name: string,
@@ -38,6 +61,8 @@ export async function createRaffloor(
   await program.methods
     .init(name, slug, null, null)
     .accounts({
+      eventAuthority: findEventAuthorityPda(),
+      program: program.programId,
       programConfig: findProgramConfigPda(),
       raffler,
       treasury: treasury || null,
@@ -50,7 +75,14 @@ export async function createRaffloor(
 
 type EntryType = { spend: {} } | { burn: { witholdBurnProceeds: boolean } } | { stake: { minimumPeriod: anchor.BN } }
 
-type PrizeType = { nft: {} } | { token: { amount: anchor.BN } }
+type PrizeType =
+  | { nft: {} }
+  | { token: { amount: anchor.BN } }
+  | { sol: { lamports: anchor.BN } }
+  | { pot: { winnerShare: number } }
+  | { cnft: {} }
+
+type UnclaimedPrize = { redraw: {} } | { reclaim: {} }
 
 export async function createRaffle({
   prizeType,
@@ -67,6 +99,16 @@ export async function createRaffle({
   entryCollectionMint = null,
   gatedCollection = null,
   maxEntriesPct = null,
+  numWinners = null,
+  maxEntrantTickets = null,
+  isCompressed = false,
+  minTickets = null,
+  claimWindow = null,
+  unclaimedPrize = null,
+  jackpotSeries = null,
+  consolation = null,
+  prizeCnft = null,
+  endTimeExtension = null,
 }: {
   prizeType: PrizeType
   authority: KeypairSigner
@@ -76,17 +118,28 @@ export async function createRaffle({
   numTickets: number | null
   ticketPrice: BigInt
   duration: number
-  prize: PublicKey
+  prize?: PublicKey | null
   startTime?: number | null
   tokenMint?: PublicKey | null
   entryCollectionMint?: PublicKey | null
   gatedCollection?: PublicKey | null
   witholdBurnProceeds?: boolean
   maxEntriesPct?: number | null
+  numWinners?: number | null
+  maxEntrantTickets?: number | null
+  isCompressed?: boolean
+  minTickets?: number | null
+  claimWindow?: number | null
+  unclaimedPrize?: UnclaimedPrize | null
+  jackpotSeries?: PublicKey | null
+  consolation?: { mint: PublicKey; amount: bigint } | null
+  prizeCnft?: { tree: CnftTree; cnft: Cnft } | null
+  endTimeExtension?: { window: number; extension: number; maxExtension: number } | null
 }) {
   const program = programPaidBy(authority)
   const rafflerAcc = await program.account.raffler.fetch(raffler)
-  const dataLen = 8 + 4 + 4 + numTickets * 32
+  // ticket runs are added as tickets are bought
+  const dataLen = 8 + 4 + 4 + 4
   const rent = await umi.rpc.getRent(dataLen)
   await createAccount(umi, {
     newAccount: entrants,
@@ -98,12 +151,19 @@ export async function createRaffle({
   const raffle = findRafflePda(entrants.publicKey)
 
   const treasury = fromWeb3JsPublicKey(rafflerAcc.treasury)
-  const prizeAcc = await fetchDigitalAsset(umi, prize)
+  // a linked staker waives the raffle fee, otherwise any override replaces it
+  const feeOverride = findFeeOverridePda(raffler)
+  const hasFeeOverride = await umi.rpc.accountExists(feeOverride)
+  // SOL and pot prizes have no mint to escrow
+  const prizeAcc = "nft" in prizeType ? await fetchDigitalAsset(umi, prize) : null
 
-  const isPfnt = unwrapOptionRecursively(prizeAcc.metadata.tokenStandard) === TokenStandard.ProgrammableNonFungible
+  const isPfnt =
+    !!prizeAcc &&
+    unwrapOptionRecursively(prizeAcc.metadata.tokenStandard) === TokenStandard.ProgrammableNonFungible
   const payer = fromWeb3JsPublicKey(program.provider.publicKey)
 
   tokenMint = (entryType as any).burn?.witholdBurnProceeds ? nativeMint : tokenMint
+  const tokenProgram = tokenMint ? await getTokenProgram(tokenMint) : null
 
   const remainingAccounts: anchor.web3.AccountMeta[] = []
 
@@ -115,6 +175,10 @@ export async function createRaffle({
     })
   }
 
+  if (prizeCnft) {
+    remainingAccounts.push(...cnftPrizeAccounts(prizeCnft.tree, prizeCnft.cnft))
+  }
+
   if ("nft" in prizeType) {
     remainingAccounts.push(
       {
@@ -163,7 +227,7 @@ export async function createRaffle({
     }
   }
 
-  await program.methods
+  const sig = await program.methods
     .initRaffle(
       prizeType,
       numTickets,
@@ -172,27 +236,61 @@ export async function createRaffle({
       startTime ? new anchor.BN(startTime) : null,
       new anchor.BN(duration),
       !!gatedCollection,
-      maxEntriesPct
+      maxEntriesPct,
+      numWinners,
+      maxEntrantTickets,
+      isCompressed,
+      prizeCnft ? cnftArgs(prizeCnft.tree, prizeCnft.cnft) : null,
+      minTickets,
+      null,
+      claimWindow ? new anchor.BN(claimWindow) : null,
+      unclaimedPrize,
+      endTimeExtension
+        ? {
+            window: new anchor.BN(endTimeExtension.window),
+            extension: new anchor.BN(endTimeExtension.extension),
+            maxExtension: new anchor.BN(endTimeExtension.maxExtension),
+          }
+        : null,
+      consolation ? { amount: new anchor.BN(consolation.amount.toString()), hookAccounts: 0 } : null
     )
     .accounts({
+      eventAuthority: findEventAuthorityPda(),
+      program: program.programId,
+      programConfig: findProgramConfigPda(),
       raffler,
+      feeOverride: hasFeeOverride ? feeOverride : null,
+      staker: rafflerAcc.staker,
       raffle,
       entrants: entrants.publicKey,
       tokenMint,
       entryCollectionMint,
-      tokenVault: tokenMint ? getTokenAccount(tokenMint, raffle) : null,
-      prize,
+      tokenVault: tokenMint ? getTokenAccount(tokenMint, raffle, tokenProgram) : null,
+      prize: prize || null,
       treasury,
-      feesWallet: tokenMint ? FEES_WALLET : null,
-      feesWalletToken: tokenMint ? getTokenAccount(tokenMint, FEES_WALLET) : null,
-      treasuryTokenAccount: tokenMint ? getTokenAccount(tokenMint, treasury) : null,
-      prizeToken: getTokenAccount(prize, payer),
-      prizeCustody: getTokenAccount(prize, raffle),
+      feesWallet: FEES_WALLET,
+      feesWalletToken: tokenMint ? getTokenAccount(tokenMint, FEES_WALLET, tokenProgram) : null,
+      treasuryTokenAccount: tokenMint ? getTokenAccount(tokenMint, treasury, tokenProgram) : null,
+      prizeToken: prize ? getTokenAccount(prize, payer) : null,
+      prizeCustody: prize ? getTokenAccount(prize, raffle) : null,
+      prizeVault: "sol" in prizeType ? findPrizeVaultPda(raffle) : null,
+      jackpotSeries,
+      consolationMint: consolation?.mint || null,
+      consolationToken: consolation ? getTokenAccount(consolation.mint, payer) : null,
+      consolationCustody: consolation ? getTokenAccount(consolation.mint, raffle) : null,
+      consolationTokenProgram: consolation ? anchor.utils.token.TOKEN_PROGRAM_ID : null,
+      tokenProgram: tokenProgram || anchor.utils.token.TOKEN_PROGRAM_ID,
+      prizeTokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
       metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
     })
     .remainingAccounts(remainingAccounts)
     .preInstructions([anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({ units: 500_000 })])
     .rpc()
+
+  if (prizeCnft) {
+    setCnftOwner(umi, prizeCnft.tree, prizeCnft.cnft, raffle)
+  }
+  return sig
 }
 
 export async function toggleRaffler(
@@ -204,6 +302,7 @@ export async function toggleRaffler(
   return await program.methods
     .toggleActive(active)
     .accounts({
+      eventAuthority: findEventAuthorityPda(),
       raffler,
       program: program.programId,
       programData: findProgramDataAddress(),
@@ -211,6 +310,38 @@ export async function toggleRaffler(
     .rpc()
 }
 
+export async function setFeeOverride(
+  user: KeypairSigner,
+  raffler: PublicKey,
+  raffleFee: bigint,
+  program: anchor.Program<Raffle> = programPaidBy(user)
+) {
+  return await program.methods
+    .setFeeOverride(new anchor.BN(raffleFee.toString()))
+    .accounts({
+      raffler,
+      feeOverride: findFeeOverridePda(raffler),
+      program: program.programId,
+      programData: findProgramDataAddress(),
+    })
+    .rpc()
+}
+
+export async function deleteFeeOverride(
+  user: KeypairSigner,
+  raffler: PublicKey,
+  program: anchor.Program<Raffle> = programPaidBy(user)
+) {
+  return await program.methods
+    .deleteFeeOverride()
+    .accounts({
+      feeOverride: findFeeOverridePda(raffler),
+      program: program.programId,
+      programData: findProgramDataAddress(),
+    })
+    .rpc()
+}
+
 export async function buyTicketsToken(
   user: KeypairSigner,
   raffle: PublicKey,
@@ -219,18 +350,24 @@ export async function buyTicketsToken(
 ) {
   const program = programPaidBy(user)
   const raffleAcc = await program.account.raffle.fetch(raffle)
-  const tokenMint = fromWeb3JsPublicKey(raffleAcc.paymentType.token.tokenMint)
+  // NFT entry raffles have no token accounts, the program rejects the purchase
+  const tokenMint = raffleAcc.paymentType.token ? fromWeb3JsPublicKey(raffleAcc.paymentType.token.tokenMint) : null
+  const tokenProgram = tokenMint ? await getTokenProgram(tokenMint) : null
   const entrant = fromWeb3JsPublicKey(program.provider.publicKey)
 
   return await program.methods
     .buyTicketsToken(amount)
     .accounts({
+      eventAuthority: findEventAuthorityPda(),
+      program: program.programId,
       raffler: raffleAcc.raffler,
       raffle,
       entrants: raffleAcc.entrants,
+      entrantTally: findEntrantTallyPda(raffle, entrant),
       tokenMint,
-      tokenSource: getTokenAccount(tokenMint, entrant),
-      tokenDestination: getTokenAccount(tokenMint, raffle),
+      tokenSource: tokenMint ? getTokenAccount(tokenMint, entrant, tokenProgram) : null,
+      tokenDestination: tokenMint ? getTokenAccount(tokenMint, raffle, tokenProgram) : null,
+      tokenProgram: tokenProgram || anchor.utils.token.TOKEN_PROGRAM_ID,
       gatedNftMint,
       gatedNftToken: gatedNftMint ? getTokenAccount(gatedNftMint, user.publicKey) : null,
       gatedNftMetadata: gatedNftMint ? findMetadataPda(umi, { mint: gatedNftMint })[0] : null,
@@ -239,6 +376,51 @@ export async function buyTicketsToken(
     .rpc()
 }
 
+export async function stakeToken(user: KeypairSigner, raffle: PublicKey, amount: number) {
+  const program = programPaidBy(user)
+  const raffleAcc = await program.account.raffle.fetch(raffle)
+  const tokenMint = fromWeb3JsPublicKey(raffleAcc.paymentType.token.tokenMint)
+
+  return await program.methods
+    .stakeToken(amount)
+    .accounts({
+      eventAuthority: findEventAuthorityPda(),
+      program: program.programId,
+      raffle,
+      raffler: raffleAcc.raffler,
+      entrants: raffleAcc.entrants,
+      entrantTally: findEntrantTallyPda(raffle, user.publicKey),
+      stakeRecord: findStakeRecordPda(raffle, user.publicKey, tokenMint),
+      tokenMint,
+      tokenSource: getTokenAccount(tokenMint, user.publicKey),
+      tokenVault: getTokenAccount(tokenMint, raffle),
+      gatedNftMint: null,
+      gatedNftMetadata: null,
+      gatedNftToken: null,
+      tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+    })
+    .preInstructions([anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({ units: 1_000_000 })])
+    .rpc()
+}
+
+export async function unstakeToken(user: KeypairSigner, raffle: PublicKey, tokenMint: PublicKey) {
+  const program = programPaidBy(user)
+
+  return await program.methods
+    .unstakeToken()
+    .accounts({
+      eventAuthority: findEventAuthorityPda(),
+      program: program.programId,
+      raffle,
+      stakeRecord: findStakeRecordPda(raffle, user.publicKey, tokenMint),
+      tokenMint,
+      tokenVault: getTokenAccount(tokenMint, raffle),
+      tokenDestination: getTokenAccount(tokenMint, user.publicKey),
+      tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+    })
+    .rpc()
+}
+
 export async function settleRaffle(
   randomnessService: RandomnessService,
   raffle: PublicKey,
@@ -257,6 +439,8 @@ export async function settleRaffle(
   await adminProgram.methods
     .drawWinner(uri, new anchor.BN(1000))
     .accounts({
+      eventAuthority: findEventAuthorityPda(),
+      program: adminProgram.programId,
       raffle,
       entrants: raffleAcc.entrants,
       randomnessService: randomnessService.programId,
@@ -287,13 +471,19 @@ export async function settleRaffle(
     )
     assert.equal(settledRandomnessEvent.isSuccess, true, "Request did not complete successfully")
   }
+
+  return fromWeb3JsPublicKey(requestKeypair.publicKey)
 }
 
-export async function claimPrize(user: KeypairSigner, raffle: PublicKey, ticketIndex?: number) {
+export async function claimPrize(
+  user: KeypairSigner,
+  raffle: PublicKey,
+  ticketIndex?: number,
+  prizeCnft?: { tree: CnftTree; cnft: Cnft }
+) {
   const program = programPaidBy(user)
   const raffleAcc = await program.account.raffle.fetch(raffle)
   const rafflerAcc = await program.account.raffler.fetch(raffleAcc.raffler)
-  const entrants = await program.account.entrants.fetch(raffleAcc.entrants)
   let proceedsMint = raffleAcc.paymentType.token?.tokenMint
     ? fromWeb3JsPublicKey(raffleAcc.paymentType.token.tokenMint)
     : null
@@ -301,18 +491,27 @@ export async function claimPrize(user: KeypairSigner, raffle: PublicKey, ticketI
   if (raffleAcc.paymentType.nft && raffleAcc.entryType.burn?.witholdBurnProceeds) {
     proceedsMint = nativeMint
   }
+  const proceedsTokenProgram = proceedsMint ? await getTokenProgram(proceedsMint) : null
 
-  const prizeDa = await fetchDigitalAsset(umi, fromWeb3JsPublicKey(raffleAcc.prize))
-  const isPnft = unwrapOptionRecursively(prizeDa.metadata.tokenStandard) === TokenStandard.ProgrammableNonFungible
+  const prizeDa = raffleAcc.prizeType.nft ? await fetchDigitalAsset(umi, fromWeb3JsPublicKey(raffleAcc.prize)) : null
+  const isPnft =
+    !!prizeDa && unwrapOptionRecursively(prizeDa.metadata.tokenStandard) === TokenStandard.ProgrammableNonFungible
+
+  // a SOL prize is paid from the prize vault, a pot from the proceeds, neither has a custody account.
+  // A cNFT prize is a leaf of its tree rather than a mint
+  const prizeMint = raffleAcc.prizeType.sol || raffleAcc.prizeType.cnft ? null : fromWeb3JsPublicKey(raffleAcc.prize)
+  const hasCustody = !!prizeMint && !raffleAcc.prizeType.pot
 
   let winner: PublicKey
   let winnerIndex = 0
-  if (raffleAcc.randomness) {
-    const winnerRand = expandRandomness(raffleAcc.randomness)
-    winnerIndex = winnerRand % entrants.total
-
-    const entrantsArray = await getEntrantsArray(fromWeb3JsPublicKey(raffleAcc.entrants))
-    winner = entrantsArray[winnerIndex]
+  if (raffleAcc.winners.length) {
+    // winners are recorded on the raffle when the draw settles
+    const claimed =
+      raffleAcc.winners.find((w) => w.ticketIndex === ticketIndex) ||
+      raffleAcc.winners.find((w) => w.entrant.toBase58() === user.publicKey) ||
+      raffleAcc.winners[0]
+    winnerIndex = claimed.ticketIndex
+    winner = fromWeb3JsPublicKey(claimed.entrant)
   } else {
     winner = user.publicKey
   }
@@ -321,6 +520,10 @@ export async function claimPrize(user: KeypairSigner, raffle: PublicKey, ticketI
 
   const remainingAccounts: anchor.web3.AccountMeta[] = []
 
+  if (prizeCnft) {
+    remainingAccounts.push(...cnftPrizeAccounts(prizeCnft.tree, prizeCnft.cnft))
+  }
+
   if (raffleAcc.prizeType.nft) {
     remainingAccounts.push(
       {
@@ -351,32 +554,43 @@ export async function claimPrize(user: KeypairSigner, raffle: PublicKey, ticketI
     )
   }
 
-  return await program.methods
-    .claimPrize(ticketIndex || winnerIndex)
+  const sig = await program.methods
+    .claimPrize(ticketIndex ?? winnerIndex, prizeCnft ? cnftArgs(prizeCnft.tree, prizeCnft.cnft) : null)
     .accounts({
+      eventAuthority: findEventAuthorityPda(),
+      program: program.programId,
       programConfig: findProgramConfigPda(),
       raffle,
       raffler: raffleAcc.raffler,
       proceedsMint,
       feesWallet: FEES_WALLET,
-      feesWalletToken: proceedsMint ? getTokenAccount(proceedsMint, FEES_WALLET) : null,
-      proceedsSource: proceedsMint ? getTokenAccount(proceedsMint, raffle) : null,
-      proceedsDestination: proceedsMint ? getTokenAccount(proceedsMint, treasury) : null,
+      feesWalletToken: proceedsMint ? getTokenAccount(proceedsMint, FEES_WALLET, proceedsTokenProgram) : null,
+      proceedsSource: proceedsMint ? getTokenAccount(proceedsMint, raffle, proceedsTokenProgram) : null,
+      proceedsDestination: proceedsMint ? getTokenAccount(proceedsMint, treasury, proceedsTokenProgram) : null,
       entrants: raffleAcc.entrants,
-      prize: prizeDa.publicKey,
+      prize: prizeMint,
       treasury,
-      prizeCustody: getTokenAccount(prizeDa.publicKey, raffle),
-      prizeDestination: getTokenAccount(prizeDa.publicKey, winner),
+      prizeCustody: hasCustody ? getTokenAccount(prizeMint, raffle) : null,
+      prizeDestination: prizeMint ? getTokenAccount(prizeMint, winner) : null,
+      prizeVault: raffleAcc.prizeType.sol ? findPrizeVaultPda(raffle) : null,
+      prizeBundle: raffleAcc.bundleItems ? findPrizeBundlePda(raffle) : null,
+      tokenProgram: proceedsTokenProgram || anchor.utils.token.TOKEN_PROGRAM_ID,
+      prizeTokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
       metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
       sysvarInstructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
       authority: rafflerAcc.authority,
       winner,
-      authRules: unwrapOptionRecursively(prizeDa.metadata.programmableConfig)?.ruleSet || null,
+      authRules: (prizeDa && unwrapOptionRecursively(prizeDa.metadata.programmableConfig)?.ruleSet) || null,
       authRulesProgram: isPnft ? MPL_TOKEN_AUTH_RULES_PROGRAM_ID : null,
     })
     .remainingAccounts(remainingAccounts)
     .preInstructions([anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({ units: 500_000 })])
     .rpc()
+
+  if (prizeCnft) {
+    setCnftOwner(umi, prizeCnft.tree, prizeCnft.cnft, winner)
+  }
+  return sig
 }
 
 export async function collectNft(authority: KeypairSigner, raffle: PublicKey, nftMint: PublicKey) {
@@ -384,11 +598,15 @@ export async function collectNft(authority: KeypairSigner, raffle: PublicKey, nf
   const raffleAcc = await program.account.raffle.fetch(raffle)
   const rafflerAcc = await program.account.raffler.fetch(raffleAcc.raffler)
   const nftDa = await fetchDigitalAsset(umi, nftMint)
-  const isPnft = unwrapOptionRecursively(nftDa.metadata.publicKey)
+  const isPnft = unwrapOptionRecursively(nftDa.metadata.tokenStandard) === TokenStandard.ProgrammableNonFungible
   const treasury = fromWeb3JsPublicKey(rafflerAcc.treasury)
+  const nftEntry = raffleAcc.minTickets !== null ? findNftEntryPda(raffle, nftMint) : null
+  const nftEntryAcc = nftEntry ? await program.account.nftEntry.fetch(nftEntry) : null
   await program.methods
     .collectNft()
     .accounts({
+      eventAuthority: findEventAuthorityPda(),
+      program: program.programId,
       raffle,
       raffler: raffleAcc.raffler,
       authority: rafflerAcc.authority,
@@ -396,6 +614,9 @@ export async function collectNft(authority: KeypairSigner, raffle: PublicKey, nf
       nftMint,
       nftSource: getTokenAccount(nftMint, raffle),
       nftDestination: getTokenAccount(nftMint, treasury),
+      nftEntry,
+      entrant: nftEntryAcc?.entrant || null,
+      prizeBundle: raffleAcc.bundleItems ? findPrizeBundlePda(raffle) : null,
       nftEdition: findMasterEditionPda(umi, { mint: nftMint })[0],
       nftMetadata: findMetadataPda(umi, { mint: nftMint })[0],
       sourceTokenRecord: isPnft ? getTokenRecordPda(nftMint, raffle) : null,
@@ -424,14 +645,18 @@ export async function buyTicketSendNft(
   return await program.methods
     .buyTicketSendNft()
     .accounts({
+      eventAuthority: findEventAuthorityPda(),
+      program: program.programId,
       raffler: raffleAcc.raffler,
       raffle,
       entrants: raffleAcc.entrants,
+      entrantTally: findEntrantTallyPda(raffle, user.publicKey),
       ownerTokenRecord: isPnft ? getTokenRecordPda(nftMint, user.publicKey) : null,
       destinationTokenRecord: isPnft ? getTokenRecordPda(nftMint, raffle) : null,
       nftMint,
       nftSource: getTokenAccount(nftMint, user.publicKey),
       nftDestination: getTokenAccount(nftMint, raffle),
+      nftEntry: raffleAcc.minTickets !== null ? findNftEntryPda(raffle, nftMint) : null,
       nftMetadata: nftDa.metadata.publicKey,
       nftEdition: nftDa.edition.publicKey,
       metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
@@ -465,9 +690,12 @@ export async function buyTicketBurnNft(
   return await program.methods
     .buyTicketBurnNft()
     .accounts({
+      eventAuthority: findEventAuthorityPda(),
+      program: program.programId,
       raffler: raffleAcc.raffler,
       raffle,
       entrants: raffleAcc.entrants,
+      entrantTally: findEntrantTallyPda(raffle, user.publicKey),
       ownerTokenRecord: isPnft ? getTokenRecordPda(nftMint, user.publicKey) : null,
       destinationTokenRecord: isPnft ? getTokenRecordPda(nftMint, raffle) : null,
       nftMint,
@@ -490,3 +718,329 @@ export async function buyTicketBurnNft(
     .preInstructions([anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({ units: 300_000 })])
     .rpc()
 }
+
+function cnftProofAccounts(proof: PublicKey[]): anchor.web3.AccountMeta[] {
+  return proof.map((node) => ({
+    pubkey: toWeb3JsPublicKey(node),
+    isWritable: false,
+    isSigner: false,
+  }))
+}
+
+// accounts for utils::cnft, in the order it reads them
+function cnftPrizeAccounts(tree: CnftTree, cnft: Cnft): anchor.web3.AccountMeta[] {
+  const { proof } = getCnftProof(tree, cnft)
+  return [
+    findTreeConfigPda(umi, { merkleTree: cnft.merkleTree })[0],
+    cnft.merkleTree,
+    MPL_BUBBLEGUM_PROGRAM_ID,
+    SPL_NOOP_PROGRAM_ID,
+    SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+  ]
+    .map((pubkey, i) => ({
+      pubkey: toWeb3JsPublicKey(pubkey),
+      isWritable: i < 2,
+      isSigner: false,
+    }))
+    .concat(cnftProofAccounts(proof))
+}
+
+function cnftArgs(tree: CnftTree, cnft: Cnft) {
+  const { root, dataHash, creatorHash } = getCnftProof(tree, cnft)
+  return {
+    root: Array.from(publicKeyBytes(root)),
+    dataHash: Array.from(dataHash),
+    creatorHash: Array.from(creatorHash),
+    nonce: new anchor.BN(cnft.leafIndex),
+    index: cnft.leafIndex,
+  }
+}
+
+export async function buyTicketCnft(
+  user: KeypairSigner,
+  raffle: PublicKey,
+  tree: CnftTree,
+  cnft: Cnft,
+  gatedNftMint: PublicKey | null = null
+) {
+  const program = programPaidBy(user)
+  const raffleAcc = await program.account.raffle.fetch(raffle)
+  const { root, proof } = getCnftProof(tree, cnft)
+
+  await program.methods
+    .buyTicketCnft(
+      Array.from(publicKeyBytes(root)),
+      Buffer.from(getMetadataArgsSerializer().serialize(cnft.metadata)),
+      new anchor.BN(cnft.leafIndex),
+      cnft.leafIndex
+    )
+    .accounts({
+      eventAuthority: findEventAuthorityPda(),
+      program: program.programId,
+      raffler: raffleAcc.raffler,
+      raffle,
+      entrants: raffleAcc.entrants,
+      entrantTally: findEntrantTallyPda(raffle, user.publicKey),
+      treeConfig: findTreeConfigPda(umi, { merkleTree: cnft.merkleTree })[0],
+      merkleTree: cnft.merkleTree,
+      leafDelegate: cnft.owner,
+      entrant: user.publicKey,
+      bubblegumProgram: MPL_BUBBLEGUM_PROGRAM_ID,
+      logWrapper: SPL_NOOP_PROGRAM_ID,
+      compressionProgram: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+      gatedNftMint,
+      gatedNftToken: gatedNftMint ? getTokenAccount(gatedNftMint, user.publicKey) : null,
+      gatedNftMetadata: gatedNftMint ? findMetadataPda(umi, { mint: gatedNftMint })[0] : null,
+    })
+    .remainingAccounts(cnftProofAccounts(proof))
+    .preInstructions([anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({ units: 300_000 })])
+    .rpc()
+
+  setCnftOwner(umi, tree, cnft, raffleAcc.entryType.spend ? raffle : null)
+}
+
+export async function collectCnft(authority: KeypairSigner, raffle: PublicKey, tree: CnftTree, cnft: Cnft) {
+  const program = programPaidBy(authority)
+  const raffleAcc = await program.account.raffle.fetch(raffle)
+  const rafflerAcc = await program.account.raffler.fetch(raffleAcc.raffler)
+  const treasury = fromWeb3JsPublicKey(rafflerAcc.treasury)
+  const { root, proof, dataHash, creatorHash } = getCnftProof(tree, cnft)
+
+  await program.methods
+    .collectCnft(
+      Array.from(publicKeyBytes(root)),
+      Array.from(dataHash),
+      Array.from(creatorHash),
+      new anchor.BN(cnft.leafIndex),
+      cnft.leafIndex
+    )
+    .accounts({
+      eventAuthority: findEventAuthorityPda(),
+      program: program.programId,
+      raffler: raffleAcc.raffler,
+      raffle,
+      treeConfig: findTreeConfigPda(umi, { merkleTree: cnft.merkleTree })[0],
+      merkleTree: cnft.merkleTree,
+      payer: authority.publicKey,
+      treasury,
+      bubblegumProgram: MPL_BUBBLEGUM_PROGRAM_ID,
+      logWrapper: SPL_NOOP_PROGRAM_ID,
+      compressionProgram: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+    })
+    .remainingAccounts(cnftProofAccounts(proof))
+    .preInstructions([anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({ units: 300_000 })])
+    .rpc()
+
+  setCnftOwner(umi, tree, cnft, treasury)
+}
+
+export async function cancelRaffle(authority: KeypairSigner, raffle: PublicKey) {
+  const program = programPaidBy(authority)
+  const raffleAcc = await program.account.raffle.fetch(raffle)
+  const tokenMint = raffleAcc.paymentType.token?.tokenMint
+    ? fromWeb3JsPublicKey(raffleAcc.paymentType.token.tokenMint)
+    : null
+  const prize = raffleAcc.prizeType.nft || raffleAcc.prizeType.token ? fromWeb3JsPublicKey(raffleAcc.prize) : null
+  const prizeDa = raffleAcc.prizeType.nft ? await fetchDigitalAsset(umi, prize) : null
+  const consolationMint = raffleAcc.consolation ? fromWeb3JsPublicKey(raffleAcc.consolation.mint) : null
+
+  const remainingAccounts: anchor.web3.AccountMeta[] = []
+
+  if (prizeDa) {
+    remainingAccounts.push(
+      {
+        pubkey: toWeb3JsPublicKey(prizeDa.metadata.publicKey),
+        isWritable: true,
+        isSigner: false,
+      },
+      {
+        pubkey: toWeb3JsPublicKey(prizeDa.edition.publicKey),
+        isWritable: false,
+        isSigner: false,
+      }
+    )
+  }
+
+  return await program.methods
+    .cancelRaffle(null, 0)
+    .accounts({
+      eventAuthority: findEventAuthorityPda(),
+      program: program.programId,
+      raffler: raffleAcc.raffler,
+      raffle,
+      entrants: raffleAcc.entrants,
+      prize,
+      prizeCustody: prize ? getTokenAccount(prize, raffle) : null,
+      prizeDestination: prize ? getTokenAccount(prize, authority.publicKey) : null,
+      prizeVault: raffleAcc.prizeType.sol ? findPrizeVaultPda(raffle) : null,
+      prizeBundle: raffleAcc.bundleItems ? findPrizeBundlePda(raffle) : null,
+      tokenMint,
+      tokenVault: tokenMint ? getTokenAccount(tokenMint, raffle) : null,
+      consolationMint,
+      consolationCustody: consolationMint ? getTokenAccount(consolationMint, raffle) : null,
+      consolationDestination: consolationMint ? getTokenAccount(consolationMint, authority.publicKey) : null,
+      consolationTokenProgram: consolationMint ? anchor.utils.token.TOKEN_PROGRAM_ID : null,
+      prizeTokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+      metadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
+      sysvarInstructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+      authRules: null,
+      authRulesProgram: null,
+    })
+    .remainingAccounts(remainingAccounts)
+    .preInstructions([anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({ units: 300_000 })])
+    .rpc()
+}
+
+export async function claimRefund(user: KeypairSigner, raffle: PublicKey) {
+  const program = programPaidBy(user)
+  const raffleAcc = await program.account.raffle.fetch(raffle)
+  const tokenMint = fromWeb3JsPublicKey(raffleAcc.paymentType.token.tokenMint)
+
+  return await program.methods
+    .claimRefund()
+    .accounts({
+      eventAuthority: findEventAuthorityPda(),
+      program: program.programId,
+      raffle,
+      entrants: raffleAcc.entrants,
+      entrantTally: findEntrantTallyPda(raffle, user.publicKey),
+      tokenMint,
+      tokenVault: getTokenAccount(tokenMint, raffle),
+      tokenDestination: getTokenAccount(tokenMint, user.publicKey),
+      nftEntry: null,
+      nftMint: null,
+      nftSource: null,
+      nftDestination: null,
+      nftMetadata: null,
+      nftEdition: null,
+      sourceTokenRecord: null,
+      destinationTokenRecord: null,
+      nftTokenProgram: null,
+      metadataProgram: null,
+      sysvarInstructions: null,
+      authRules: null,
+      authRulesProgram: null,
+    })
+    .rpc()
+}
+
+export async function addBundlePrize(authority: KeypairSigner, raffle: PublicKey, prize: PublicKey, amount: bigint) {
+  const program = programPaidBy(authority)
+  const raffleAcc = await program.account.raffle.fetch(raffle)
+
+  return await program.methods
+    .addBundlePrize(new anchor.BN(amount.toString()))
+    .accounts({
+      eventAuthority: findEventAuthorityPda(),
+      program: program.programId,
+      raffler: raffleAcc.raffler,
+      raffle,
+      prizeBundle: findPrizeBundlePda(raffle),
+      prize,
+      prizeToken: getTokenAccount(prize, authority.publicKey),
+      prizeCustody: getTokenAccount(prize, raffle),
+      prizeTokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+    })
+    .rpc()
+}
+
+export async function claimBundlePrize(payer: KeypairSigner, raffle: PublicKey, prize: PublicKey) {
+  const program = programPaidBy(payer)
+  const prizeBundle = findPrizeBundlePda(raffle)
+  const bundleAcc = await program.account.prizeBundle.fetch(prizeBundle)
+  const recipient = fromWeb3JsPublicKey(bundleAcc.recipient)
+
+  return await program.methods
+    .claimBundlePrize()
+    .accounts({
+      eventAuthority: findEventAuthorityPda(),
+      program: program.programId,
+      prizeBundle,
+      raffle,
+      prize,
+      prizeCustody: getTokenAccount(prize, raffle),
+      prizeDestination: getTokenAccount(prize, recipient),
+      recipient,
+      authority: bundleAcc.authority,
+      prizeTokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+    })
+    .rpc()
+}
+
+export async function initJackpotSeries(
+  authority: KeypairSigner,
+  raffler: PublicKey,
+  tokenMint: PublicKey,
+  winnerShare: number
+) {
+  const program = programPaidBy(authority)
+  const jackpotSeries = findJackpotSeriesPda(raffler, tokenMint)
+
+  await program.methods
+    .initJackpotSeries(winnerShare)
+    .accounts({
+      raffler,
+      jackpotSeries,
+      tokenMint,
+    })
+    .rpc()
+
+  return jackpotSeries
+}
+
+export async function rollJackpot(payer: KeypairSigner, raffle: PublicKey) {
+  const program = programPaidBy(payer)
+  const raffleAcc = await program.account.raffle.fetch(raffle)
+  const rafflerAcc = await program.account.raffler.fetch(raffleAcc.raffler)
+  const seriesAcc = await program.account.jackpotSeries.fetch(raffleAcc.jackpotSeries)
+  const tokenMint = fromWeb3JsPublicKey(seriesAcc.tokenMint)
+  const nextRaffle = fromWeb3JsPublicKey(seriesAcc.currentRaffle)
+  const treasury = fromWeb3JsPublicKey(rafflerAcc.treasury)
+
+  return await program.methods
+    .rollJackpot()
+    .accounts({
+      eventAuthority: findEventAuthorityPda(),
+      program: program.programId,
+      programConfig: findProgramConfigPda(),
+      raffler: raffleAcc.raffler,
+      jackpotSeries: raffleAcc.jackpotSeries,
+      raffle,
+      entrants: raffleAcc.entrants,
+      nextRaffle,
+      tokenMint,
+      proceedsSource: getTokenAccount(tokenMint, raffle),
+      nextVault: getTokenAccount(tokenMint, nextRaffle),
+      feesWallet: FEES_WALLET,
+      feesWalletToken: getTokenAccount(tokenMint, FEES_WALLET),
+      proceedsDestination: getTokenAccount(tokenMint, treasury),
+      prizeBundle: raffleAcc.bundleItems ? findPrizeBundlePda(raffle) : null,
+      treasury,
+      authority: rafflerAcc.authority,
+    })
+    .rpc()
+}
+
+export async function claimConsolation(user: KeypairSigner, raffle: PublicKey, withTally = true) {
+  const program = programPaidBy(user)
+  const raffleAcc = await program.account.raffle.fetch(raffle)
+  const rafflerAcc = await program.account.raffler.fetch(raffleAcc.raffler)
+  const consolationMint = fromWeb3JsPublicKey(raffleAcc.consolation.mint)
+
+  return await program.methods
+    .claimConsolation()
+    .accounts({
+      eventAuthority: findEventAuthorityPda(),
+      program: program.programId,
+      raffler: raffleAcc.raffler,
+      raffle,
+      // the raffler authority claims a pool nobody can share in without a tally
+      entrantTally: withTally ? findEntrantTallyPda(raffle, user.publicKey) : null,
+      consolationMint,
+      consolationCustody: getTokenAccount(consolationMint, raffle),
+      consolationDestination: getTokenAccount(consolationMint, user.publicKey),
+      authority: rafflerAcc.authority,
+      tokenProgram: anchor.utils.token.TOKEN_PROGRAM_ID,
+    })
+    .rpc()
+}
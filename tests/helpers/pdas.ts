@@ -9,14 +9,18 @@ import { NATIVE_MINT } from "@switchboard-xyz/solana-randomness-service"
 
 const programId = publicKey(idl.metadata.address)
 
-export function getTokenAccount(mint: PublicKey, owner: PublicKey) {
-  return findAssociatedTokenPda(umi, { mint, owner })[0]
+export function getTokenAccount(mint: PublicKey, owner: PublicKey, tokenProgramId?: PublicKey) {
+  return findAssociatedTokenPda(umi, { mint, owner, tokenProgramId })[0]
 }
 
 export function findProgramConfigPda() {
   return umi.eddsa.findPda(programId, [string({ size: "variable" }).serialize("program-config")])[0]
 }
 
+export function findEventAuthorityPda() {
+  return umi.eddsa.findPda(programId, [string({ size: "variable" }).serialize("__event_authority")])[0]
+}
+
 export function findProgramDataAddress() {
   return umi.eddsa.findPda(publicKey("BPFLoaderUpgradeab1e11111111111111111111111"), [
     publicKeySerializer().serialize(programId),
@@ -39,6 +43,34 @@ export function findRafflePda(entrants: PublicKey) {
   ])[0]
 }
 
+export function findEntrantTallyPda(raffle: PublicKey, entrant: PublicKey) {
+  return umi.eddsa.findPda(programId, [
+    string({ size: "variable" }).serialize("RAFFLE"),
+    publicKeySerializer().serialize(raffle),
+    publicKeySerializer().serialize(entrant),
+    string({ size: "variable" }).serialize("entrant-tally"),
+  ])[0]
+}
+
+export function findNftEntryPda(raffle: PublicKey, nftMint: PublicKey) {
+  return umi.eddsa.findPda(programId, [
+    string({ size: "variable" }).serialize("RAFFLE"),
+    publicKeySerializer().serialize(raffle),
+    publicKeySerializer().serialize(nftMint),
+    string({ size: "variable" }).serialize("nft-entry"),
+  ])[0]
+}
+
+export function findStakeRecordPda(raffle: PublicKey, entrant: PublicKey, mint: PublicKey) {
+  return umi.eddsa.findPda(programId, [
+    string({ size: "variable" }).serialize("RAFFLE"),
+    publicKeySerializer().serialize(raffle),
+    publicKeySerializer().serialize(entrant),
+    publicKeySerializer().serialize(mint),
+    string({ size: "variable" }).serialize("stake"),
+  ])[0]
+}
+
 export function findProceedsAuthPda(raffle: PublicKey) {
   return umi.eddsa.findPda(programId, [
     string({ size: "variable" }).serialize("RAFFLE"),
@@ -47,6 +79,39 @@ export function findProceedsAuthPda(raffle: PublicKey) {
   ])[0]
 }
 
+export function findPrizeBundlePda(raffle: PublicKey) {
+  return umi.eddsa.findPda(programId, [
+    string({ size: "variable" }).serialize("RAFFLE"),
+    publicKeySerializer().serialize(raffle),
+    string({ size: "variable" }).serialize("prize-bundle"),
+  ])[0]
+}
+
+export function findJackpotSeriesPda(raffler: PublicKey, tokenMint: PublicKey) {
+  return umi.eddsa.findPda(programId, [
+    string({ size: "variable" }).serialize("RAFFLE"),
+    publicKeySerializer().serialize(raffler),
+    publicKeySerializer().serialize(tokenMint),
+    string({ size: "variable" }).serialize("jackpot-series"),
+  ])[0]
+}
+
+export function findFeeOverridePda(raffler: PublicKey) {
+  return umi.eddsa.findPda(programId, [
+    string({ size: "variable" }).serialize("RAFFLE"),
+    publicKeySerializer().serialize(raffler),
+    string({ size: "variable" }).serialize("fee-override"),
+  ])[0]
+}
+
+export function findPrizeVaultPda(raffle: PublicKey) {
+  return umi.eddsa.findPda(programId, [
+    string({ size: "variable" }).serialize("RAFFLE"),
+    publicKeySerializer().serialize(raffle),
+    string({ size: "variable" }).serialize("prize-vault"),
+  ])[0]
+}
+
 export function getTokenRecordPda(mint: PublicKey, owner: PublicKey) {
   return findTokenRecordPda(umi, {
     mint,
@@ -2,6 +2,7 @@ import fs from "fs"
 import { createUmi } from "@metaplex-foundation/umi-bundle-defaults"
 import { mplTokenMetadata } from "@metaplex-foundation/mpl-token-metadata"
 import { mplToolbox } from "@metaplex-foundation/mpl-toolbox"
+import { mplBubblegum } from "@metaplex-foundation/mpl-bubblegum"
 import { Keypair, Signer, createSignerFromKeypair, keypairIdentity, signerIdentity } from "@metaplex-foundation/umi"
 
 const kpfile = "/Users/joefitter/.config/solana/id.json"
@@ -10,6 +11,7 @@ const kp = new Uint8Array(JSON.parse(fs.readFileSync(kpfile).toString()))
 export const umi = createUmi("http://localhost:8899", { commitment: "processed" })
   .use(mplTokenMetadata())
   .use(mplToolbox())
+  .use(mplBubblegum())
 
 umi.use(keypairIdentity(umi.eddsa.createKeypairFromSecretKey(kp)))
 
@@ -17,5 +19,6 @@ export function getUmi(kp: Keypair) {
   return createUmi("http://localhost:8899", { commitment: "processed" })
     .use(mplTokenMetadata())
     .use(mplToolbox())
+    .use(mplBubblegum())
     .use(keypairIdentity(kp))
 }
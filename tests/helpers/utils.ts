@@ -7,14 +7,15 @@ import { umi } from "./umi"
 import { RafflooorProgram } from "../../target/types/rafflooor_program"
 import { safeFetchToken } from "@metaplex-foundation/mpl-toolbox"
 import { getTokenAccount } from "./pdas"
+import { adminProgram } from "../helper"
 import { createSignerFromKeypair } from "@metaplex-foundation/umi"
-import _ from "lodash"
 
 export const TX_FEE = 5000n
 export const MAX_REALLOC_SIZE = 10240
 export const PNFT_SIZE = 11957280n
 
 export const FEES_WALLET = publicKey("D7sZPRf5WRC6BpLsu6k3gwcwxZGxbTrFMyDvrMxkVeJP")
+export const STAKE_PROGRAM = publicKey("STAKEQkGBjkhCXabzB5cUbWgSSvbVJFEm2oEnyWzdKE")
 
 export async function sleep(ms: number) {
   return new Promise((resolve) => setTimeout(resolve, ms))
@@ -54,10 +55,39 @@ export function assertEqualishLamports(num1: bigint, num2: bigint, msg?: string)
   assert.ok(Math.abs(Number(num1) - Number(num2)) < 100, msg)
 }
 
+// emit_cpi! events are the data of a self-CPI, tagged with anchor's event instruction discriminator
+const EVENT_IX_TAG = Buffer.from("e445a52e51cb9a1d", "hex")
+
+export async function getEvents(signature: string) {
+  let tx: anchor.web3.VersionedTransactionResponse | null = null
+  while (!tx) {
+    tx = await adminProgram.provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    })
+    if (!tx) {
+      await sleep(500)
+    }
+  }
+
+  const accountKeys = tx.transaction.message.getAccountKeys()
+  return tx.meta.innerInstructions
+    .flatMap((inner) => inner.instructions)
+    .filter((ix) => accountKeys.get(ix.programIdIndex).equals(adminProgram.programId))
+    .map((ix) => Buffer.from(anchor.utils.bytes.bs58.decode(ix.data)))
+    .filter((data) => data.subarray(0, 8).equals(EVENT_IX_TAG))
+    .map((data) => adminProgram.coder.events.decode(data.subarray(8).toString("base64")))
+}
+
 export async function getTokenAmount(tokenMint: PublicKey, owner: PublicKey): Promise<bigint> {
   return (await safeFetchToken(umi, getTokenAccount(tokenMint, owner)))?.amount || 0n
 }
 
+// the program that owns the mint, SPL Token or Token-2022
+export async function getTokenProgram(mint: PublicKey) {
+  return (await umi.rpc.getAccount(mint)).owner
+}
+
 export const DANDIES_COLLECTION_SIGNER = createSignerFromKeypair(
   umi,
   umi.eddsa.createKeypairFromSecretKey(
@@ -69,16 +99,81 @@ export const DANDIES_COLLECTION_SIGNER = createSignerFromKeypair(
   )
 )
 
-// Output is only u32, so can be a number
-export function expandRandomness(randomValue: number[]): number {
-  const hasher = sha3.keccak_256.create()
-  hasher.update(new Uint8Array(randomValue))
+// mirrors utils::randomness_tools::RandomnessStream, block n is keccak256(randomness || n as u32 le)
+// read as four little endian u64 words
+export class RandomnessStream {
+  private counter = 0
+  private block: Uint8Array = new Uint8Array(32)
+  private offset = 32
+
+  constructor(private readonly randomness: number[] | Uint8Array) {}
+
+  nextU64(): bigint {
+    if (this.offset === 32) {
+      const counter = new Uint8Array(4)
+      new DataView(counter.buffer).setUint32(0, this.counter, true)
+      const hasher = sha3.keccak_256.create()
+      hasher.update(new Uint8Array(this.randomness))
+      hasher.update(counter)
+      this.block = new Uint8Array(hasher.digest())
+      this.counter = (this.counter + 1) >>> 0
+      this.offset = 0
+    }
+
+    const word = new DataView(this.block.buffer, this.block.byteOffset).getBigUint64(this.offset, true)
+    this.offset += 8
+    return word
+  }
 
-  return new anchor.BN(hasher.digest().slice(0, 4), "le").toNumber()
+  // rejection sampling, words below 2^64 mod bound are discarded
+  nextBelow(bound: number): number {
+    const big = BigInt(bound)
+    const threshold = (1n << 64n) % big
+    for (;;) {
+      const word = this.nextU64()
+      if (word >= threshold) {
+        return Number(word % big)
+      }
+    }
+  }
 }
 
+// mirrors utils::randomness_tools::winning_ticket_indices
+export function winningTicketIndices(
+  randomness: number[] | Uint8Array,
+  total: number,
+  numWinners: number,
+  excluded: number[] = []
+): number[] {
+  const stream = new RandomnessStream(randomness)
+  // each excluded ticket counts once, an unclaimed winner is also listed as forfeited
+  const eligible = total - new Set(excluded.filter((ticket) => ticket < total)).size
+  const winners: number[] = []
+  while (winners.length < Math.min(numWinners, eligible)) {
+    const index = stream.nextBelow(total)
+    if (!winners.includes(index) && !excluded.includes(index)) {
+      winners.push(index)
+    }
+  }
+  return winners
+}
+
+// entrants are stored as runs of (entrant, cumulative ticket count), expanded here to one entry per ticket
 export async function getEntrantsArray(entrantsPk: PublicKey) {
   const acc = await umi.rpc.getAccount(entrantsPk)
-  const data = acc.exists && acc.data.slice(8 + 4 + 4)
-  return _.chunk(data, 32).map((arr) => publicKey(new Uint8Array(arr)))
+  if (!acc.exists) {
+    return []
+  }
+  const view = new DataView(acc.data.buffer, acc.data.byteOffset, acc.data.byteLength)
+  const ranges = view.getUint32(8 + 4 + 4, true)
+  const entrants: PublicKey[] = []
+  for (let i = 0; i < ranges; i++) {
+    const start = 8 + 4 + 4 + 4 + i * (32 + 4)
+    const entrant = publicKey(acc.data.slice(start, start + 32))
+    const end = view.getUint32(start + 32, true)
+    while (entrants.length < end) {
+      entrants.push(entrant)
+    }
+  }
+  return entrants
 }
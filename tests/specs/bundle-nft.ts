@@ -0,0 +1,79 @@
+import * as anchor from "@coral-xyz/anchor"
+import { DigitalAsset } from "@metaplex-foundation/mpl-token-metadata"
+import { KeypairSigner, PublicKey, generateSigner, tokenAmount } from "@metaplex-foundation/umi"
+import { assert } from "chai"
+import { randomnessService, createNewUser } from "../helper"
+import { createCollection } from "../helpers/create-collection"
+import { createNft } from "../helpers/create-nft"
+import {
+  createRaffle,
+  buyTicketSendNft,
+  settleRaffle,
+  collectNft,
+  createRaffloor,
+  addBundlePrize,
+} from "../helpers/instructions"
+import { findRafflePda } from "../helpers/pdas"
+import { umi } from "../helpers/umi"
+import { mintNfts, expectFail, assertErrorCode, getTokenAmount, sleep } from "../helpers/utils"
+import { createToken } from "../helpers/create-token"
+
+describe("Prize bundles with NFT entries", () => {
+  let collection: DigitalAsset
+  let entryNft: DigitalAsset
+  let bundleNft: DigitalAsset
+  let authority: KeypairSigner
+  let raffler: PublicKey
+  let user: KeypairSigner
+  let prizeToken: PublicKey
+  let startTime: number
+  const prizeAmount = tokenAmount(100, "token", 6).basisPoints
+  const treasury = generateSigner(umi).publicKey
+  const entrants = generateSigner(umi)
+  const raffle = findRafflePda(entrants.publicKey)
+
+  before(async () => {
+    user = await createNewUser()
+    ;[authority, raffler] = await createRaffloor("Bundle NFT Raffle", "bundle_nft_raffle", treasury)
+    prizeToken = await createToken(umi, prizeAmount, 6, undefined, authority.publicKey)
+    collection = await createCollection(umi)
+    ;[entryNft] = await mintNfts(collection.publicKey, 1, true, user.publicKey)
+    bundleNft = await createNft(umi, false, undefined, authority.publicKey)
+    startTime = Math.floor(Date.now() / 1000) + 10
+
+    await createRaffle({
+      prizeType: { token: { amount: new anchor.BN(prizeAmount.toString()) } },
+      authority,
+      raffler,
+      entrants,
+      numTickets: 1,
+      tokenMint: null,
+      entryType: { spend: {} },
+      ticketPrice: null,
+      startTime,
+      duration: 60 * 60 * 24,
+      prize: prizeToken,
+      entryCollectionMint: collection.publicKey,
+    })
+
+    await addBundlePrize(authority, raffle, bundleNft.publicKey, 1n)
+    await sleep(startTime * 1000 - Date.now() + 2000)
+    await buyTicketSendNft(user, raffle, entryNft.publicKey)
+    await settleRaffle(randomnessService, raffle)
+  })
+
+  it("cannot collect a bundle NFT as an entry", async () => {
+    await expectFail(
+      () => collectNft(authority, raffle, bundleNft.publicKey),
+      (err) => assertErrorCode(err, "InvalidPrize")
+    )
+
+    assert.equal(await getTokenAmount(bundleNft.publicKey, raffle), 1n, "Expected the raffle to keep the bundle NFT")
+  })
+
+  it("can collect the entry NFT", async () => {
+    await collectNft(authority, raffle, entryNft.publicKey)
+
+    assert.equal(await getTokenAmount(entryNft.publicKey, treasury), 1n, "Expected the treasury to hold the entry NFT")
+  })
+})
@@ -0,0 +1,123 @@
+import * as anchor from "@coral-xyz/anchor"
+import { KeypairSigner, PublicKey, generateSigner, sol, tokenAmount } from "@metaplex-foundation/umi"
+import { assert } from "chai"
+import { randomnessService, adminProgram, createNewUser } from "../helper"
+import {
+  createRaffle,
+  buyTicketsToken,
+  settleRaffle,
+  claimPrize,
+  createRaffloor,
+  addBundlePrize,
+  claimBundlePrize,
+} from "../helpers/instructions"
+import { findPrizeBundlePda, findRafflePda, nativeMint } from "../helpers/pdas"
+import { umi } from "../helpers/umi"
+import { expectFail, assertErrorCode, getTokenAmount, sleep } from "../helpers/utils"
+import { createToken } from "../helpers/create-token"
+
+describe("Prize bundles", () => {
+  let entrants: KeypairSigner
+  let raffle: PublicKey
+  let authority: KeypairSigner
+  let raffler: PublicKey
+  let user: KeypairSigner
+  let prizeToken: PublicKey
+  let bundleTokenA: PublicKey
+  let bundleTokenB: PublicKey
+  let startTime: number
+  const prizeAmount = tokenAmount(100, "token", 6).basisPoints
+  const bundleAmountA = tokenAmount(50, "token", 6).basisPoints
+  const bundleAmountB = tokenAmount(10, "token", 6).basisPoints
+
+  before(async () => {
+    user = await createNewUser()
+    ;[authority, raffler] = await createRaffloor("Bundle Raffle", "bundle_raffle")
+    prizeToken = await createToken(umi, prizeAmount * 2n, 6, undefined, authority.publicKey)
+    bundleTokenA = await createToken(umi, bundleAmountA * 2n, 6, undefined, authority.publicKey)
+    bundleTokenB = await createToken(umi, bundleAmountB, 6, undefined, authority.publicKey)
+    entrants = generateSigner(umi)
+    raffle = findRafflePda(entrants.publicKey)
+    startTime = Math.floor(Date.now() / 1000) + 10
+
+    await createRaffle({
+      prizeType: { token: { amount: new anchor.BN(prizeAmount.toString()) } },
+      authority,
+      raffler,
+      entrants,
+      numTickets: 1,
+      tokenMint: nativeMint,
+      entryType: { spend: {} },
+      ticketPrice: sol(0.1).basisPoints,
+      startTime,
+      duration: 60 * 60 * 24,
+      prize: prizeToken,
+    })
+  })
+
+  it("cannot add a bundle to a raffle with multiple winners", async () => {
+    const multiEntrants = generateSigner(umi)
+    await createRaffle({
+      prizeType: { token: { amount: new anchor.BN(prizeAmount.toString()) } },
+      authority,
+      raffler,
+      entrants: multiEntrants,
+      numTickets: 10,
+      tokenMint: nativeMint,
+      entryType: { spend: {} },
+      ticketPrice: sol(0.1).basisPoints,
+      startTime,
+      duration: 60 * 60 * 24,
+      prize: prizeToken,
+      numWinners: 2,
+    })
+
+    await expectFail(
+      () => addBundlePrize(authority, findRafflePda(multiEntrants.publicKey), bundleTokenA, bundleAmountA),
+      (err) => assertErrorCode(err, "BundleRequiresSingleWinner")
+    )
+  })
+
+  it("can add prizes to the bundle before the raffle starts", async () => {
+    await addBundlePrize(authority, raffle, bundleTokenA, bundleAmountA)
+    await addBundlePrize(authority, raffle, bundleTokenB, bundleAmountB)
+
+    const bundleAcc = await adminProgram.account.prizeBundle.fetch(findPrizeBundlePda(raffle))
+    assert.equal(bundleAcc.items.length, 2, "Expected 2 items in the bundle")
+    assert.equal(bundleAcc.items[0].amount.toString(), bundleAmountA.toString(), "Expected item amount to be recorded")
+
+    const raffleAcc = await adminProgram.account.raffle.fetch(raffle)
+    assert.equal(raffleAcc.bundleItems, 2, "Expected the raffle to count the bundle items")
+  })
+
+  it("cannot add to the bundle once the raffle has started", async () => {
+    await sleep(startTime * 1000 - Date.now() + 2000)
+
+    await expectFail(
+      () => addBundlePrize(authority, raffle, bundleTokenA, bundleAmountA),
+      (err) => assertErrorCode(err, "BundleLocked")
+    )
+  })
+
+  it("delivers the bundle to the winner", async () => {
+    await buyTicketsToken(user, raffle, 1)
+    await settleRaffle(randomnessService, raffle)
+    await claimPrize(user, raffle)
+
+    const bundleAcc = await adminProgram.account.prizeBundle.fetch(findPrizeBundlePda(raffle))
+    assert.equal(bundleAcc.recipient.toBase58(), user.publicKey, "Expected the winner to receive the bundle")
+
+    await claimBundlePrize(user, raffle, bundleTokenA)
+    await claimBundlePrize(user, raffle, bundleTokenB)
+
+    assert.equal(await getTokenAmount(bundleTokenA, user.publicKey), bundleAmountA, "Expected bundle item A")
+    assert.equal(await getTokenAmount(bundleTokenB, user.publicKey), bundleAmountB, "Expected bundle item B")
+  })
+
+  it("cannot deliver a bundle item twice", async () => {
+    await expectFail(
+      () => claimBundlePrize(user, raffle, bundleTokenA),
+      (err) => assertErrorCode(err, "AccountNotInitialized")
+    )
+  })
+})
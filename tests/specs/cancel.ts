@@ -0,0 +1,76 @@
+import * as anchor from "@coral-xyz/anchor"
+import { KeypairSigner, PublicKey, generateSigner, sol, tokenAmount } from "@metaplex-foundation/umi"
+import { assert } from "chai"
+import { createNewUser } from "../helper"
+import { createRaffle, buyTicketsToken, cancelRaffle, createRaffloor } from "../helpers/instructions"
+import { findRafflePda, nativeMint } from "../helpers/pdas"
+import { umi } from "../helpers/umi"
+import { expectFail, assertErrorCode, getTokenAmount } from "../helpers/utils"
+import { createToken } from "../helpers/create-token"
+
+describe("Cancel raffle", () => {
+  let authority: KeypairSigner
+  let raffler: PublicKey
+  let user: KeypairSigner
+  let prizeToken: PublicKey
+  const prizeAmount = tokenAmount(100, "token", 6).basisPoints
+
+  async function newRaffle(startTime: number | null) {
+    const entrants = generateSigner(umi)
+    await createRaffle({
+      prizeType: { token: { amount: new anchor.BN(prizeAmount.toString()) } },
+      authority,
+      raffler,
+      entrants,
+      numTickets: 10,
+      tokenMint: nativeMint,
+      entryType: { spend: {} },
+      ticketPrice: sol(0.1).basisPoints,
+      startTime,
+      duration: 60 * 60 * 24,
+      prize: prizeToken,
+    })
+
+    return findRafflePda(entrants.publicKey)
+  }
+
+  before(async () => {
+    user = await createNewUser()
+    ;[authority, raffler] = await createRaffloor("Cancel Raffle", "cancel_raffle")
+    prizeToken = await createToken(umi, prizeAmount, 6, undefined, authority.publicKey)
+  })
+
+  it("cannot cancel someone else's raffle", async () => {
+    const raffle = await newRaffle(Math.floor(Date.now() / 1000) + 60)
+
+    await expectFail(
+      () => cancelRaffle(user, raffle),
+      (err) => assertErrorCode(err, "Unauthorized")
+    )
+
+    await cancelRaffle(authority, raffle)
+  })
+
+  it("can cancel a scheduled raffle and take back the prize", async () => {
+    const raffle = await newRaffle(Math.floor(Date.now() / 1000) + 60)
+    const authBalBefore = await getTokenAmount(prizeToken, authority.publicKey)
+
+    await cancelRaffle(authority, raffle)
+
+    const authBalAfter = await getTokenAmount(prizeToken, authority.publicKey)
+    assert.equal(authBalAfter - authBalBefore, prizeAmount, "Expected the prize to be returned")
+
+    const raffleAcc = await umi.rpc.getAccount(raffle)
+    assert.ok(!raffleAcc.exists, "Expected the raffle account to be closed")
+  })
+
+  it("cannot cancel a raffle with entrants", async () => {
+    const raffle = await newRaffle(null)
+    await buyTicketsToken(user, raffle, 1)
+
+    await expectFail(
+      () => cancelRaffle(authority, raffle),
+      (err) => assertErrorCode(err, "RaffleHasEntrants")
+    )
+  })
+})
@@ -0,0 +1,110 @@
+import * as anchor from "@coral-xyz/anchor"
+import { DigitalAsset } from "@metaplex-foundation/mpl-token-metadata"
+import { KeypairSigner, PublicKey, generateSigner, tokenAmount } from "@metaplex-foundation/umi"
+import { assert } from "chai"
+import { randomnessService, adminProgram, createNewUser } from "../helper"
+import { createCollection } from "../helpers/create-collection"
+import { Cnft, CnftTree, createCnft, createCnftTree, isCnftTreeInSync } from "../helpers/create-cnft"
+import {
+  createRaffle,
+  buyTicketsToken,
+  buyTicketCnft,
+  settleRaffle,
+  claimPrize,
+  collectCnft,
+  createRaffloor,
+} from "../helpers/instructions"
+import { findRafflePda } from "../helpers/pdas"
+import { umi } from "../helpers/umi"
+import { getEntrantsArray, expectFail, assertErrorCode } from "../helpers/utils"
+import { createToken } from "../helpers/create-token"
+
+describe("Raffle with cNFTs as currency", () => {
+  let collection: DigitalAsset
+  let tree: CnftTree
+  let validCnfts: Cnft[]
+  let invalidCnft: Cnft
+  let authority: KeypairSigner
+  let raffler: PublicKey
+  let user: KeypairSigner
+  let prizeToken: PublicKey
+  const prizeAmount = tokenAmount(100, "token", 6).basisPoints
+  const treasury = generateSigner(umi).publicKey
+  const entrants = generateSigner(umi)
+  const raffle = findRafflePda(entrants.publicKey)
+
+  before(async () => {
+    ;[authority, raffler] = await createRaffloor("cNFT entry", "cnft_entry", treasury)
+    user = await createNewUser()
+    prizeToken = await createToken(umi, prizeAmount, 6, undefined, authority.publicKey)
+    collection = await createCollection(umi)
+    tree = await createCnftTree(umi)
+    validCnfts = [
+      await createCnft(umi, tree, user.publicKey, collection.publicKey),
+      await createCnft(umi, tree, user.publicKey, collection.publicKey),
+    ]
+    invalidCnft = await createCnft(umi, tree, user.publicKey)
+  })
+
+  it("can create a raffle", async () => {
+    await createRaffle({
+      prizeType: { token: { amount: new anchor.BN(prizeAmount.toString()) } },
+      authority,
+      raffler,
+      entrants,
+      numTickets: 2,
+      tokenMint: null,
+      entryType: { spend: {} },
+      ticketPrice: null,
+      duration: 60 * 60 * 24,
+      prize: prizeToken,
+      entryCollectionMint: collection.publicKey,
+      isCompressed: true,
+    })
+
+    const raffleAcc = await adminProgram.account.raffle.fetch(raffle)
+    assert.equal(raffleAcc.paymentType.cnft.collection.toBase58(), collection.publicKey, "Expected a cNFT entry")
+  })
+
+  it("cannot buy a ticket without a cNFT", async () => {
+    await expectFail(
+      () => buyTicketsToken(user, raffle, 1),
+      (err) => assertErrorCode(err, "InvalidInstruction")
+    )
+
+    const entrantsArray = await getEntrantsArray(entrants.publicKey)
+    assert.equal(entrantsArray.length, 0, "Expected no tickets to be issued")
+  })
+
+  it("cannot buy a ticket with a cNFT outside the collection", async () => {
+    await expectFail(
+      () => buyTicketCnft(user, raffle, tree, invalidCnft),
+      (err) => assertErrorCode(err, "InvalidCollection")
+    )
+  })
+
+  it("can buy a ticket with a valid cNFT", async () => {
+    await buyTicketCnft(user, raffle, tree, validCnfts[0])
+
+    const entrantsArray = await getEntrantsArray(entrants.publicKey)
+    assert.deepEqual(entrantsArray, [user.publicKey], "Expected the user to hold one ticket")
+    assert.ok(await isCnftTreeInSync(umi, tree), "Expected the raffle to own the cNFT")
+  })
+
+  it("can sell out and draw the raffle", async () => {
+    await buyTicketCnft(user, raffle, tree, validCnfts[1])
+    await settleRaffle(randomnessService, raffle)
+    await claimPrize(user, raffle)
+
+    const raffleAcc = await adminProgram.account.raffle.fetch(raffle)
+    assert.ok(raffleAcc.claimed, "Expected the prize to be claimed")
+  })
+
+  it("can collect the entry cNFTs to the treasury", async () => {
+    for (const cnft of validCnfts) {
+      await collectCnft(authority, raffle, tree, cnft)
+    }
+
+    assert.ok(await isCnftTreeInSync(umi, tree), "Expected the treasury to own the cNFTs")
+  })
+})
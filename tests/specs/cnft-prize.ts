@@ -0,0 +1,82 @@
+import { DigitalAsset } from "@metaplex-foundation/mpl-token-metadata"
+import { KeypairSigner, PublicKey, generateSigner } from "@metaplex-foundation/umi"
+import { assert } from "chai"
+import { randomnessService, adminProgram, createNewUser } from "../helper"
+import { createCollection } from "../helpers/create-collection"
+import { Cnft, CnftTree, createCnft, createCnftTree, isCnftTreeInSync } from "../helpers/create-cnft"
+import {
+  createRaffle,
+  buyTicketCnft,
+  settleRaffle,
+  claimPrize,
+  collectCnft,
+  createRaffloor,
+} from "../helpers/instructions"
+import { findRafflePda } from "../helpers/pdas"
+import { umi } from "../helpers/umi"
+import { expectFail, assertErrorCode } from "../helpers/utils"
+
+describe("Raffle with a cNFT prize", () => {
+  let collection: DigitalAsset
+  let tree: CnftTree
+  let prize: Cnft
+  let entry: Cnft
+  let authority: KeypairSigner
+  let raffler: PublicKey
+  let user: KeypairSigner
+  const treasury = generateSigner(umi).publicKey
+  const entrants = generateSigner(umi)
+  const raffle = findRafflePda(entrants.publicKey)
+
+  before(async () => {
+    ;[authority, raffler] = await createRaffloor("cNFT prize", "cnft_prize", treasury)
+    user = await createNewUser()
+    collection = await createCollection(umi)
+    tree = await createCnftTree(umi)
+    prize = await createCnft(umi, tree, authority.publicKey)
+    entry = await createCnft(umi, tree, user.publicKey, collection.publicKey)
+  })
+
+  it("escrows the prize when the raffle is created", async () => {
+    await createRaffle({
+      prizeType: { cnft: {} },
+      authority,
+      raffler,
+      entrants,
+      numTickets: 1,
+      tokenMint: null,
+      entryType: { spend: {} },
+      ticketPrice: null,
+      duration: 60 * 60 * 24,
+      entryCollectionMint: collection.publicKey,
+      isCompressed: true,
+      prizeCnft: { tree, cnft: prize },
+    })
+
+    const raffleAcc = await adminProgram.account.raffle.fetch(raffle)
+    assert.equal(raffleAcc.prize.toBase58(), prize.assetId, "Expected the prize to be the cNFT's asset id")
+    assert.ok(await isCnftTreeInSync(umi, tree), "Expected the raffle to own the prize")
+  })
+
+  it("cannot collect the prize as an entry", async () => {
+    await buyTicketCnft(user, raffle, tree, entry)
+    await settleRaffle(randomnessService, raffle)
+
+    await expectFail(
+      () => collectCnft(authority, raffle, tree, prize),
+      (err) => assertErrorCode(err, "InvalidPrize")
+    )
+  })
+
+  it("delivers the prize to the winner", async () => {
+    await claimPrize(user, raffle, undefined, { tree, cnft: prize })
+
+    assert.ok(await isCnftTreeInSync(umi, tree), "Expected the winner to own the prize")
+  })
+
+  it("can collect the entry cNFT", async () => {
+    await collectCnft(authority, raffle, tree, entry)
+
+    assert.ok(await isCnftTreeInSync(umi, tree), "Expected the treasury to own the entry")
+  })
+})
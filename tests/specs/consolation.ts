@@ -0,0 +1,101 @@
+import * as anchor from "@coral-xyz/anchor"
+import { KeypairSigner, PublicKey, generateSigner, sol, tokenAmount } from "@metaplex-foundation/umi"
+import { assert } from "chai"
+import { randomnessService, adminProgram, createNewUser } from "../helper"
+import {
+  createRaffle,
+  buyTicketsToken,
+  settleRaffle,
+  createRaffloor,
+  claimConsolation,
+} from "../helpers/instructions"
+import { findRafflePda, getTokenAccount, nativeMint } from "../helpers/pdas"
+import { umi } from "../helpers/umi"
+import { expectFail, assertErrorCode, getTokenAmount } from "../helpers/utils"
+import { createToken } from "../helpers/create-token"
+
+describe("Consolation pool", () => {
+  let entrants: KeypairSigner
+  let raffle: PublicKey
+  let authority: KeypairSigner
+  let raffler: PublicKey
+  let user1: KeypairSigner
+  let user2: KeypairSigner
+  let prizeToken: PublicKey
+  let consolationToken: PublicKey
+  const prizeAmount = tokenAmount(100, "token", 6).basisPoints
+  const poolAmount = tokenAmount(30, "token", 6).basisPoints
+
+  before(async () => {
+    user1 = await createNewUser()
+    user2 = await createNewUser()
+    ;[authority, raffler] = await createRaffloor("Consolation Raffle", "consolation_raffle")
+    prizeToken = await createToken(umi, prizeAmount, 6, undefined, authority.publicKey)
+    consolationToken = await createToken(umi, poolAmount, 6, undefined, authority.publicKey)
+    entrants = generateSigner(umi)
+    raffle = findRafflePda(entrants.publicKey)
+
+    await createRaffle({
+      prizeType: { token: { amount: new anchor.BN(prizeAmount.toString()) } },
+      authority,
+      raffler,
+      entrants,
+      numTickets: 4,
+      tokenMint: nativeMint,
+      entryType: { spend: {} },
+      ticketPrice: sol(0.1).basisPoints,
+      duration: 60 * 60 * 24,
+      prize: prizeToken,
+      consolation: { mint: consolationToken, amount: poolAmount },
+    })
+
+    await buyTicketsToken(user1, raffle, 2)
+    await buyTicketsToken(user2, raffle, 2)
+  })
+
+  it("cannot claim a consolation share before the draw", async () => {
+    await expectFail(
+      () => claimConsolation(user1, raffle),
+      (err) => assertErrorCode(err, "WinnersNotFinal")
+    )
+  })
+
+  it("records the non-winning tickets when the draw settles", async () => {
+    await settleRaffle(randomnessService, raffle)
+
+    const raffleAcc = await adminProgram.account.raffle.fetch(raffle)
+    assert.equal(raffleAcc.consolation.eligibleTickets, 3, "Expected the 3 losing tickets to share the pool")
+  })
+
+  it("pays a share per losing ticket, and only once", async () => {
+    const balanceBefore = await getTokenAmount(consolationToken, user1.publicKey)
+    const raffleAcc = await adminProgram.account.raffle.fetch(raffle)
+    const user1Won = raffleAcc.winners.filter((w) => w.entrant.toBase58() === user1.publicKey).length
+
+    await claimConsolation(user1, raffle)
+
+    const balanceAfter = await getTokenAmount(consolationToken, user1.publicKey)
+    assert.equal(
+      balanceAfter - balanceBefore,
+      (poolAmount * BigInt(2 - user1Won)) / 3n,
+      "Expected a third of the pool per losing ticket"
+    )
+
+    await expectFail(
+      () => claimConsolation(user1, raffle),
+      (err) => assertErrorCode(err, "AlreadyClaimed")
+    )
+  })
+
+  it("pays out the rest of the pool to the last claimant", async () => {
+    await claimConsolation(user2, raffle)
+
+    const paid =
+      (await getTokenAmount(consolationToken, user1.publicKey)) +
+      (await getTokenAmount(consolationToken, user2.publicKey))
+    assert.equal(paid, poolAmount, "Expected the whole pool to be paid out")
+
+    const custody = await umi.rpc.getAccount(getTokenAccount(consolationToken, raffle))
+    assert.ok(!custody.exists, "Expected the pool custody to be closed")
+  })
+})
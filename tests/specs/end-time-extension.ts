@@ -0,0 +1,69 @@
+import { KeypairSigner, PublicKey, generateSigner, sol } from "@metaplex-foundation/umi"
+import { assert } from "chai"
+import { adminProgram, createNewUser } from "../helper"
+import { createRaffle, buyTicketsToken, createRaffloor } from "../helpers/instructions"
+import { findRafflePda, nativeMint } from "../helpers/pdas"
+import { umi } from "../helpers/umi"
+import { expectFail, assertErrorCode, sleep } from "../helpers/utils"
+
+describe("End time extension", () => {
+  let entrants: KeypairSigner
+  let raffle: PublicKey
+  let authority: KeypairSigner
+  let raffler: PublicKey
+  let user: KeypairSigner
+  let endTime: number
+
+  before(async () => {
+    user = await createNewUser()
+    ;[authority, raffler] = await createRaffloor("Extending Raffle", "extending_raffle")
+    entrants = generateSigner(umi)
+    raffle = findRafflePda(entrants.publicKey)
+
+    // the window covers the whole raffle, so every purchase extends it
+    await createRaffle({
+      prizeType: { pot: { winnerShare: 5000 } },
+      authority,
+      raffler,
+      entrants,
+      numTickets: 10,
+      tokenMint: nativeMint,
+      entryType: { spend: {} },
+      ticketPrice: sol(0.1).basisPoints,
+      duration: 10,
+      endTimeExtension: { window: 60, extension: 5, maxExtension: 8 },
+    })
+
+    const raffleAcc = await adminProgram.account.raffle.fetch(raffle)
+    endTime = raffleAcc.endTime.toNumber()
+  })
+
+  it("caps extensions from the original end time", async () => {
+    const raffleAcc = await adminProgram.account.raffle.fetch(raffle)
+    assert.equal(raffleAcc.maxEndTime.toNumber(), endTime + 8, "Expected the cap to be the max extension")
+  })
+
+  it("extends the end time on a purchase in the window", async () => {
+    await buyTicketsToken(user, raffle, 1)
+
+    const raffleAcc = await adminProgram.account.raffle.fetch(raffle)
+    assert.equal(raffleAcc.endTime.toNumber(), endTime + 5, "Expected the purchase to extend the raffle")
+  })
+
+  it("stops extending at the cap", async () => {
+    await buyTicketsToken(user, raffle, 1)
+    await buyTicketsToken(user, raffle, 1)
+
+    const raffleAcc = await adminProgram.account.raffle.fetch(raffle)
+    assert.equal(raffleAcc.endTime.toNumber(), endTime + 8, "Expected the end time to stop at the cap")
+  })
+
+  it("cannot buy once the capped end time passes", async () => {
+    await sleep(20_000)
+
+    await expectFail(
+      () => buyTicketsToken(user, raffle, 1),
+      (err) => assertErrorCode(err, "Ended")
+    )
+  })
+})
@@ -0,0 +1,80 @@
+import * as anchor from "@coral-xyz/anchor"
+import { KeypairSigner, PublicKey, generateSigner, sol, tokenAmount } from "@metaplex-foundation/umi"
+import { assert } from "chai"
+import { randomnessService, adminProgram, createNewUser } from "../helper"
+import { createRaffle, buyTicketsToken, settleRaffle, claimPrize, createRaffloor } from "../helpers/instructions"
+import { findRafflePda, nativeMint } from "../helpers/pdas"
+import { umi } from "../helpers/umi"
+import { getEvents } from "../helpers/utils"
+import { createToken } from "../helpers/create-token"
+
+describe("Events", () => {
+  let entrants: KeypairSigner
+  let raffle: PublicKey
+  let authority: KeypairSigner
+  let raffler: PublicKey
+  let user1: KeypairSigner
+  let user2: KeypairSigner
+  const ticketPrice = sol(0.1).basisPoints
+  const prizeAmount = tokenAmount(100, "token", 6).basisPoints
+
+  before(async () => {
+    user1 = await createNewUser()
+    user2 = await createNewUser()
+    ;[authority, raffler] = await createRaffloor("Events Raffle", "events_raffle")
+    entrants = generateSigner(umi)
+    raffle = findRafflePda(entrants.publicKey)
+  })
+
+  it("emits RaffleCreated", async () => {
+    const prizeToken = await createToken(umi, prizeAmount, 6, undefined, authority.publicKey)
+    const sig = await createRaffle({
+      prizeType: { token: { amount: new anchor.BN(prizeAmount.toString()) } },
+      authority,
+      raffler,
+      entrants,
+      numTickets: 5,
+      tokenMint: nativeMint,
+      entryType: { spend: {} },
+      ticketPrice,
+      duration: 60 * 60 * 24,
+      prize: prizeToken,
+    })
+
+    const [event] = (await getEvents(sig)).filter((e) => e.name === "RaffleCreated")
+    assert.equal(event.data.raffle.toBase58(), raffle, "Expected the new raffle")
+    assert.equal(event.data.raffler.toBase58(), raffler, "Expected the raffler")
+    assert.equal(event.data.prize.toBase58(), prizeToken, "Expected the prize")
+    assert.equal(event.data.maxTickets, 5, "Expected the ticket supply")
+    assert.equal(event.data.numWinners, 1, "Expected a single winner")
+  })
+
+  it("emits TicketsPurchased with the first ticket of each purchase", async () => {
+    await buyTicketsToken(user1, raffle, 2)
+    const sig = await buyTicketsToken(user2, raffle, 3)
+
+    const [event] = (await getEvents(sig)).filter((e) => e.name === "TicketsPurchased")
+    assert.equal(event.data.entrant.toBase58(), user2.publicKey, "Expected the buyer")
+    assert.equal(event.data.amount, 3, "Expected the tickets bought")
+    assert.equal(event.data.cost.toString(), (3n * ticketPrice).toString(), "Expected the cost of the tickets")
+    assert.equal(event.data.firstTicketIndex, 2, "Expected the purchase to follow the first buyer's tickets")
+  })
+
+  it("emits PrizeClaimed with the fee split", async () => {
+    await settleRaffle(randomnessService, raffle)
+    const raffleAcc = await adminProgram.account.raffle.fetch(raffle)
+    const winner = raffleAcc.winners[0].entrant.toBase58() === user1.publicKey ? user1 : user2
+    const sig = await claimPrize(winner, raffle)
+
+    const [event] = (await getEvents(sig)).filter((e) => e.name === "PrizeClaimed")
+    assert.equal(event.data.winner.toBase58(), winner.publicKey, "Expected the winner")
+    const proceeds = 5n * ticketPrice
+    const fee = (proceeds * 500n) / 10_000n
+    assert.equal(event.data.fee.toString(), fee.toString(), "Expected the program's cut of the proceeds")
+    assert.equal(
+      event.data.treasuryProceeds.toString(),
+      (proceeds - fee).toString(),
+      "Expected the rest of the proceeds to go to the treasury"
+    )
+  })
+})
@@ -0,0 +1,98 @@
+import { KeypairSigner, PublicKey, generateSigner, sol } from "@metaplex-foundation/umi"
+import { assert } from "chai"
+import { randomnessService, adminProgram, createNewUser } from "../helper"
+import {
+  createRaffle,
+  buyTicketsToken,
+  settleRaffle,
+  claimPrize,
+  createRaffloor,
+  initJackpotSeries,
+  rollJackpot,
+} from "../helpers/instructions"
+import { findRafflePda, nativeMint } from "../helpers/pdas"
+import { umi } from "../helpers/umi"
+import { expectFail, assertErrorCode, getTokenAmount, sleep } from "../helpers/utils"
+
+describe("Jackpot series", () => {
+  let authority: KeypairSigner
+  let raffler: PublicKey
+  let user: KeypairSigner
+  let jackpotSeries: PublicKey
+  let round1: PublicKey
+  let round2: PublicKey
+
+  async function newRound(numTickets: number) {
+    const entrants = generateSigner(umi)
+    await createRaffle({
+      prizeType: { pot: { winnerShare: 5000 } },
+      authority,
+      raffler,
+      entrants,
+      numTickets,
+      tokenMint: nativeMint,
+      entryType: { spend: {} },
+      ticketPrice: sol(0.1).basisPoints,
+      duration: 60 * 60 * 24,
+      claimWindow: 2,
+      unclaimedPrize: { reclaim: {} },
+      jackpotSeries,
+    })
+
+    return findRafflePda(entrants.publicKey)
+  }
+
+  before(async () => {
+    user = await createNewUser()
+    ;[authority, raffler] = await createRaffloor("Jackpot Raffle", "jackpot_raffle")
+  })
+
+  it("cannot start a series without a winner share", async () => {
+    await expectFail(
+      () => initJackpotSeries(authority, raffler, nativeMint, 0),
+      (err) => assertErrorCode(err, "InvalidWinnerShare")
+    )
+  })
+
+  it("can start a series and open its first round", async () => {
+    jackpotSeries = await initJackpotSeries(authority, raffler, nativeMint, 5000)
+    round1 = await newRound(2)
+
+    const seriesAcc = await adminProgram.account.jackpotSeries.fetch(jackpotSeries)
+    assert.equal(seriesAcc.rounds, 1, "Expected one round")
+    assert.equal(seriesAcc.currentRaffle.toBase58(), round1, "Expected round 1 to be the latest round")
+  })
+
+  it("cannot roll a round into itself", async () => {
+    await expectFail(
+      () => rollJackpot(authority, round1),
+      (err) => assertErrorCode(err, "NextRoundNotOpen")
+    )
+  })
+
+  it("cannot claim the pot once the claim window lapses", async () => {
+    await buyTicketsToken(user, round1, 2)
+    await settleRaffle(randomnessService, round1)
+    await sleep(3000)
+
+    await expectFail(
+      () => claimPrize(user, round1),
+      (err) => assertErrorCode(err, "ClaimWindowExpired")
+    )
+  })
+
+  it("rolls the unclaimed pot into the next round", async () => {
+    round2 = await newRound(10)
+    const pot = (await getTokenAmount(nativeMint, round1)) / 2n
+
+    await rollJackpot(authority, round1)
+
+    const round1Acc = await adminProgram.account.raffle.fetch(round1)
+    assert.ok(round1Acc.state.claimed, "Expected round 1 to be closed out")
+    assert.equal(round1Acc.carriedPot.toString(), "0", "Expected round 1 to hold no pot")
+
+    const round2Acc = await adminProgram.account.raffle.fetch(round2)
+    assert.equal(round2Acc.carriedPot.toString(), pot.toString(), "Expected the pot to carry into round 2")
+    assert.equal(await getTokenAmount(nativeMint, round2), pot, "Expected the pot to be in round 2's vault")
+  })
+})
@@ -0,0 +1,126 @@
+import * as anchor from "@coral-xyz/anchor"
+import { KeypairSigner, PublicKey, generateSigner, sol, tokenAmount } from "@metaplex-foundation/umi"
+import { assert } from "chai"
+import { randomnessService, adminProgram, createNewUser } from "../helper"
+import { createRaffle, buyTicketsToken, settleRaffle, claimPrize, createRaffloor } from "../helpers/instructions"
+import { findRafflePda, nativeMint } from "../helpers/pdas"
+import { umi } from "../helpers/umi"
+import { expectFail, assertErrorCode, getTokenAmount } from "../helpers/utils"
+import { createToken } from "../helpers/create-token"
+
+describe("Multiple winners", () => {
+  let entrants: KeypairSigner
+  let raffle: PublicKey
+  let authority: KeypairSigner
+  let raffler: PublicKey
+  let user1: KeypairSigner
+  let user2: KeypairSigner
+  let prizeToken: PublicKey
+  const prizeAmount = tokenAmount(90, "token", 6).basisPoints
+
+  before(async () => {
+    user1 = await createNewUser()
+    user2 = await createNewUser()
+    ;[authority, raffler] = await createRaffloor("Multi Winner Raffle", "multi_winner_raffle")
+    prizeToken = await createToken(umi, prizeAmount, 6, undefined, authority.publicKey)
+    entrants = generateSigner(umi)
+    raffle = findRafflePda(entrants.publicKey)
+  })
+
+  it("cannot create a raffle with no winners", async () => {
+    await expectFail(
+      () =>
+        createRaffle({
+          prizeType: { token: { amount: new anchor.BN(prizeAmount.toString()) } },
+          authority,
+          raffler,
+          entrants: generateSigner(umi),
+          numTickets: 10,
+          tokenMint: nativeMint,
+          entryType: { spend: {} },
+          ticketPrice: sol(0.1).basisPoints,
+          duration: 60 * 60 * 24,
+          prize: prizeToken,
+          numWinners: 0,
+        }),
+      (err) => assertErrorCode(err, "WinnersRequired")
+    )
+  })
+
+  it("can create a raffle with 3 winners", async () => {
+    await createRaffle({
+      prizeType: { token: { amount: new anchor.BN(prizeAmount.toString()) } },
+      authority,
+      raffler,
+      entrants,
+      numTickets: 10,
+      tokenMint: nativeMint,
+      entryType: { spend: {} },
+      ticketPrice: sol(0.1).basisPoints,
+      duration: 60 * 60 * 24,
+      prize: prizeToken,
+      numWinners: 3,
+    })
+
+    const raffleAcc = await adminProgram.account.raffle.fetch(raffle)
+    assert.equal(raffleAcc.numWinners, 3, "Expected 3 winners")
+  })
+
+  it("can sell out the raffle", async () => {
+    await buyTicketsToken(user1, raffle, 5)
+    await buyTicketsToken(user2, raffle, 5)
+
+    const entrantsAcc = await adminProgram.account.entrants.fetch(entrants.publicKey)
+    assert.equal(entrantsAcc.total, 10, "Expected 10 tickets to have been bought")
+  })
+
+  it("draws 3 distinct winning tickets", async () => {
+    await settleRaffle(randomnessService, raffle)
+
+    const raffleAcc = await adminProgram.account.raffle.fetch(raffle)
+    const indices = raffleAcc.winners.map((w) => w.ticketIndex)
+
+    assert.equal(indices.length, 3, "Expected 3 winners to be drawn")
+    assert.equal(new Set(indices).size, 3, "Expected each winning ticket to be different")
+    indices.forEach((index) => assert.ok(index < 10, "Expected winning tickets to be in range"))
+  })
+
+  it("cannot claim another winner's share", async () => {
+    const raffleAcc = await adminProgram.account.raffle.fetch(raffle)
+    const user2Win = raffleAcc.winners.find((w) => w.entrant.toBase58() === user2.publicKey)
+    const user1Win = raffleAcc.winners.find((w) => w.entrant.toBase58() === user1.publicKey)
+
+    // pick a share owned by whichever user didn't win it
+    const [claimer, share] = user2Win ? [user1, user2Win] : [user2, user1Win]
+
+    await expectFail(
+      () => claimPrize(claimer, raffle, share.ticketIndex),
+      (err) => assertErrorCode(err, "OnlyWinnerOrAdminCanSettle")
+    )
+  })
+
+  it("splits the prize between the winners", async () => {
+    const raffleAcc = await adminProgram.account.raffle.fetch(raffle)
+    const user1Before = await getTokenAmount(prizeToken, user1.publicKey)
+    const user2Before = await getTokenAmount(prizeToken, user2.publicKey)
+
+    for (const winner of raffleAcc.winners) {
+      const user = winner.entrant.toBase58() === user1.publicKey ? user1 : user2
+      await claimPrize(user, raffle, winner.ticketIndex)
+    }
+
+    const user1Won = raffleAcc.winners.filter((w) => w.entrant.toBase58() === user1.publicKey).length
+    const user1Gained = (await getTokenAmount(prizeToken, user1.publicKey)) - user1Before
+    const user2Gained = (await getTokenAmount(prizeToken, user2.publicKey)) - user2Before
+
+    assert.equal(user1Gained, (prizeAmount / 3n) * BigInt(user1Won), "Expected a third of the prize per winning ticket")
+    assert.equal(user1Gained + user2Gained, prizeAmount, "Expected the whole prize to be paid out")
+
+    const raffleAccAfter = await adminProgram.account.raffle.fetch(raffle)
+    assert.ok(raffleAccAfter.claimed, "Expected the raffle to be claimed once every share is paid")
+    assert.ok(
+      raffleAccAfter.winners.every((w) => w.claimed),
+      "Expected every share to be marked as claimed"
+    )
+  })
+})
@@ -0,0 +1,85 @@
+import { KeypairSigner, PublicKey, generateSigner, sol } from "@metaplex-foundation/umi"
+import { assert } from "chai"
+import { randomnessService, adminProgram, createNewUser } from "../helper"
+import { createRaffle, buyTicketsToken, settleRaffle, claimPrize, createRaffloor } from "../helpers/instructions"
+import { findRafflePda, getTokenAccount, nativeMint } from "../helpers/pdas"
+import { umi } from "../helpers/umi"
+import { expectFail, assertErrorCode, getTokenAmount, FEES_WALLET } from "../helpers/utils"
+
+describe("Pot raffle", () => {
+  let entrants: KeypairSigner
+  let raffle: PublicKey
+  let authority: KeypairSigner
+  let raffler: PublicKey
+  let user: KeypairSigner
+
+  before(async () => {
+    user = await createNewUser()
+    ;[authority, raffler] = await createRaffloor("Pot Raffle", "pot_raffle")
+    entrants = generateSigner(umi)
+    raffle = findRafflePda(entrants.publicKey)
+  })
+
+  it("cannot create a pot raffle without a winner share", async () => {
+    await expectFail(
+      () =>
+        createRaffle({
+          prizeType: { pot: { winnerShare: 0 } },
+          authority,
+          raffler,
+          entrants: generateSigner(umi),
+          numTickets: 4,
+          tokenMint: nativeMint,
+          entryType: { spend: {} },
+          ticketPrice: sol(0.1).basisPoints,
+          duration: 60 * 60 * 24,
+        }),
+      (err) => assertErrorCode(err, "InvalidWinnerShare")
+    )
+  })
+
+  it("can create a pot raffle paying out half the proceeds", async () => {
+    await createRaffle({
+      prizeType: { pot: { winnerShare: 5000 } },
+      authority,
+      raffler,
+      entrants,
+      numTickets: 4,
+      tokenMint: nativeMint,
+      entryType: { spend: {} },
+      ticketPrice: sol(0.1).basisPoints,
+      duration: 60 * 60 * 24,
+    })
+
+    const raffleAcc = await adminProgram.account.raffle.fetch(raffle)
+    assert.equal(raffleAcc.prize.toBase58(), nativeMint, "Expected the pot to be paid in the ticket mint")
+  })
+
+  it("pays the pot to the winner and splits the rest", async () => {
+    await buyTicketsToken(user, raffle, 4)
+    await settleRaffle(randomnessService, raffle)
+
+    const proceeds = await getTokenAmount(nativeMint, raffle)
+    const userBefore = await getTokenAmount(nativeMint, user.publicKey)
+    const authBefore = await getTokenAmount(nativeMint, authority.publicKey)
+    const feesBefore = await getTokenAmount(nativeMint, FEES_WALLET)
+
+    await claimPrize(user, raffle)
+
+    const pot = (proceeds * 5000n) / 10000n
+    const fee = ((proceeds - pot) * 500n) / 10000n
+
+    assert.equal((await getTokenAmount(nativeMint, user.publicKey)) - userBefore, pot, "Expected half to the winner")
+    assert.equal(
+      (await getTokenAmount(nativeMint, authority.publicKey)) - authBefore,
+      proceeds - pot - fee,
+      "Expected the rest less fees to the treasury"
+    )
+    assert.equal((await getTokenAmount(nativeMint, FEES_WALLET)) - feesBefore, fee, "Expected the fee on the rest")
+
+    const vault = await umi.rpc.getAccount(getTokenAccount(nativeMint, raffle))
+    assert.ok(!vault.exists, "Expected the proceeds vault to be closed")
+    const raffleAcc = await adminProgram.account.raffle.fetch(raffle)
+    assert.ok(raffleAcc.claimed, "Expected the pot to be claimed")
+  })
+})
@@ -0,0 +1,90 @@
+import { KeypairSigner, PublicKey, generateSigner, sol } from "@metaplex-foundation/umi"
+import { createAccount } from "@metaplex-foundation/mpl-toolbox"
+import { assert } from "chai"
+import { adminProgram } from "../helper"
+import { createRaffle, createRaffloor, deleteFeeOverride, setFeeOverride } from "../helpers/instructions"
+import { findFeeOverridePda, nativeMint } from "../helpers/pdas"
+import { umi } from "../helpers/umi"
+import { FEES_WALLET, STAKE_PROGRAM, expectFail, assertErrorCode } from "../helpers/utils"
+
+describe("Raffle fee", () => {
+  let authority: KeypairSigner
+  let raffler: PublicKey
+  const programFee = sol(0.01).basisPoints
+  const discountedFee = sol(0.002).basisPoints
+
+  // the fee the fees wallet receives for creating a raffle
+  async function rafflePays(authority: KeypairSigner, raffler: PublicKey) {
+    const balanceBefore = (await umi.rpc.getBalance(FEES_WALLET)).basisPoints
+
+    await createRaffle({
+      prizeType: { pot: { winnerShare: 5000 } },
+      authority,
+      raffler,
+      entrants: generateSigner(umi),
+      numTickets: 10,
+      tokenMint: nativeMint,
+      entryType: { spend: {} },
+      ticketPrice: sol(0.1).basisPoints,
+      duration: 60 * 60 * 24,
+    })
+
+    return (await umi.rpc.getBalance(FEES_WALLET)).basisPoints - balanceBefore
+  }
+
+  before(async () => {
+    ;[authority, raffler] = await createRaffloor("Fee Raffle", "fee_raffle")
+  })
+
+  it("charges the program fee", async () => {
+    assert.equal(await rafflePays(authority, raffler), programFee, "Expected the program fee to be charged")
+  })
+
+  it("cannot set a fee override as a non-admin", async () => {
+    await expectFail(
+      () => setFeeOverride(authority, raffler, discountedFee),
+      (err) => assertErrorCode(err, "AdminOnly")
+    )
+  })
+
+  it("charges the override instead of the program fee", async () => {
+    await setFeeOverride(undefined, raffler, discountedFee, adminProgram)
+
+    const feeOverride = await adminProgram.account.feeOverride.fetch(findFeeOverridePda(raffler))
+    assert.equal(feeOverride.raffleFee.toString(), discountedFee.toString(), "Expected the override to be stored")
+
+    assert.equal(await rafflePays(authority, raffler), discountedFee, "Expected the override to be charged")
+  })
+
+  it("cannot delete a fee override as a non-admin", async () => {
+    await expectFail(
+      () => deleteFeeOverride(authority, raffler),
+      (err) => assertErrorCode(err, "AdminOnly")
+    )
+  })
+
+  it("charges the program fee again once the override is deleted", async () => {
+    await deleteFeeOverride(undefined, raffler, adminProgram)
+
+    assert.ok(!(await umi.rpc.accountExists(findFeeOverridePda(raffler))), "Expected the override to be closed")
+    assert.equal(await rafflePays(authority, raffler), programFee, "Expected the program fee to be charged")
+  })
+
+  it("waives the fee for rafflers with a linked staker", async () => {
+    const staker = generateSigner(umi)
+    await createAccount(umi, {
+      newAccount: staker,
+      space: 0,
+      lamports: await umi.rpc.getRent(0),
+      programId: STAKE_PROGRAM,
+    }).sendAndConfirm(umi)
+    const [stakerAuthority, stakerRaffler] = await createRaffloor(
+      "Staker Raffle",
+      "staker_raffle",
+      undefined,
+      staker.publicKey
+    )
+
+    assert.equal(await rafflePays(stakerAuthority, stakerRaffler), 0n, "Expected the fee to be waived")
+  })
+})
@@ -0,0 +1,60 @@
+import { KeypairSigner, PublicKey, generateSigner, sol } from "@metaplex-foundation/umi"
+import { assert } from "chai"
+import { randomnessService, adminProgram, createNewUser } from "../helper"
+import { createRaffle, buyTicketsToken, settleRaffle, createRaffloor } from "../helpers/instructions"
+import { findRafflePda, nativeMint } from "../helpers/pdas"
+import { umi } from "../helpers/umi"
+import { expectFail, assertErrorCode } from "../helpers/utils"
+
+describe("Randomness requests", () => {
+  let entrants: KeypairSigner
+  let raffle: PublicKey
+  let authority: KeypairSigner
+  let raffler: PublicKey
+  let user: KeypairSigner
+
+  before(async () => {
+    user = await createNewUser()
+    ;[authority, raffler] = await createRaffloor("Request Raffle", "request_raffle")
+    entrants = generateSigner(umi)
+    raffle = findRafflePda(entrants.publicKey)
+
+    await createRaffle({
+      prizeType: { pot: { winnerShare: 5000 } },
+      authority,
+      raffler,
+      entrants,
+      numTickets: 2,
+      tokenMint: nativeMint,
+      entryType: { spend: {} },
+      ticketPrice: sol(0.1).basisPoints,
+      duration: 60 * 60 * 24,
+    })
+
+    await buyTicketsToken(user, raffle, 2)
+  })
+
+  it("has no request before the draw", async () => {
+    const raffleAcc = await adminProgram.account.raffle.fetch(raffle)
+    assert.isNull(raffleAcc.randomnessRequest, "Expected no pending request")
+    assert.equal(raffleAcc.requestSlot.toNumber(), 0, "Expected no request slot")
+  })
+
+  it("settles from the request made in draw_winner", async () => {
+    const slotBefore = await umi.rpc.getSlot()
+    await settleRaffle(randomnessService, raffle)
+
+    const raffleAcc = await adminProgram.account.raffle.fetch(raffle)
+    assert.ok(raffleAcc.state.drawn, "Expected the raffle to be drawn")
+    assert.isNotNull(raffleAcc.randomness, "Expected the randomness to be stored")
+    assert.isNull(raffleAcc.randomnessRequest, "Expected the request to be cleared once fulfilled")
+    assert.isAtLeast(raffleAcc.requestSlot.toNumber(), slotBefore, "Expected the request slot to be recorded")
+  })
+
+  it("cannot be drawn again once settled", async () => {
+    await expectFail(
+      () => settleRaffle(randomnessService, raffle, undefined, true),
+      (err) => assertErrorCode(err, "WinnerAlreadyDrawn")
+    )
+  })
+})
@@ -0,0 +1,67 @@
+import { assert } from "chai"
+import { RandomnessStream, winningTicketIndices } from "../helpers/utils"
+
+// vectors produced by utils::randomness_tools in the program, so draws can be audited off-chain
+describe("Winner selection", () => {
+  it("Expands randomness into a reproducible stream", () => {
+    const stream = new RandomnessStream(new Array(32).fill(0))
+    const words = Array.from({ length: 5 }, () => stream.nextU64().toString())
+
+    assert.deepEqual(words, [
+      "15589685029427180148",
+      "13335784868504167063",
+      "18176509182764399806",
+      "1958065747258641393",
+      "1983417590045772508",
+    ])
+  })
+
+  it("Derives the same winning tickets as the program", () => {
+    const sequential = Array.from({ length: 32 }, (_, i) => i)
+
+    assert.deepEqual(winningTicketIndices(sequential, 1000, 5), [99, 174, 875, 231, 486])
+    assert.deepEqual(winningTicketIndices(new Array(32).fill(7), 3_000_000_000, 3), [
+      534839736, 995315358, 2649050750,
+    ])
+  })
+
+  it("Picks every ticket once when all tickets win", () => {
+    const winners = winningTicketIndices(new Array(32).fill(255), 10, 10)
+
+    assert.deepEqual(winners, [7, 1, 4, 5, 9, 8, 0, 2, 6, 3])
+  })
+
+  it("Skips excluded tickets like the program", () => {
+    const sequential = Array.from({ length: 32 }, (_, i) => i)
+
+    assert.deepEqual(winningTicketIndices(sequential, 1000, 5, [99, 875]), [174, 231, 486, 91, 513])
+    assert.deepEqual(winningTicketIndices(new Array(32).fill(255), 10, 10, [7, 3]), [1, 4, 5, 9, 8, 0, 2, 6])
+  })
+
+  it("Re-draws when a forfeited winner is excluded twice", () => {
+    // 2 tickets, the unclaimed winner is both a current winner and forfeited
+    assert.deepEqual(winningTicketIndices(new Array(32).fill(3), 2, 1, [1, 1]), [0])
+
+    const winners = winningTicketIndices(new Array(32).fill(42), 5, 2, [0, 1, 0, 1])
+    assert.equal(winners.length, 2)
+    winners.forEach((winner) => assert.include([2, 3, 4], winner))
+  })
+
+  it("Selects tickets uniformly", () => {
+    const buckets = 7
+    const samples = 700_000
+    const counts = new Array(buckets).fill(0)
+    const stream = new RandomnessStream(new Array(32).fill(9))
+
+    for (let i = 0; i < samples; i++) {
+      counts[stream.nextBelow(buckets)]++
+    }
+
+    assert.deepEqual(counts, [99833, 100082, 100155, 100179, 99810, 99910, 100031])
+
+    // chi-squared critical value for 6 degrees of freedom at p = 0.001
+    const expected = samples / buckets
+    const chiSquared = counts.reduce((sum, count) => sum + (count - expected) ** 2 / expected, 0)
+    assert.isBelow(chiSquared, 22.458)
+  })
+})
@@ -0,0 +1,80 @@
+import * as anchor from "@coral-xyz/anchor"
+import { KeypairSigner, PublicKey, generateSigner, sol, tokenAmount } from "@metaplex-foundation/umi"
+import { assert } from "chai"
+import { adminProgram, createNewUser } from "../helper"
+import { createRaffle, buyTicketsToken, claimPrize, claimRefund, createRaffloor } from "../helpers/instructions"
+import { findEntrantTallyPda, findRafflePda, nativeMint } from "../helpers/pdas"
+import { umi } from "../helpers/umi"
+import { expectFail, assertErrorCode, getTokenAmount, sleep } from "../helpers/utils"
+import { createToken } from "../helpers/create-token"
+
+describe("Refunds", () => {
+  let entrants: KeypairSigner
+  let raffle: PublicKey
+  let authority: KeypairSigner
+  let raffler: PublicKey
+  let user: KeypairSigner
+  let prizeToken: PublicKey
+  const prizeAmount = tokenAmount(100, "token", 6).basisPoints
+
+  before(async () => {
+    user = await createNewUser()
+    ;[authority, raffler] = await createRaffloor("Refund Raffle", "refund_raffle")
+    prizeToken = await createToken(umi, prizeAmount, 6, undefined, authority.publicKey)
+    entrants = generateSigner(umi)
+    raffle = findRafflePda(entrants.publicKey)
+
+    await createRaffle({
+      prizeType: { token: { amount: new anchor.BN(prizeAmount.toString()) } },
+      authority,
+      raffler,
+      entrants,
+      numTickets: 10,
+      tokenMint: nativeMint,
+      entryType: { spend: {} },
+      ticketPrice: sol(0.1).basisPoints,
+      duration: 3,
+      prize: prizeToken,
+      minTickets: 5,
+    })
+
+    await buyTicketsToken(user, raffle, 2)
+  })
+
+  it("cannot claim a refund before the raffle ends", async () => {
+    await expectFail(
+      () => claimRefund(user, raffle),
+      (err) => assertErrorCode(err, "RaffleNotRefunding")
+    )
+  })
+
+  it("can claim a refund once the raffle ends below its minimum tickets", async () => {
+    await sleep(4000)
+    const balanceBefore = await getTokenAmount(nativeMint, user.publicKey)
+
+    await claimRefund(user, raffle)
+
+    const balanceAfter = await getTokenAmount(nativeMint, user.publicKey)
+    assert.equal(balanceAfter - balanceBefore, 2n * sol(0.1).basisPoints, "Expected both tickets to be refunded")
+
+    const raffleAcc = await adminProgram.account.raffle.fetch(raffle)
+    assert.ok(raffleAcc.state.refunding, "Expected the raffle to be refunding")
+
+    const tally = await umi.rpc.getAccount(findEntrantTallyPda(raffle, user.publicKey))
+    assert.ok(!tally.exists, "Expected the entrant tally to be closed")
+  })
+
+  it("cannot claim a refund twice", async () => {
+    await expectFail(
+      () => claimRefund(user, raffle),
+      (err) => assertErrorCode(err, "AccountNotInitialized")
+    )
+  })
+
+  it("returns the prize to the raffler", async () => {
+    await claimPrize(authority, raffle)
+
+    const balance = await getTokenAmount(prizeToken, authority.publicKey)
+    assert.equal(balance, prizeAmount, "Expected the prize to be returned to the raffler")
+  })
+})
@@ -0,0 +1,85 @@
+import * as anchor from "@coral-xyz/anchor"
+import { KeypairSigner, PublicKey, generateSigner, sol } from "@metaplex-foundation/umi"
+import { assert } from "chai"
+import { randomnessService, adminProgram, createNewUser } from "../helper"
+import { createRaffle, buyTicketsToken, settleRaffle, claimPrize, createRaffloor } from "../helpers/instructions"
+import { findPrizeVaultPda, findRafflePda, nativeMint } from "../helpers/pdas"
+import { umi } from "../helpers/umi"
+import { TX_FEE, expectFail, assertErrorCode } from "../helpers/utils"
+
+describe("SOL prize", () => {
+  let entrants: KeypairSigner
+  let raffle: PublicKey
+  let authority: KeypairSigner
+  let raffler: PublicKey
+  let user: KeypairSigner
+
+  before(async () => {
+    user = await createNewUser()
+    ;[authority, raffler] = await createRaffloor("SOL Prize Raffle", "sol_prize_raffle")
+    entrants = generateSigner(umi)
+    raffle = findRafflePda(entrants.publicKey)
+  })
+
+  it("cannot create a raffle with an empty SOL prize", async () => {
+    await expectFail(
+      () =>
+        createRaffle({
+          prizeType: { sol: { lamports: new anchor.BN(0) } },
+          authority,
+          raffler,
+          entrants: generateSigner(umi),
+          numTickets: 2,
+          tokenMint: nativeMint,
+          entryType: { spend: {} },
+          ticketPrice: sol(0.1).basisPoints,
+          duration: 60 * 60 * 24,
+        }),
+      (err) => assertErrorCode(err, "InvalidPrize")
+    )
+  })
+
+  it("can create a raffle with a SOL prize", async () => {
+    await createRaffle({
+      prizeType: { sol: { lamports: new anchor.BN(sol(1).basisPoints.toString()) } },
+      authority,
+      raffler,
+      entrants,
+      numTickets: 2,
+      tokenMint: nativeMint,
+      entryType: { spend: {} },
+      ticketPrice: sol(0.1).basisPoints,
+      duration: 60 * 60 * 24,
+    })
+
+    const vaultBalance = await umi.rpc.getBalance(findPrizeVaultPda(raffle))
+    assert.ok(vaultBalance.basisPoints > sol(1).basisPoints, "Expected the prize and vault rent to be escrowed")
+
+    const raffleAcc = await adminProgram.account.raffle.fetch(raffle)
+    assert.equal(raffleAcc.prize.toBase58(), findPrizeVaultPda(raffle), "Expected the prize to be the vault")
+  })
+
+  it("can sell out and settle the raffle", async () => {
+    await buyTicketsToken(user, raffle, 2)
+    await settleRaffle(randomnessService, raffle)
+
+    const raffleAcc = await adminProgram.account.raffle.fetch(raffle)
+    assert.equal(raffleAcc.winners[0].entrant.toBase58(), user.publicKey, "Expected user to have won")
+  })
+
+  it("pays the SOL prize to the winner", async () => {
+    const balanceBefore = await umi.rpc.getBalance(user.publicKey)
+
+    await claimPrize(user, raffle)
+
+    const balanceAfter = await umi.rpc.getBalance(user.publicKey)
+    assert.equal(
+      balanceAfter.basisPoints - balanceBefore.basisPoints,
+      sol(1).basisPoints - TX_FEE,
+      "Expected the winner to receive 1 SOL less the tx fee"
+    )
+
+    const vaultBalance = await umi.rpc.getBalance(findPrizeVaultPda(raffle))
+    assert.equal(vaultBalance.basisPoints, 0n, "Expected the prize vault to be emptied")
+  })
+})
@@ -0,0 +1,109 @@
+import * as anchor from "@coral-xyz/anchor"
+import { KeypairSigner, PublicKey, generateSigner, tokenAmount, transactionBuilder } from "@metaplex-foundation/umi"
+import { createAssociatedToken, mintTokensTo } from "@metaplex-foundation/mpl-toolbox"
+import { assert } from "chai"
+import { randomnessService, adminProgram, createNewUser } from "../helper"
+import {
+  createRaffle,
+  settleRaffle,
+  claimPrize,
+  createRaffloor,
+  stakeToken,
+  unstakeToken,
+} from "../helpers/instructions"
+import { findRafflePda, findStakeRecordPda, getTokenAccount } from "../helpers/pdas"
+import { umi } from "../helpers/umi"
+import { expectFail, assertErrorCode, getTokenAmount } from "../helpers/utils"
+import { createToken } from "../helpers/create-token"
+
+describe("Token stake raffle", () => {
+  let entrants: KeypairSigner
+  let raffle: PublicKey
+  let authority: KeypairSigner
+  let raffler: PublicKey
+  let user1: KeypairSigner
+  let user2: KeypairSigner
+  let stakeMint: PublicKey
+  let prizeToken: PublicKey
+  const ticketPrice = tokenAmount(10, "token", 6).basisPoints
+  const balance = tokenAmount(100, "token", 6).basisPoints
+  const prizeAmount = tokenAmount(100, "token", 6).basisPoints
+
+  before(async () => {
+    user1 = await createNewUser()
+    user2 = await createNewUser()
+    ;[authority, raffler] = await createRaffloor("Stake Raffle", "stake_raffle")
+    prizeToken = await createToken(umi, prizeAmount, 6, undefined, authority.publicKey)
+    stakeMint = await createToken(umi, balance, 6, undefined, user1.publicKey)
+    await transactionBuilder()
+      .add(createAssociatedToken(umi, { mint: stakeMint, owner: user2.publicKey }))
+      .add(mintTokensTo(umi, { mint: stakeMint, token: getTokenAccount(stakeMint, user2.publicKey), amount: balance }))
+      .sendAndConfirm(umi)
+
+    entrants = generateSigner(umi)
+    raffle = findRafflePda(entrants.publicKey)
+
+    await createRaffle({
+      prizeType: { token: { amount: new anchor.BN(prizeAmount.toString()) } },
+      authority,
+      raffler,
+      entrants,
+      numTickets: 4,
+      tokenMint: stakeMint,
+      entryType: { stake: { minimumPeriod: new anchor.BN(0) } },
+      ticketPrice,
+      duration: 60 * 60 * 24,
+      prize: prizeToken,
+    })
+  })
+
+  it("locks the stake in escrow for tickets", async () => {
+    await stakeToken(user1, raffle, 2)
+    await stakeToken(user2, raffle, 2)
+
+    assert.equal(
+      await getTokenAmount(stakeMint, user1.publicKey),
+      balance - 2n * ticketPrice,
+      "Expected the stake to leave the wallet"
+    )
+    assert.equal(await getTokenAmount(stakeMint, raffle), 4n * ticketPrice, "Expected both stakes in escrow")
+
+    const stakeRecord = await adminProgram.account.stakeRecord.fetch(findStakeRecordPda(raffle, user1.publicKey, stakeMint))
+    assert.equal(stakeRecord.amount.toString(), (2n * ticketPrice).toString(), "Expected the stake to be recorded")
+  })
+
+  it("cannot withdraw a stake before the draw", async () => {
+    await expectFail(
+      () => unstakeToken(user1, raffle, stakeMint),
+      (err) => assertErrorCode(err, "NotDrawn")
+    )
+  })
+
+  it("returns a losing stake once drawn", async () => {
+    await settleRaffle(randomnessService, raffle)
+    const raffleAcc = await adminProgram.account.raffle.fetch(raffle)
+    const loser = raffleAcc.winners[0].entrant.toBase58() === user1.publicKey ? user2 : user1
+
+    await unstakeToken(loser, raffle, stakeMint)
+
+    assert.equal(await getTokenAmount(stakeMint, loser.publicKey), balance, "Expected the whole stake back")
+    const stakeRecord = await umi.rpc.getAccount(findStakeRecordPda(raffle, loser.publicKey, stakeMint))
+    assert.ok(!stakeRecord.exists, "Expected the stake record to be closed")
+  })
+
+  it("holds the winning stake until the prize is claimed", async () => {
+    const raffleAcc = await adminProgram.account.raffle.fetch(raffle)
+    const winner = raffleAcc.winners[0].entrant.toBase58() === user1.publicKey ? user1 : user2
+
+    await expectFail(
+      () => unstakeToken(winner, raffle, stakeMint),
+      (err) => assertErrorCode(err, "WinnerStakeLocked")
+    )
+
+    await claimPrize(winner, raffle)
+    await unstakeToken(winner, raffle, stakeMint)
+
+    assert.equal(await getTokenAmount(stakeMint, winner.publicKey), balance, "Expected the whole stake back")
+    assert.equal(await getTokenAmount(prizeToken, winner.publicKey), prizeAmount, "Expected the prize to be paid")
+  })
+})
@@ -0,0 +1,87 @@
+import * as anchor from "@coral-xyz/anchor"
+import { KeypairSigner, PublicKey, generateSigner, tokenAmount } from "@metaplex-foundation/umi"
+import { fromWeb3JsPublicKey, toWeb3JsPublicKey } from "@metaplex-foundation/umi-web3js-adapters"
+import { TOKEN_2022_PROGRAM_ID, getAccount } from "@solana/spl-token"
+import { assert } from "chai"
+import { randomnessService, adminProgram, createNewUser } from "../helper"
+import { createRaffle, buyTicketsToken, settleRaffle, claimPrize, createRaffloor } from "../helpers/instructions"
+import { findEntrantTallyPda, findRafflePda, getTokenAccount } from "../helpers/pdas"
+import { umi } from "../helpers/umi"
+import { FEES_WALLET, getEvents } from "../helpers/utils"
+import { createToken } from "../helpers/create-token"
+import { createTransferFeeToken } from "../helpers/create-token-2022"
+
+describe("Token-2022 raffle", () => {
+  let entrants: KeypairSigner
+  let raffle: PublicKey
+  let authority: KeypairSigner
+  let raffler: PublicKey
+  let user: KeypairSigner
+  let tokenMint: PublicKey
+  const ticketPrice = tokenAmount(100, "token", 6).basisPoints
+  const prizeAmount = tokenAmount(100, "token", 6).basisPoints
+
+  // balances of Token-2022 accounts, less anything withheld as transfer fees
+  async function balance(owner: PublicKey) {
+    const token = toWeb3JsPublicKey(getTokenAccount(tokenMint, owner, fromWeb3JsPublicKey(TOKEN_2022_PROGRAM_ID)))
+    const account = await getAccount(adminProgram.provider.connection, token, "confirmed", TOKEN_2022_PROGRAM_ID)
+    return account.amount
+  }
+
+  // transfers withhold 1%, and every amount here divides evenly
+  const afterTransferFee = (amount: bigint) => amount - amount / 100n
+
+  before(async () => {
+    user = await createNewUser()
+    ;[authority, raffler] = await createRaffloor("Token-2022 Raffle", "token_2022_raffle")
+    tokenMint = await createTransferFeeToken(100, 10n * ticketPrice, 6, [user.publicKey])
+    const prizeToken = await createToken(umi, prizeAmount, 6, undefined, authority.publicKey)
+    entrants = generateSigner(umi)
+    raffle = findRafflePda(entrants.publicKey)
+
+    await createRaffle({
+      prizeType: { token: { amount: new anchor.BN(prizeAmount.toString()) } },
+      authority,
+      raffler,
+      entrants,
+      numTickets: 2,
+      tokenMint,
+      entryType: { spend: {} },
+      ticketPrice,
+      duration: 60 * 60 * 24,
+      prize: prizeToken,
+    })
+  })
+
+  it("keeps only what reached the vault after the transfer fee", async () => {
+    await buyTicketsToken(user, raffle, 2)
+
+    assert.equal(await balance(user.publicKey), 8n * ticketPrice, "Expected the full price to leave the wallet")
+    assert.equal(await balance(raffle), afterTransferFee(2n * ticketPrice), "Expected the fee to be withheld")
+
+    const tally = await adminProgram.account.entrantTally.fetch(findEntrantTallyPda(raffle, user.publicKey))
+    assert.equal(
+      tally.paid.toString(),
+      afterTransferFee(2n * ticketPrice).toString(),
+      "Expected the tally to record what the vault received"
+    )
+  })
+
+  it("takes the proceeds share from what the vault received", async () => {
+    await settleRaffle(randomnessService, raffle)
+    const sig = await claimPrize(user, raffle)
+
+    const proceeds = afterTransferFee(2n * ticketPrice)
+    const fee = (proceeds * 500n) / 10_000n
+    const [event] = (await getEvents(sig)).filter((e) => e.name === "PrizeClaimed")
+    assert.equal(event.data.fee.toString(), fee.toString(), "Expected the share of the received proceeds")
+    assert.equal(event.data.treasuryProceeds.toString(), (proceeds - fee).toString(), "Expected the rest to the treasury")
+
+    assert.equal(await balance(FEES_WALLET), afterTransferFee(fee), "Expected the fees wallet to receive its share")
+    assert.equal(
+      await balance(authority.publicKey),
+      afterTransferFee(proceeds - fee),
+      "Expected the treasury to receive the rest"
+    )
+  })
+})
@@ -0,0 +1,68 @@
+import { KeypairSigner, PublicKey, generateSigner, sol } from "@metaplex-foundation/umi"
+import { assert } from "chai"
+import { adminProgram, createNewUser } from "../helper"
+import { createRaffle, buyTicketsToken, createRaffloor } from "../helpers/instructions"
+import { findEntrantTallyPda, findRafflePda, nativeMint } from "../helpers/pdas"
+import { umi } from "../helpers/umi"
+import { expectFail, assertErrorCode } from "../helpers/utils"
+
+describe("Per-wallet ticket cap", () => {
+  let entrants: KeypairSigner
+  let raffle: PublicKey
+  let authority: KeypairSigner
+  let raffler: PublicKey
+  let user1: KeypairSigner
+  let user2: KeypairSigner
+
+  before(async () => {
+    user1 = await createNewUser()
+    user2 = await createNewUser()
+    ;[authority, raffler] = await createRaffloor("Capped Raffle", "capped_raffle")
+    entrants = generateSigner(umi)
+    raffle = findRafflePda(entrants.publicKey)
+
+    // 50% of 10 tickets, tightened to 3 by the absolute limit
+    await createRaffle({
+      prizeType: { pot: { winnerShare: 5000 } },
+      authority,
+      raffler,
+      entrants,
+      numTickets: 10,
+      tokenMint: nativeMint,
+      entryType: { spend: {} },
+      ticketPrice: sol(0.1).basisPoints,
+      duration: 60 * 60 * 24,
+      maxEntriesPct: 5000,
+      maxEntrantTickets: 3,
+    })
+  })
+
+  it("can buy up to the cap across purchases", async () => {
+    await buyTicketsToken(user1, raffle, 2)
+    await buyTicketsToken(user1, raffle, 1)
+
+    const tally = await adminProgram.account.entrantTally.fetch(findEntrantTallyPda(raffle, user1.publicKey))
+    assert.equal(tally.tickets, 3, "Expected the tally to count both purchases")
+  })
+
+  it("cannot buy past the cap", async () => {
+    await expectFail(
+      () => buyTicketsToken(user1, raffle, 1),
+      (err) => assertErrorCode(err, "MaxTicketsPerWallet")
+    )
+  })
+
+  it("cannot buy past the cap in a single purchase", async () => {
+    await expectFail(
+      () => buyTicketsToken(user2, raffle, 4),
+      (err) => assertErrorCode(err, "MaxTicketsPerWallet")
+    )
+  })
+
+  it("caps each wallet separately", async () => {
+    await buyTicketsToken(user2, raffle, 3)
+
+    const entrantsAcc = await adminProgram.account.entrants.fetch(entrants.publicKey)
+    assert.equal(entrantsAcc.total, 6, "Expected both wallets' tickets to be sold")
+  })
+})
@@ -0,0 +1,81 @@
+import { KeypairSigner, PublicKey, generateSigner, sol } from "@metaplex-foundation/umi"
+import { assert } from "chai"
+import { randomnessService, adminProgram, createNewUser } from "../helper"
+import { createRaffle, buyTicketsToken, settleRaffle, claimPrize, createRaffloor } from "../helpers/instructions"
+import { findRafflePda, nativeMint } from "../helpers/pdas"
+import { umi } from "../helpers/umi"
+import { expectFail, assertErrorCode, getEntrantsArray, winningTicketIndices } from "../helpers/utils"
+
+describe("Winner recording", () => {
+  let entrants: KeypairSigner
+  let raffle: PublicKey
+  let authority: KeypairSigner
+  let raffler: PublicKey
+  let user1: KeypairSigner
+  let user2: KeypairSigner
+  let winningTicket: number
+
+  before(async () => {
+    user1 = await createNewUser()
+    user2 = await createNewUser()
+    ;[authority, raffler] = await createRaffloor("Recorded Raffle", "recorded_raffle")
+    entrants = generateSigner(umi)
+    raffle = findRafflePda(entrants.publicKey)
+
+    await createRaffle({
+      prizeType: { pot: { winnerShare: 5000 } },
+      authority,
+      raffler,
+      entrants,
+      numTickets: 5,
+      tokenMint: nativeMint,
+      entryType: { spend: {} },
+      ticketPrice: sol(0.1).basisPoints,
+      duration: 60 * 60 * 24,
+    })
+
+    await buyTicketsToken(user1, raffle, 3)
+    await buyTicketsToken(user2, raffle, 2)
+  })
+
+  it("records the winning ticket and its owner when the draw settles", async () => {
+    await settleRaffle(randomnessService, raffle)
+
+    const raffleAcc = await adminProgram.account.raffle.fetch(raffle)
+    assert.equal(raffleAcc.winners.length, 1, "Expected a single winner")
+
+    winningTicket = raffleAcc.winners[0].ticketIndex
+    assert.equal(
+      winningTicket,
+      winningTicketIndices(raffleAcc.randomness, 5, 1)[0],
+      "Expected the winning ticket to be derived from the randomness"
+    )
+
+    const entrantsArray = await getEntrantsArray(entrants.publicKey)
+    assert.equal(
+      raffleAcc.winners[0].entrant.toBase58(),
+      entrantsArray[winningTicket],
+      "Expected the winner to own the winning ticket"
+    )
+    assert.ok(!raffleAcc.winners[0].claimed, "Expected the prize to be unclaimed")
+  })
+
+  it("cannot claim with a ticket that didn't win", async () => {
+    const losingTicket = (winningTicket + 1) % 5
+
+    await expectFail(
+      () => claimPrize(user1, raffle, losingTicket),
+      (err) => assertErrorCode(err, "TicketNotWinner")
+    )
+  })
+
+  it("pays the recorded winner without them passing their ticket", async () => {
+    const raffleAcc = await adminProgram.account.raffle.fetch(raffle)
+    const winner = raffleAcc.winners[0].entrant.toBase58() === user1.publicKey ? user1 : user2
+
+    await claimPrize(winner, raffle)
+
+    const raffleAccAfter = await adminProgram.account.raffle.fetch(raffle)
+    assert.ok(raffleAccAfter.winners[0].claimed, "Expected the prize to be claimed")
+  })
+})